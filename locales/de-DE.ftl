@@ -19,8 +19,14 @@ file-no-file-specified = Keine Datei angegeben
 file-opened-success = ✅ Geöffnet: { $filename }
 file-new-created = 🆕 Neue Datei '{ $filename }' erstellt - bereit zum Bearbeiten!
 file-unsaved-changes = Datei hat ungespeicherte Änderungen! Drücken Sie Ctrl+Q erneut, um ohne Speichern zu beenden.
-file-save-before-new = Aktuelle Datei vor Erstellen einer neuen speichern (Ctrl+S)
-file-save-before-open = Aktuelle Datei vor Öffnen einer neuen speichern (Ctrl+S)
+status-buffers-unsaved = { $count } Dateien haben ungespeicherte Änderungen! Drücken Sie Ctrl+Q erneut, um ohne Speichern zu beenden.
+status-buffer-unsaved = { $filename } hat ungespeicherte Änderungen! Erneut schließen, um sie zu verwerfen.
+status-buffer-closed = { $filename } geschlossen
+status-buffer-switched = { $filename } ({ $index }/{ $count })
+status-files-opened = { $count } Dateien geöffnet
+status-pane-split = Bereich geteilt
+status-pane-closed = Bereich geschlossen
+status-last-pane = Der letzte Bereich kann nicht geschlossen werden
 
 # Willkommensinhalt
 welcome-title = Willkommen im IOCraft Erweiterten Texteditor!
@@ -33,8 +39,8 @@ welcome-feature-shortcuts = ⌨️ Tastenkombinationen im Sublime Text-Stil
 welcome-feature-linting = 🔍 Echtzeit-Code-Linting und Problemerkennung
 welcome-shortcuts = Tastenkombinationen:
 welcome-shortcuts-file = 📄 Datei: Ctrl+O (öffnen), Ctrl+S (speichern), Ctrl+N (neu)
-welcome-shortcuts-edit = ✂️ Bearbeiten: Ctrl+D (Zeile duplizieren), Ctrl+K (Zeile löschen)
-welcome-shortcuts-navigate = 🔍 Navigation: Ctrl+Home/End (Dokument), Home/End (Zeile)
+welcome-shortcuts-edit = ✂️ Bearbeiten: Ctrl+D (Zeile duplizieren), Ctrl+K (Zeile löschen), Ctrl+Z/Y (rückgängig/wiederholen), Ctrl+C/X/V (kopieren/ausschneiden/einfügen)
+welcome-shortcuts-navigate = 🔍 Navigation: Ctrl+Home/End (Dokument), Home/End (Zeile), Ctrl+F (suchen), Ctrl+R (ersetzen)
 welcome-shortcuts-view = 🎨 Ansicht: Ctrl+H (Hervorhebung umschalten), Ctrl+E (Linting umschalten)
 welcome-shortcuts-quit = 🚪 Beenden: Ctrl+Q
 welcome-start-editing = Beginnen Sie hier zu bearbeiten...
@@ -45,23 +51,98 @@ status-syntax-enabled = Syntaxhervorhebung aktiviert
 status-syntax-disabled = Syntaxhervorhebung deaktiviert
 status-linting-enabled = Code-Linting aktiviert
 status-linting-disabled = Code-Linting deaktiviert
+status-soft-wrap-enabled = Weicher Zeilenumbruch aktiviert
+status-soft-wrap-disabled = Weicher Zeilenumbruch deaktiviert
 status-new-file-created = Neue Datei erstellt
 status-document-start = Dokumentanfang
 status-document-end = Dokumentende
-status-goto-line-soon = Gehe zu Zeile: (Funktion kommt bald)
+goto-prompt = Gehe zu Zeile: { $input }  (Zeile, Zeile:Spalte, +N/-N, N%)
+goto-invalid = Ungültige Zeile: { $input }
+goto-canceled = Gehe zu Zeile abgebrochen
 status-line-duplicated = Zeile dupliziert
 status-line-deleted = Zeile gelöscht
 status-line-cleared = Zeile geleert
+status-undo = Rückgängig
+status-redo = Wiederholen
+status-nothing-to-undo = Nichts rückgängig zu machen
+status-nothing-to-redo = Nichts zu wiederholen
+status-copied = { $count } Zeichen kopiert
+status-copied-internal = { $count } Zeichen kopiert, zu viele für die System-Zwischenablage; sie lassen sich nur in diesem Editor einfügen
+status-cut = Auswahl in die Zwischenablage ausgeschnitten
+status-pasted = { $count ->
+    [one] { $count } Zeile eingefügt
+   *[other] { $count } Zeilen eingefügt
+}
+status-nothing-selected = Nichts ausgewählt
+status-clipboard-empty = Die Zwischenablage ist leer
+status-clipboard-error = Fehler der Zwischenablage: { $error }
 status-cursor-moved = Cursor bewegt zu Zeile { $row }, Spalte { $col }
 status-word-selected = Wort ausgewählt (Doppelklick)
 status-text-selected = { $count } Zeichen ausgewählt
+status-carets = { $count } Cursor
+status-all-occurrences-selected = Alle Vorkommen sind ausgewählt
+status-keys-pending = { $keys } gedrückt, warte auf die nächste Taste...
+status-keymap-error = { $count ->
+    [one] Fehler in der Tastenbelegung: { $error }
+   *[other] { $count } Fehler in der Tastenbelegung, erster: { $error }
+}
+theme-picker-prompt = Design: { $name } ({ $index }/{ $count })  ↑/↓ Vorschau, Enter übernehmen, Esc abbrechen
+theme-applied = Design auf { $name } gesetzt
+theme-not-saved = Design auf { $name } gesetzt, konnte aber nicht gespeichert werden: { $error }
+theme-picker-canceled = Design unverändert
+language-picker-prompt = Sprache: { $query } → { $name } ({ $index }/{ $count })  ↑/↓ wählen, Enter übernehmen, Esc abbrechen
+language-picker-no-match = Sprache: { $query } keine passende Sprache
+language-picker-detect = Automatisch erkennen
+language-picker-canceled = Sprache unverändert
+language-set = Sprache auf { $name } gesetzt
+language-detected = Sprache als { $name } erkannt
+language-not-saved = Sprache auf { $name } gesetzt, konnte aber nicht gespeichert werden: { $error }
+ui-language-picked = { $name } (gewählt)
+quick-fix-prompt = Korrektur: { $title } ({ $index }/{ $count })  ↑/↓ wählen, Enter übernehmen, Esc abbrechen
+quick-fix-none = Keine Schnellkorrekturen in dieser Zeile
+quick-fix-canceled = Schnellkorrektur abgebrochen
+quick-fix-applied = Übernommen: { $title }
+lint-issue-at-cursor = { $rule }: { $message }
+fix-all-none = Nichts zu korrigieren
+fix-all-applied = { $count ->
+    [one] 1 Korrektur übernommen
+   *[other] { $count } Korrekturen übernommen
+}
+status-theme-error = { $count ->
+    [one] Designfehler: { $error }
+   *[other] { $count } Designfehler, erster: { $error }
+}
+status-language-error = { $count ->
+    [one] Fehler in der Sprachkonfiguration: { $error }
+   *[other] { $count } Fehler in der Sprachkonfiguration, erster: { $error }
+}
+status-vim-enabled = Vim-Modus aktiviert
+status-vim-disabled = Vim-Modus deaktiviert
+status-mark-set = Marke gesetzt
+status-kill-ring-empty = Kill-Ring ist leer
+status-yank-pop-without-yank = Der vorherige Befehl war kein Einfügen (Yank)
+vim-mode-insert = -- EINFÜGEN --
+vim-mode-visual = -- VISUELL --
+vim-mode-visual-line = -- VISUELL ZEILE --
 status-selecting = Text wird ausgewählt...
 status-scrolled-up = Nach oben gescrollt
 status-scrolled-down = Nach unten gescrollt
+status-scrolled-left = Nach links gescrollt
+status-scrolled-right = Nach rechts gescrollt
+
+# Suchen und Ersetzen
+search-prompt = Suchen: { $query }
+search-prompt-backward = Rückwärts suchen: { $query }
+replace-prompt = Ersetzen: { $query } → { $replacement }
+search-match-position = Treffer { $current } von { $total }
+search-no-matches = keine Treffer
+search-invalid-regex = ungültiger regulärer Ausdruck: { $error }
+search-canceled = Suche abgebrochen
+search-replaced-all = { $count } Vorkommen ersetzt
 
 # Kontextmenü
-context-menu-copy-cut-paste = Kontextmenü: Kopieren/Ausschneiden/Einfügen verfügbar bei Zeile { $row }, Spalte { $col }
-context-menu-paste = Kontextmenü: Einfügen verfügbar bei Zeile { $row }, Spalte { $col }
+context-menu-copy-cut-paste = Kontextmenü: Kopieren (Ctrl+C) / Ausschneiden (Ctrl+X) / Einfügen (Ctrl+V) verfügbar bei Zeile { $row }, Spalte { $col }
+context-menu-paste = Kontextmenü: Einfügen (Ctrl+V) verfügbar bei Zeile { $row }, Spalte { $col }
 
 # Dialogsystem
 dialog-open-file = 📂 Datei Öffnen - IOCraft Dateibrowser
@@ -109,4 +190,4 @@ error-file-operation = Dateioperation Fehler: { $error }
 error-general = Fehler: { $message }
 
 # Beenden-Eingabeaufforderung
-exit-prompt = Drücken Sie Ctrl+C zum Beenden, falls nötig
+exit-prompt = Drücken Sie Ctrl+Q zum Beenden
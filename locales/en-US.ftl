@@ -19,8 +19,14 @@ file-no-file-specified = No file specified
 file-opened-success = ✅ Opened: { $filename }
 file-new-created = 🆕 New file '{ $filename }' created - ready for editing!
 file-unsaved-changes = File has unsaved changes! Press Ctrl+Q again to quit without saving.
-file-save-before-new = Save current file before creating new one (Ctrl+S)
-file-save-before-open = Save current file before opening new one (Ctrl+S)
+status-buffers-unsaved = { $count } files have unsaved changes! Press Ctrl+Q again to quit without saving.
+status-buffer-unsaved = { $filename } has unsaved changes! Close it again to discard them.
+status-buffer-closed = Closed { $filename }
+status-buffer-switched = { $filename } ({ $index }/{ $count })
+status-files-opened = Opened { $count } files
+status-pane-split = Split pane
+status-pane-closed = Closed pane
+status-last-pane = The last pane cannot be closed
 
 # Welcome Content
 welcome-title = Welcome to IOCraft Enhanced Text Editor!
@@ -33,8 +39,8 @@ welcome-feature-shortcuts = ⌨️ Sublime Text-style keyboard shortcuts
 welcome-feature-linting = 🔍 Real-time code linting and issue detection
 welcome-shortcuts = Keyboard Shortcuts:
 welcome-shortcuts-file = 📄 File: Ctrl+O (open), Ctrl+S (save), Ctrl+N (new)
-welcome-shortcuts-edit = ✂️ Edit: Ctrl+D (duplicate line), Ctrl+K (delete line)
-welcome-shortcuts-navigate = 🔍 Navigate: Ctrl+Home/End (document), Home/End (line)
+welcome-shortcuts-edit = ✂️ Edit: Ctrl+D (duplicate line), Ctrl+K (delete line), Ctrl+Z/Y (undo/redo), Ctrl+C/X/V (copy/cut/paste)
+welcome-shortcuts-navigate = 🔍 Navigate: Ctrl+Home/End (document), Home/End (line), Ctrl+F (find), Ctrl+R (replace)
 welcome-shortcuts-view = 🎨 View: Ctrl+H (toggle highlighting), Ctrl+E (toggle linting)
 welcome-shortcuts-quit = 🚪 Quit: Ctrl+Q
 welcome-start-editing = Start editing here...
@@ -45,23 +51,104 @@ status-syntax-enabled = Syntax highlighting enabled
 status-syntax-disabled = Syntax highlighting disabled
 status-linting-enabled = Code linting enabled
 status-linting-disabled = Code linting disabled
+status-soft-wrap-enabled = Soft wrap enabled
+status-soft-wrap-disabled = Soft wrap disabled
 status-new-file-created = New file created
 status-document-start = Start of document
 status-document-end = End of document
-status-goto-line-soon = Go to line: (feature coming soon)
+goto-prompt = Go to line: { $input }  (line, line:col, +N/-N, N%)
+goto-invalid = Invalid line: { $input }
+goto-canceled = Go to line canceled
 status-line-duplicated = Line duplicated
 status-line-deleted = Line deleted
 status-line-cleared = Line cleared
+status-undo = Undo
+status-redo = Redo
+status-nothing-to-undo = Nothing to undo
+status-nothing-to-redo = Nothing to redo
+status-copied = Copied { $count } characters
+status-copied-internal = Copied { $count } characters, too many for the system clipboard; they paste in this editor only
+status-cut = Cut selection to clipboard
+status-pasted = { $count ->
+    [one] Pasted { $count } line
+   *[other] Pasted { $count } lines
+}
+status-nothing-selected = Nothing selected
+status-clipboard-empty = Clipboard is empty
+status-clipboard-error = Clipboard error: { $error }
 status-cursor-moved = Cursor moved to row { $row }, col { $col }
 status-word-selected = Word selected (double-click)
 status-text-selected = Selected { $count } characters
+status-carets = { $count ->
+    [one] { $count } cursor
+   *[other] { $count } cursors
+}
+status-all-occurrences-selected = All occurrences are selected
+status-keys-pending = { $keys } was pressed, waiting for the next key...
+status-keymap-error = { $count ->
+    [one] Keymap error: { $error }
+   *[other] { $count } keymap errors, first: { $error }
+}
+theme-picker-prompt = Theme: { $name } ({ $index }/{ $count })  ↑/↓ preview, Enter apply, Esc cancel
+theme-applied = Theme set to { $name }
+theme-not-saved = Theme set to { $name }, but it could not be saved: { $error }
+theme-picker-canceled = Theme unchanged
+language-picker-prompt = Language: { $query } → { $name } ({ $index }/{ $count })  ↑/↓ choose, Enter apply, Esc cancel
+language-picker-no-match = Language: { $query } no matching language
+language-picker-detect = Detect automatically
+language-picker-canceled = Language unchanged
+language-set = Language set to { $name }
+language-detected = Language detected as { $name }
+language-not-saved = Language set to { $name }, but it could not be saved: { $error }
+ui-language-picked = { $name } (set)
+quick-fix-prompt = Fix: { $title } ({ $index }/{ $count })  ↑/↓ choose, Enter apply, Esc cancel
+quick-fix-none = No quick fixes on this line
+quick-fix-canceled = Quick fix canceled
+quick-fix-applied = Applied: { $title }
+lint-issue-at-cursor = { $rule }: { $message }
+fix-all-none = Nothing to fix
+fix-all-applied = { $count ->
+    [one] Applied 1 fix
+   *[other] Applied { $count } fixes
+}
+status-theme-error = { $count ->
+    [one] Theme error: { $error }
+   *[other] { $count } theme errors, first: { $error }
+}
+status-language-error = { $count ->
+    [one] Language config error: { $error }
+   *[other] { $count } language config errors, first: { $error }
+}
+status-vim-enabled = Vim mode enabled
+status-vim-disabled = Vim mode disabled
+status-mark-set = Mark set
+status-kill-ring-empty = Kill ring is empty
+status-yank-pop-without-yank = Previous command was not a yank
+vim-mode-insert = -- INSERT --
+vim-mode-visual = -- VISUAL --
+vim-mode-visual-line = -- VISUAL LINE --
 status-selecting = Selecting text...
 status-scrolled-up = Scrolled up
 status-scrolled-down = Scrolled down
+status-scrolled-left = Scrolled left
+status-scrolled-right = Scrolled right
+
+# Search and Replace
+search-prompt = Find: { $query }
+search-prompt-backward = Find backward: { $query }
+replace-prompt = Replace: { $query } → { $replacement }
+search-match-position = match { $current } of { $total }
+search-no-matches = no matches
+search-invalid-regex = invalid regex: { $error }
+search-canceled = Search canceled
+search-replaced-all = { $count ->
+    [one] Replaced { $count } occurrence
+   *[other] Replaced { $count } occurrences
+}
 
 # Context Menu
-context-menu-copy-cut-paste = Context menu: Copy/Cut/Paste available at row { $row }, col { $col }
-context-menu-paste = Context menu: Paste available at row { $row }, col { $col }
+context-menu-copy-cut-paste = Context menu: Copy (Ctrl+C) / Cut (Ctrl+X) / Paste (Ctrl+V) available at row { $row }, col { $col }
+context-menu-paste = Context menu: Paste (Ctrl+V) available at row { $row }, col { $col }
 
 # Dialog System
 dialog-open-file = 📂 Open File - IOCraft File Browser
@@ -109,4 +196,4 @@ error-file-operation = File operation error: { $error }
 error-general = Error: { $message }
 
 # Exit Prompt
-exit-prompt = Press Ctrl+C to exit if needed
+exit-prompt = Press Ctrl+Q to exit
@@ -19,8 +19,14 @@ file-no-file-specified = No se especificó archivo
 file-opened-success = ✅ Abierto: { $filename }
 file-new-created = 🆕 Nuevo archivo '{ $filename }' creado - ¡listo para editar!
 file-unsaved-changes = ¡El archivo tiene cambios sin guardar! Presiona Ctrl+Q de nuevo para salir sin guardar.
-file-save-before-new = Guarda el archivo actual antes de crear uno nuevo (Ctrl+S)
-file-save-before-open = Guarda el archivo actual antes de abrir uno nuevo (Ctrl+S)
+status-buffers-unsaved = ¡{ $count } archivos tienen cambios sin guardar! Presiona Ctrl+Q de nuevo para salir sin guardar.
+status-buffer-unsaved = ¡{ $filename } tiene cambios sin guardar! Ciérralo de nuevo para descartarlos.
+status-buffer-closed = { $filename } cerrado
+status-buffer-switched = { $filename } ({ $index }/{ $count })
+status-files-opened = { $count } archivos abiertos
+status-pane-split = Panel dividido
+status-pane-closed = Panel cerrado
+status-last-pane = El último panel no se puede cerrar
 
 # Contenido de Bienvenida
 welcome-title = ¡Bienvenido al Editor de Texto Mejorado IOCraft!
@@ -33,8 +39,8 @@ welcome-feature-shortcuts = ⌨️ Atajos de teclado estilo Sublime Text
 welcome-feature-linting = 🔍 Detección de problemas de código en tiempo real
 welcome-shortcuts = Atajos de Teclado:
 welcome-shortcuts-file = 📄 Archivo: Ctrl+O (abrir), Ctrl+S (guardar), Ctrl+N (nuevo)
-welcome-shortcuts-edit = ✂️ Editar: Ctrl+D (duplicar línea), Ctrl+K (eliminar línea)
-welcome-shortcuts-navigate = 🔍 Navegar: Ctrl+Home/End (documento), Home/End (línea)
+welcome-shortcuts-edit = ✂️ Editar: Ctrl+D (duplicar línea), Ctrl+K (eliminar línea), Ctrl+Z/Y (deshacer/rehacer), Ctrl+C/X/V (copiar/cortar/pegar)
+welcome-shortcuts-navigate = 🔍 Navegar: Ctrl+Home/End (documento), Home/End (línea), Ctrl+F (buscar), Ctrl+R (reemplazar)
 welcome-shortcuts-view = 🎨 Vista: Ctrl+H (alternar resaltado), Ctrl+E (alternar análisis)
 welcome-shortcuts-quit = 🚪 Salir: Ctrl+Q
 welcome-start-editing = Comienza a editar aquí...
@@ -45,23 +51,104 @@ status-syntax-enabled = Resaltado de sintaxis habilitado
 status-syntax-disabled = Resaltado de sintaxis deshabilitado
 status-linting-enabled = Análisis de código habilitado
 status-linting-disabled = Análisis de código deshabilitado
+status-soft-wrap-enabled = Ajuste de línea activado
+status-soft-wrap-disabled = Ajuste de línea desactivado
 status-new-file-created = Nuevo archivo creado
 status-document-start = Inicio del documento
 status-document-end = Fin del documento
-status-goto-line-soon = Ir a línea: (función próximamente)
+goto-prompt = Ir a línea: { $input }  (línea, línea:col, +N/-N, N%)
+goto-invalid = Línea inválida: { $input }
+goto-canceled = Ir a línea cancelado
 status-line-duplicated = Línea duplicada
 status-line-deleted = Línea eliminada
 status-line-cleared = Línea limpiada
+status-undo = Deshacer
+status-redo = Rehacer
+status-nothing-to-undo = Nada que deshacer
+status-nothing-to-redo = Nada que rehacer
+status-copied = Copiados { $count } caracteres
+status-copied-internal = Copiados { $count } caracteres, demasiados para el portapapeles del sistema; solo se pegan en este editor
+status-cut = Selección cortada al portapapeles
+status-pasted = { $count ->
+    [one] Pegada { $count } línea
+   *[other] Pegadas { $count } líneas
+}
+status-nothing-selected = Nada seleccionado
+status-clipboard-empty = El portapapeles está vacío
+status-clipboard-error = Error del portapapeles: { $error }
 status-cursor-moved = Cursor movido a fila { $row }, columna { $col }
 status-word-selected = Palabra seleccionada (doble clic)
 status-text-selected = Seleccionados { $count } caracteres
+status-carets = { $count ->
+    [one] { $count } cursor
+   *[other] { $count } cursores
+}
+status-all-occurrences-selected = Todas las coincidencias están seleccionadas
+status-keys-pending = Se pulsó { $keys }, esperando la siguiente tecla...
+status-keymap-error = { $count ->
+    [one] Error en el mapa de teclas: { $error }
+   *[other] { $count } errores en el mapa de teclas, primero: { $error }
+}
+theme-picker-prompt = Tema: { $name } ({ $index }/{ $count })  ↑/↓ vista previa, Enter aplicar, Esc cancelar
+theme-applied = Tema cambiado a { $name }
+theme-not-saved = Tema cambiado a { $name }, pero no se pudo guardar: { $error }
+theme-picker-canceled = Tema sin cambios
+language-picker-prompt = Lenguaje: { $query } → { $name } ({ $index }/{ $count })  ↑/↓ elegir, Enter aplicar, Esc cancelar
+language-picker-no-match = Lenguaje: { $query } ningún lenguaje coincide
+language-picker-detect = Detectar automáticamente
+language-picker-canceled = Lenguaje sin cambios
+language-set = Lenguaje establecido en { $name }
+language-detected = Lenguaje detectado: { $name }
+language-not-saved = Lenguaje establecido en { $name }, pero no se pudo guardar: { $error }
+ui-language-picked = { $name } (elegido)
+quick-fix-prompt = Corrección: { $title } ({ $index }/{ $count })  ↑/↓ elegir, Enter aplicar, Esc cancelar
+quick-fix-none = No hay correcciones rápidas en esta línea
+quick-fix-canceled = Corrección rápida cancelada
+quick-fix-applied = Aplicado: { $title }
+lint-issue-at-cursor = { $rule }: { $message }
+fix-all-none = Nada que corregir
+fix-all-applied = { $count ->
+    [one] 1 corrección aplicada
+   *[other] { $count } correcciones aplicadas
+}
+status-theme-error = { $count ->
+    [one] Error de tema: { $error }
+   *[other] { $count } errores de tema, primero: { $error }
+}
+status-language-error = { $count ->
+    [one] Error en la configuración de lenguajes: { $error }
+   *[other] { $count } errores en la configuración de lenguajes, primero: { $error }
+}
+status-vim-enabled = Modo Vim activado
+status-vim-disabled = Modo Vim desactivado
+status-mark-set = Marca establecida
+status-kill-ring-empty = El anillo de cortes está vacío
+status-yank-pop-without-yank = El comando anterior no fue un pegado (yank)
+vim-mode-insert = -- INSERTAR --
+vim-mode-visual = -- VISUAL --
+vim-mode-visual-line = -- VISUAL LÍNEA --
 status-selecting = Seleccionando texto...
 status-scrolled-up = Desplazado hacia arriba
 status-scrolled-down = Desplazado hacia abajo
+status-scrolled-left = Desplazado hacia la izquierda
+status-scrolled-right = Desplazado hacia la derecha
+
+# Buscar y Reemplazar
+search-prompt = Buscar: { $query }
+search-prompt-backward = Buscar hacia atrás: { $query }
+replace-prompt = Reemplazar: { $query } → { $replacement }
+search-match-position = coincidencia { $current } de { $total }
+search-no-matches = sin coincidencias
+search-invalid-regex = expresión regular inválida: { $error }
+search-canceled = Búsqueda cancelada
+search-replaced-all = { $count ->
+    [one] { $count } coincidencia reemplazada
+   *[other] { $count } coincidencias reemplazadas
+}
 
 # Menú Contextual
-context-menu-copy-cut-paste = Menú contextual: Copiar/Cortar/Pegar disponible en fila { $row }, columna { $col }
-context-menu-paste = Menú contextual: Pegar disponible en fila { $row }, columna { $col }
+context-menu-copy-cut-paste = Menú contextual: Copiar (Ctrl+C) / Cortar (Ctrl+X) / Pegar (Ctrl+V) disponible en fila { $row }, columna { $col }
+context-menu-paste = Menú contextual: Pegar (Ctrl+V) disponible en fila { $row }, columna { $col }
 
 # Sistema de Diálogo
 dialog-open-file = 📂 Abrir Archivo - Explorador de Archivos IOCraft
@@ -109,4 +196,4 @@ error-file-operation = Error de operación de archivo: { $error }
 error-general = Error: { $message }
 
 # Prompt de Salida
-exit-prompt = Presiona Ctrl+C para salir si es necesario
+exit-prompt = Presiona Ctrl+Q para salir
@@ -19,8 +19,14 @@ file-no-file-specified = Aucun fichier spécifié
 file-opened-success = ✅ Ouvert : { $filename }
 file-new-created = 🆕 Nouveau fichier '{ $filename }' créé - prêt à éditer !
 file-unsaved-changes = Le fichier contient des modifications non sauvegardées ! Appuyez à nouveau sur Ctrl+Q pour quitter sans sauvegarder.
-file-save-before-new = Sauvegardez le fichier actuel avant d'en créer un nouveau (Ctrl+S)
-file-save-before-open = Sauvegardez le fichier actuel avant d'en ouvrir un nouveau (Ctrl+S)
+status-buffers-unsaved = { $count } fichiers contiennent des modifications non sauvegardées ! Appuyez à nouveau sur Ctrl+Q pour quitter sans sauvegarder.
+status-buffer-unsaved = { $filename } contient des modifications non sauvegardées ! Fermez-le à nouveau pour les abandonner.
+status-buffer-closed = { $filename } fermé
+status-buffer-switched = { $filename } ({ $index }/{ $count })
+status-files-opened = { $count } fichiers ouverts
+status-pane-split = Volet divisé
+status-pane-closed = Volet fermé
+status-last-pane = Le dernier volet ne peut pas être fermé
 
 # Contenu de Bienvenue
 welcome-title = Bienvenue dans l'Éditeur de Texte Amélioré IOCraft !
@@ -33,8 +39,8 @@ welcome-feature-shortcuts = ⌨️ Raccourcis clavier style Sublime Text
 welcome-feature-linting = 🔍 Détection de problèmes de code en temps réel
 welcome-shortcuts = Raccourcis Clavier :
 welcome-shortcuts-file = 📄 Fichier : Ctrl+O (ouvrir), Ctrl+S (sauvegarder), Ctrl+N (nouveau)
-welcome-shortcuts-edit = ✂️ Édition : Ctrl+D (dupliquer ligne), Ctrl+K (supprimer ligne)
-welcome-shortcuts-navigate = 🔍 Navigation : Ctrl+Home/End (document), Home/End (ligne)
+welcome-shortcuts-edit = ✂️ Édition : Ctrl+D (dupliquer ligne), Ctrl+K (supprimer ligne), Ctrl+Z/Y (annuler/rétablir), Ctrl+C/X/V (copier/couper/coller)
+welcome-shortcuts-navigate = 🔍 Navigation : Ctrl+Home/End (document), Home/End (ligne), Ctrl+F (rechercher), Ctrl+R (remplacer)
 welcome-shortcuts-view = 🎨 Affichage : Ctrl+H (basculer coloration), Ctrl+E (basculer vérification)
 welcome-shortcuts-quit = 🚪 Quitter : Ctrl+Q
 welcome-start-editing = Commencez à éditer ici...
@@ -45,23 +51,104 @@ status-syntax-enabled = Coloration syntaxique activée
 status-syntax-disabled = Coloration syntaxique désactivée
 status-linting-enabled = Vérification de code activée
 status-linting-disabled = Vérification de code désactivée
+status-soft-wrap-enabled = Retour à la ligne automatique activé
+status-soft-wrap-disabled = Retour à la ligne automatique désactivé
 status-new-file-created = Nouveau fichier créé
 status-document-start = Début du document
 status-document-end = Fin du document
-status-goto-line-soon = Aller à la ligne : (fonctionnalité bientôt disponible)
+goto-prompt = Aller à la ligne : { $input }  (ligne, ligne:col, +N/-N, N%)
+goto-invalid = Ligne invalide : { $input }
+goto-canceled = Aller à la ligne annulé
 status-line-duplicated = Ligne dupliquée
 status-line-deleted = Ligne supprimée
 status-line-cleared = Ligne effacée
+status-undo = Annuler
+status-redo = Rétablir
+status-nothing-to-undo = Rien à annuler
+status-nothing-to-redo = Rien à rétablir
+status-copied = { $count } caractères copiés
+status-copied-internal = { $count } caractères copiés, trop pour le presse-papiers système ; ils ne se collent que dans cet éditeur
+status-cut = Sélection coupée dans le presse-papiers
+status-pasted = { $count ->
+    [one] { $count } ligne collée
+   *[other] { $count } lignes collées
+}
+status-nothing-selected = Aucune sélection
+status-clipboard-empty = Le presse-papiers est vide
+status-clipboard-error = Erreur du presse-papiers : { $error }
 status-cursor-moved = Curseur déplacé à la ligne { $row }, colonne { $col }
 status-word-selected = Mot sélectionné (double-clic)
 status-text-selected = { $count } caractères sélectionnés
+status-carets = { $count ->
+    [one] { $count } curseur
+   *[other] { $count } curseurs
+}
+status-all-occurrences-selected = Toutes les occurrences sont sélectionnées
+status-keys-pending = { $keys } appuyé, en attente de la touche suivante...
+status-keymap-error = { $count ->
+    [one] Erreur de raccourcis : { $error }
+   *[other] { $count } erreurs de raccourcis, première : { $error }
+}
+theme-picker-prompt = Thème : { $name } ({ $index }/{ $count })  ↑/↓ aperçu, Entrée appliquer, Échap annuler
+theme-applied = Thème défini sur { $name }
+theme-not-saved = Thème défini sur { $name }, mais impossible de l'enregistrer : { $error }
+theme-picker-canceled = Thème inchangé
+language-picker-prompt = Langage : { $query } → { $name } ({ $index }/{ $count })  ↑/↓ choisir, Entrée appliquer, Échap annuler
+language-picker-no-match = Langage : { $query } aucun langage correspondant
+language-picker-detect = Détecter automatiquement
+language-picker-canceled = Langage inchangé
+language-set = Langage défini sur { $name }
+language-detected = Langage détecté : { $name }
+language-not-saved = Langage défini sur { $name }, mais impossible de l'enregistrer : { $error }
+ui-language-picked = { $name } (choisi)
+quick-fix-prompt = Correction : { $title } ({ $index }/{ $count })  ↑/↓ choisir, Entrée appliquer, Échap annuler
+quick-fix-none = Aucune correction rapide sur cette ligne
+quick-fix-canceled = Correction rapide annulée
+quick-fix-applied = Appliqué : { $title }
+lint-issue-at-cursor = { $rule } : { $message }
+fix-all-none = Rien à corriger
+fix-all-applied = { $count ->
+    [one] 1 correction appliquée
+   *[other] { $count } corrections appliquées
+}
+status-theme-error = { $count ->
+    [one] Erreur de thème : { $error }
+   *[other] { $count } erreurs de thème, première : { $error }
+}
+status-language-error = { $count ->
+    [one] Erreur de configuration des langages : { $error }
+   *[other] { $count } erreurs de configuration des langages, première : { $error }
+}
+status-vim-enabled = Mode Vim activé
+status-vim-disabled = Mode Vim désactivé
+status-mark-set = Marque posée
+status-kill-ring-empty = L’anneau de coupe est vide
+status-yank-pop-without-yank = La commande précédente n’était pas un collage (yank)
+vim-mode-insert = -- INSERTION --
+vim-mode-visual = -- VISUEL --
+vim-mode-visual-line = -- VISUEL LIGNE --
 status-selecting = Sélection du texte...
 status-scrolled-up = Défilement vers le haut
 status-scrolled-down = Défilement vers le bas
+status-scrolled-left = Défilement vers la gauche
+status-scrolled-right = Défilement vers la droite
+
+# Rechercher et Remplacer
+search-prompt = Rechercher : { $query }
+search-prompt-backward = Rechercher en arrière : { $query }
+replace-prompt = Remplacer : { $query } → { $replacement }
+search-match-position = résultat { $current } sur { $total }
+search-no-matches = aucun résultat
+search-invalid-regex = expression régulière invalide : { $error }
+search-canceled = Recherche annulée
+search-replaced-all = { $count ->
+    [one] { $count } occurrence remplacée
+   *[other] { $count } occurrences remplacées
+}
 
 # Menu Contextuel
-context-menu-copy-cut-paste = Menu contextuel : Copier/Couper/Coller disponible à la ligne { $row }, colonne { $col }
-context-menu-paste = Menu contextuel : Coller disponible à la ligne { $row }, colonne { $col }
+context-menu-copy-cut-paste = Menu contextuel : Copier (Ctrl+C) / Couper (Ctrl+X) / Coller (Ctrl+V) disponible à la ligne { $row }, colonne { $col }
+context-menu-paste = Menu contextuel : Coller (Ctrl+V) disponible à la ligne { $row }, colonne { $col }
 
 # Système de Dialogue
 dialog-open-file = 📂 Ouvrir Fichier - Navigateur de Fichiers IOCraft
@@ -109,4 +196,4 @@ error-file-operation = Erreur d'opération fichier : { $error }
 error-general = Erreur : { $message }
 
 # Invite de Sortie
-exit-prompt = Appuyez sur Ctrl+C pour quitter si nécessaire
+exit-prompt = Appuyez sur Ctrl+Q pour quitter
@@ -0,0 +1,445 @@
+use ropey::Rope;
+use std::fmt;
+use std::io::{self, Read, Write};
+use crate::text_metrics;
+
+/// Rope-backed text storage shared by all editor variants.
+///
+/// Positions are expressed either as absolute char indices or as
+/// `(row, col)` pairs where `col` counts chars within the line, excluding
+/// the line ending. Inserts, deletes and index conversions are O(log n),
+/// so large files stay responsive while typing.
+#[derive(Debug, Clone)]
+pub struct TextBuffer {
+    rope: Rope,
+    // Lines edited since the last `take_changed_lines`
+    changes: Option<LineChanges>,
+}
+
+/// Lines that edits may have touched, in current line numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineChanges {
+    pub first: usize,
+    pub last: usize,
+}
+
+impl TextBuffer {
+    /// Create an empty buffer containing a single empty line
+    pub fn new() -> Self {
+        Self::from_rope(Rope::new())
+    }
+
+    /// A fresh buffer counts as changed throughout
+    fn from_rope(rope: Rope) -> Self {
+        let last = rope.len_lines() - 1;
+        Self { rope, changes: Some(LineChanges { first: 0, last }) }
+    }
+
+    /// Build a buffer from individual lines (joined with `\n`)
+    pub fn from_lines<S: AsRef<str>>(lines: &[S]) -> Self {
+        let mut rope = Rope::new();
+        for (idx, line) in lines.iter().enumerate() {
+            if idx > 0 {
+                rope.insert_char(rope.len_chars(), '\n');
+            }
+            rope.insert(rope.len_chars(), line.as_ref());
+        }
+        Self::from_rope(rope)
+    }
+
+    /// Build a buffer by streaming text from a reader
+    pub fn from_reader<R: Read>(reader: R) -> io::Result<Self> {
+        Ok(Self::from_rope(Rope::from_reader(reader)?))
+    }
+
+    /// Stream the buffer contents to a writer without building one big string
+    pub fn write_to<W: Write>(&self, writer: W) -> io::Result<()> {
+        self.rope.write_to(writer)
+    }
+
+    /// Number of lines (an empty buffer still has one line)
+    pub fn len_lines(&self) -> usize {
+        self.rope.len_lines()
+    }
+
+    /// Total number of chars, including line endings
+    pub fn len_chars(&self) -> usize {
+        self.rope.len_chars()
+    }
+
+    /// Total number of bytes, including line endings
+    pub fn len_bytes(&self) -> usize {
+        self.rope.len_bytes()
+    }
+
+    /// Check whether the buffer holds no text at all
+    pub fn is_empty(&self) -> bool {
+        self.rope.len_chars() == 0
+    }
+
+    /// Get the text of a line without its line ending
+    pub fn line(&self, row: usize) -> String {
+        if row >= self.len_lines() {
+            return String::new();
+        }
+        let line = self.rope.line(row);
+        let content_len = line.len_chars() - Self::line_ending_len(&line);
+        line.slice(..content_len).to_string()
+    }
+
+    /// Length of a line in chars, excluding its line ending
+    pub fn line_len(&self, row: usize) -> usize {
+        if row >= self.len_lines() {
+            return 0;
+        }
+        let line = self.rope.line(row);
+        line.len_chars() - Self::line_ending_len(&line)
+    }
+
+    /// Iterate over all lines without their line endings
+    pub fn lines(&self) -> impl Iterator<Item = String> + '_ {
+        (0..self.len_lines()).map(move |row| self.line(row))
+    }
+
+    /// Copy every line into a vector of owned strings
+    pub fn to_lines(&self) -> Vec<String> {
+        self.lines().collect()
+    }
+
+    fn line_ending_len(line: &ropey::RopeSlice) -> usize {
+        let len = line.len_chars();
+        if len > 0 && line.char(len - 1) == '\n' {
+            if len > 1 && line.char(len - 2) == '\r' { 2 } else { 1 }
+        } else {
+            0
+        }
+    }
+
+    /// Convert a char index to a byte index
+    pub fn char_to_byte(&self, char_idx: usize) -> usize {
+        self.rope.char_to_byte(char_idx.min(self.len_chars()))
+    }
+
+    /// Convert a byte index to a char index
+    pub fn byte_to_char(&self, byte_idx: usize) -> usize {
+        self.rope.byte_to_char(byte_idx.min(self.len_bytes()))
+    }
+
+    /// Get the line containing a char index
+    pub fn char_to_line(&self, char_idx: usize) -> usize {
+        self.rope.char_to_line(char_idx.min(self.len_chars()))
+    }
+
+    /// Get the char index of the start of a line
+    pub fn line_to_char(&self, row: usize) -> usize {
+        self.rope.line_to_char(row.min(self.len_lines()))
+    }
+
+    /// Get the byte index of the start of a line
+    pub fn line_to_byte(&self, row: usize) -> usize {
+        self.rope.line_to_byte(row.min(self.len_lines()))
+    }
+
+    /// Get the line containing a byte index
+    pub fn byte_to_line(&self, byte_idx: usize) -> usize {
+        self.rope.byte_to_line(byte_idx.min(self.len_bytes()))
+    }
+
+    /// Convert a `(row, col)` position to a char index, clamping to the buffer
+    pub fn pos_to_char(&self, row: usize, col: usize) -> usize {
+        if row >= self.len_lines() {
+            return self.len_chars();
+        }
+        self.rope.line_to_char(row) + col.min(self.line_len(row))
+    }
+
+    /// Convert a char index to a `(row, col)` position
+    pub fn char_to_pos(&self, char_idx: usize) -> (usize, usize) {
+        let char_idx = char_idx.min(self.len_chars());
+        let row = self.rope.char_to_line(char_idx);
+        let col = (char_idx - self.rope.line_to_char(row)).min(self.line_len(row));
+        (row, col)
+    }
+
+    /// Char index one grapheme cluster before `char_idx`; a line break counts as one step
+    pub fn prev_grapheme_boundary(&self, char_idx: usize) -> usize {
+        let (row, col) = self.char_to_pos(char_idx);
+        if col == 0 {
+            return if row == 0 { 0 } else { self.pos_to_char(row - 1, self.line_len(row - 1)) };
+        }
+        self.line_to_char(row) + text_metrics::prev_boundary(&self.line(row), col)
+    }
+
+    /// Char index one grapheme cluster after `char_idx`; a line break counts as one step
+    pub fn next_grapheme_boundary(&self, char_idx: usize) -> usize {
+        let (row, col) = self.char_to_pos(char_idx);
+        if col >= self.line_len(row) {
+            return if row + 1 < self.len_lines() { self.line_to_char(row + 1) } else { self.len_chars() };
+        }
+        self.line_to_char(row) + text_metrics::next_boundary(&self.line(row), col)
+    }
+
+    /// Get the char at an absolute char index
+    pub fn char_at(&self, char_idx: usize) -> Option<char> {
+        self.rope.get_char(char_idx)
+    }
+
+    /// Copy a char range out of the buffer
+    pub fn slice(&self, start: usize, end: usize) -> String {
+        let end = end.min(self.len_chars());
+        let start = start.min(end);
+        self.rope.slice(start..end).to_string()
+    }
+
+    /// Copy the text between two `(row, col)` positions
+    pub fn text_range(&self, start_row: usize, start_col: usize, end_row: usize, end_col: usize) -> String {
+        let start = self.pos_to_char(start_row, start_col);
+        let end = self.pos_to_char(end_row, end_col);
+        self.slice(start, end)
+    }
+
+    /// Insert text at an absolute char index
+    pub fn insert(&mut self, char_idx: usize, text: &str) {
+        let char_idx = char_idx.min(self.len_chars());
+        let row = self.rope.char_to_line(char_idx);
+        let lines_before = self.len_lines();
+        self.rope.insert(char_idx, text);
+        self.record_change(row, self.len_lines() - lines_before, 0);
+    }
+
+    /// Remove a char range from the buffer
+    pub fn remove(&mut self, start: usize, end: usize) {
+        let end = end.min(self.len_chars());
+        if start < end {
+            let row = self.rope.char_to_line(start);
+            let lines_before = self.len_lines();
+            self.rope.remove(start..end);
+            self.record_change(row, 0, lines_before - self.len_lines());
+        }
+    }
+
+    /// Widen the changed lines by an edit at `row` that added or removed line breaks
+    fn record_change(&mut self, row: usize, added: usize, removed: usize) {
+        let last = match self.changes {
+            // Lines changed earlier move along with the edit
+            Some(changes) if changes.last > row + removed => changes.last + added - removed,
+            Some(changes) if changes.last > row => row + added,
+            Some(changes) => changes.last.max(row + added),
+            None => row + added,
+        };
+        let first = self.changes.map_or(row, |changes| changes.first.min(row));
+        self.changes = Some(LineChanges { first, last });
+    }
+
+    /// Lines touched since the last call, or since the buffer was created
+    pub fn take_changed_lines(&mut self) -> Option<LineChanges> {
+        self.changes.take()
+    }
+
+    /// Insert a single char at a `(row, col)` position
+    pub fn insert_char_at(&mut self, row: usize, col: usize, ch: char) {
+        let idx = self.pos_to_char(row, col);
+        self.insert(idx, ch.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Insert text at a `(row, col)` position and return the position after it
+    pub fn insert_str_at(&mut self, row: usize, col: usize, text: &str) -> (usize, usize) {
+        let idx = self.pos_to_char(row, col);
+        self.insert(idx, text);
+        self.char_to_pos(idx + text.chars().count())
+    }
+
+    /// Remove the text between two `(row, col)` positions and return it
+    pub fn remove_range(&mut self, start_row: usize, start_col: usize, end_row: usize, end_col: usize) -> String {
+        let start = self.pos_to_char(start_row, start_col);
+        let end = self.pos_to_char(end_row, end_col);
+        let removed = self.slice(start, end);
+        self.remove(start, end);
+        removed
+    }
+
+    /// Break a line in two at the given column
+    pub fn split_line(&mut self, row: usize, col: usize) {
+        self.insert_char_at(row, col, '\n');
+    }
+
+    /// Join a line with the one following it
+    pub fn join_with_next(&mut self, row: usize) {
+        if row + 1 >= self.len_lines() {
+            return;
+        }
+        let start = self.rope.line_to_char(row) + self.line_len(row);
+        let end = self.rope.line_to_char(row + 1);
+        self.remove(start, end);
+    }
+
+    /// Insert a new line so that it ends up at index `row`
+    pub fn insert_line(&mut self, row: usize, text: &str) {
+        if row < self.len_lines() {
+            let idx = self.rope.line_to_char(row);
+            self.insert(idx, &format!("{}\n", text));
+        } else {
+            let idx = self.len_chars();
+            self.insert(idx, &format!("\n{}", text));
+        }
+    }
+
+    /// Char range covered by a whole line, including one adjacent line break.
+    ///
+    /// The last line takes the break before it, so removing the range never
+    /// leaves a dangling empty line behind.
+    pub fn line_range(&self, row: usize) -> (usize, usize) {
+        if row >= self.len_lines() {
+            return (self.len_chars(), self.len_chars());
+        }
+        if self.len_lines() == 1 {
+            (0, self.len_chars())
+        } else if row + 1 < self.len_lines() {
+            (self.rope.line_to_char(row), self.rope.line_to_char(row + 1))
+        } else {
+            (self.rope.line_to_char(row - 1) + self.line_len(row - 1), self.len_chars())
+        }
+    }
+
+    /// Remove a whole line and return its text; the last remaining line is cleared instead
+    pub fn remove_line(&mut self, row: usize) -> String {
+        if row >= self.len_lines() {
+            return String::new();
+        }
+        let text = self.line(row);
+        let (start, end) = self.line_range(row);
+        self.remove(start, end);
+        text
+    }
+
+    /// Replace the text of a line, keeping its line ending
+    pub fn set_line(&mut self, row: usize, text: &str) {
+        if row >= self.len_lines() {
+            return;
+        }
+        let start = self.rope.line_to_char(row);
+        let end = start + self.line_len(row);
+        self.remove(start, end);
+        self.insert(start, text);
+    }
+}
+
+impl Default for TextBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Buffers are equal when they hold the same text
+impl PartialEq for TextBuffer {
+    fn eq(&self, other: &Self) -> bool {
+        self.rope == other.rope
+    }
+}
+
+impl From<&str> for TextBuffer {
+    fn from(text: &str) -> Self {
+        Self::from_rope(Rope::from_str(text))
+    }
+}
+
+impl From<String> for TextBuffer {
+    fn from(text: String) -> Self {
+        Self::from(text.as_str())
+    }
+}
+
+impl fmt::Display for TextBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chunk in self.rope.chunks() {
+            f.write_str(chunk)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_indexing() {
+        let buffer = TextBuffer::from("first\r\nsecond\nthird");
+        assert_eq!(buffer.len_lines(), 3);
+        assert_eq!(buffer.line(0), "first");
+        assert_eq!(buffer.line(1), "second");
+        assert_eq!(buffer.line_len(0), 5);
+        assert_eq!(buffer.line_len(2), 5);
+        assert_eq!(buffer.to_lines(), vec!["first", "second", "third"]);
+    }
+
+    #[test]
+    fn test_position_conversions() {
+        let buffer = TextBuffer::from("héllo\nwörld");
+        assert_eq!(buffer.pos_to_char(1, 2), 8);
+        assert_eq!(buffer.char_to_pos(8), (1, 2));
+        assert_eq!(buffer.char_to_byte(2), 3);
+        assert_eq!(buffer.byte_to_char(3), 2);
+        assert_eq!(buffer.char_to_line(7), 1);
+        assert_eq!(buffer.line_to_byte(1), 7);
+        // Columns past the end clamp to the line length
+        assert_eq!(buffer.pos_to_char(0, 99), 5);
+
+        // Grapheme steps skip combining marks and treat CRLF as one break
+        let buffer = TextBuffer::from("ae\u{301}\r\nb");
+        assert_eq!(buffer.next_grapheme_boundary(1), 3);
+        assert_eq!(buffer.next_grapheme_boundary(3), 5);
+        assert_eq!(buffer.prev_grapheme_boundary(5), 3);
+        assert_eq!(buffer.prev_grapheme_boundary(3), 1);
+        assert_eq!(buffer.prev_grapheme_boundary(0), 0);
+    }
+
+    #[test]
+    fn test_editing_operations() {
+        let mut buffer = TextBuffer::from_lines(&["hello", "world"]);
+        buffer.insert_char_at(0, 5, '!');
+        buffer.split_line(1, 2);
+        assert_eq!(buffer.to_lines(), vec!["hello!", "wo", "rld"]);
+
+        buffer.join_with_next(1);
+        assert_eq!(buffer.to_lines(), vec!["hello!", "world"]);
+
+        let removed = buffer.remove_range(0, 3, 1, 2);
+        assert_eq!(removed, "lo!\nwo");
+        assert_eq!(buffer.to_string(), "helrld");
+    }
+
+    #[test]
+    fn test_line_operations() {
+        let mut buffer = TextBuffer::from_lines(&["a", "b", "c"]);
+        buffer.insert_line(1, "x");
+        buffer.insert_line(4, "z");
+        assert_eq!(buffer.to_lines(), vec!["a", "x", "b", "c", "z"]);
+
+        assert_eq!(buffer.remove_line(4), "z");
+        assert_eq!(buffer.remove_line(0), "a");
+        buffer.set_line(1, "B");
+        assert_eq!(buffer.to_string(), "x\nB\nc");
+
+        let mut single = TextBuffer::from("only");
+        single.remove_line(0);
+        assert_eq!(single.len_lines(), 1);
+        assert_eq!(single.line(0), "");
+    }
+
+    #[test]
+    fn test_changed_lines() {
+        let mut buffer = TextBuffer::from_lines(&["a", "b", "c", "d", "e"]);
+        assert_eq!(buffer.take_changed_lines(), Some(LineChanges { first: 0, last: 4 }));
+        assert_eq!(buffer.take_changed_lines(), None);
+
+        buffer.set_line(3, "D");
+        buffer.split_line(1, 1);
+        // The earlier edit moved down a line along with the text after the split
+        assert_eq!(buffer.take_changed_lines(), Some(LineChanges { first: 1, last: 4 }));
+
+        buffer.insert_char_at(4, 0, '!');
+        buffer.join_with_next(0);
+        assert_eq!(buffer.take_changed_lines(), Some(LineChanges { first: 0, last: 3 }));
+    }
+}
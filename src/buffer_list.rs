@@ -0,0 +1,277 @@
+use std::ops::Range;
+use std::path::Path;
+use crate::{buffer::TextBuffer, cursor, linter, mouse, syntax, undo};
+
+/// Identifies an open buffer; unlike its tab index it never changes
+pub type BufferId = usize;
+
+/// Cursor, selection and scroll position of a view onto a buffer
+#[derive(Clone, Default)]
+pub struct ViewState {
+    pub cursor: cursor::CursorController,
+    pub text_selection: Option<mouse::TextSelection>,
+    pub selection_anchor: Option<(usize, usize)>,
+    pub scroll_offset: usize,
+    pub scroll_offset_x: usize,
+}
+
+impl ViewState {
+    /// Pull positions that an edit made elsewhere left past the end back into `buffer`
+    pub fn clamp(&mut self, buffer: &TextBuffer) {
+        let last_row = buffer.len_lines().saturating_sub(1);
+        let row = self.cursor.row().min(last_row);
+        let col = self.cursor.col().min(buffer.line_len(row));
+        if (row, col) != (self.cursor.row(), self.cursor.col()) {
+            self.cursor.set_position(row, col);
+            self.cursor.clear_extra_carets();
+            self.text_selection = None;
+            self.selection_anchor = None;
+        }
+        self.scroll_offset = self.scroll_offset.min(last_row);
+    }
+}
+
+/// Everything the editor keeps per open file
+#[derive(Default)]
+pub struct BufferState {
+    pub buffer: TextBuffer,
+    pub filename: Option<String>,
+    // Language picked by hand, overriding detection
+    pub language: Option<String>,
+    pub modified: bool,
+    pub lint_issues: Vec<linter::LintIssue>,
+    // Language the buffer was last linted as, detected once rather than per edit
+    pub lint_language: Option<String>,
+    // Lint issues predate a configuration change and are redone when the buffer is next shown
+    pub lint_stale: bool,
+    pub highlights: syntax::HighlightCache,
+    pub undo_stack: undo::UndoStack,
+    // Undo revision of the text on disk; `None` if it was never saved
+    pub saved_revision: Option<u64>,
+    // Where the buffer was last shown
+    pub view: ViewState,
+}
+
+impl BufferState {
+    pub fn new(buffer: TextBuffer, filename: Option<String>) -> Self {
+        Self { buffer, filename, saved_revision: Some(0), ..Self::default() }
+    }
+}
+
+/// Short name shown on a tab: the file name without its directories
+pub fn tab_title(filename: &str) -> &str {
+    Path::new(filename)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(filename)
+}
+
+/// Open buffers in tab order.
+///
+/// The editor works on the active buffer's state directly, so its slot here
+/// is an empty placeholder; switching parks the live state in its slot and
+/// hands out the state of the buffer being switched to.
+#[derive(Default)]
+pub struct BufferList {
+    buffers: Vec<BufferState>,
+    ids: Vec<BufferId>,
+    active: usize,
+    next_id: BufferId,
+}
+
+impl BufferList {
+    /// A list holding just the active buffer
+    pub fn new() -> Self {
+        Self { buffers: vec![BufferState::default()], ids: vec![0], active: 0, next_id: 1 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    pub fn active_id(&self) -> BufferId {
+        self.ids[self.active]
+    }
+
+    /// Tab index of the buffer with `id`, if it is still open
+    pub fn index_of(&self, id: BufferId) -> Option<usize> {
+        self.ids.iter().position(|open| *open == id)
+    }
+
+    /// Parked state of an inactive buffer; the active slot is a placeholder
+    pub fn get(&self, idx: usize) -> Option<&BufferState> {
+        self.buffers.get(idx)
+    }
+
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut BufferState> {
+        self.buffers.get_mut(idx)
+    }
+
+    /// Index of the tab after the active one, wrapping around
+    pub fn next_index(&self) -> usize {
+        (self.active + 1) % self.buffers.len()
+    }
+
+    /// Index of the tab before the active one, wrapping around
+    pub fn prev_index(&self) -> usize {
+        (self.active + self.buffers.len() - 1) % self.buffers.len()
+    }
+
+    /// Inactive buffer showing `filename`, if any
+    pub fn find(&self, filename: &str) -> Option<usize> {
+        self.buffers
+            .iter()
+            .enumerate()
+            .find(|(idx, state)| *idx != self.active && state.filename.as_deref() == Some(filename))
+            .map(|(idx, _)| idx)
+    }
+
+    /// Park `current` and take out the state of buffer `idx`, which becomes active
+    pub fn switch_to(&mut self, idx: usize, current: BufferState) -> BufferState {
+        self.buffers[self.active] = current;
+        self.active = idx;
+        std::mem::take(&mut self.buffers[idx])
+    }
+
+    /// Park `current` and add a new active tab right after it
+    pub fn open(&mut self, current: BufferState) {
+        self.buffers[self.active] = current;
+        self.active += 1;
+        self.buffers.insert(self.active, BufferState::default());
+        self.ids.insert(self.active, self.next_id);
+        self.next_id += 1;
+    }
+
+    /// Drop the active buffer and take out the state of its neighbour.
+    ///
+    /// The tab to the right becomes active, or the one to the left when the
+    /// last tab is closed. Returns `None` if no other buffer is open.
+    pub fn close_active(&mut self) -> Option<BufferState> {
+        if self.buffers.len() == 1 {
+            return None;
+        }
+        self.buffers.remove(self.active);
+        self.ids.remove(self.active);
+        self.active = self.active.min(self.buffers.len() - 1);
+        Some(std::mem::take(&mut self.buffers[self.active]))
+    }
+
+    /// Parked states of every inactive buffer
+    pub fn parked_mut(&mut self) -> impl Iterator<Item = &mut BufferState> + '_ {
+        let active = self.active;
+        self.buffers
+            .iter_mut()
+            .enumerate()
+            .filter(move |(idx, _)| *idx != active)
+            .map(|(_, state)| state)
+    }
+
+    /// Inactive buffers with unsaved changes
+    pub fn modified(&self) -> impl Iterator<Item = usize> + '_ {
+        self.buffers
+            .iter()
+            .enumerate()
+            .filter(|(idx, state)| *idx != self.active && state.modified)
+            .map(|(idx, _)| idx)
+    }
+}
+
+/// Tabs that fit in `available` cells, always including `active`.
+///
+/// Tabs are dropped from the far end first, so the bar scrolls just enough
+/// to keep the active tab visible.
+pub fn visible_tabs(widths: &[usize], active: usize, available: usize) -> Range<usize> {
+    let mut range = active..active + 1;
+    let mut used = widths[active];
+    loop {
+        let right = widths.get(range.end).filter(|&&width| used + width <= available);
+        if let Some(width) = right {
+            used += width;
+            range.end += 1;
+            continue;
+        }
+        match range.start.checked_sub(1) {
+            Some(left) if used + widths[left] <= available => {
+                used += widths[left];
+                range.start = left;
+            }
+            _ => return range,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(name: &str) -> BufferState {
+        BufferState::new(TextBuffer::from_lines(&[name]), Some(name.to_string()))
+    }
+
+    #[test]
+    fn test_switch_keeps_state() {
+        let mut list = BufferList::new();
+        list.open(named("a.rs"));
+        let mut current = named("b.rs");
+        current.view.cursor.set_position(0, 2);
+        current.modified = true;
+
+        let a = list.switch_to(0, current);
+        assert_eq!(a.filename.as_deref(), Some("a.rs"));
+        assert_eq!(list.active(), 0);
+        assert_eq!(list.find("b.rs"), Some(1));
+        assert_eq!(list.modified().collect::<Vec<_>>(), vec![1]);
+
+        let b = list.switch_to(1, a);
+        assert_eq!(b.view.cursor.col(), 2);
+        assert!(b.modified);
+        assert_eq!(list.find("b.rs"), None);
+    }
+
+    #[test]
+    fn test_open_and_close_order() {
+        let mut list = BufferList::new();
+        list.open(named("a.rs"));
+        list.open(named("b.rs"));
+        assert_eq!((list.len(), list.active(), list.active_id()), (3, 2, 2));
+        assert_eq!((list.next_index(), list.prev_index()), (0, 1));
+
+        // Opening from the middle inserts next to the active tab
+        let a = list.switch_to(0, named("c.rs"));
+        list.open(a);
+        assert_eq!(list.active(), 1);
+        assert_eq!(list.get(2).and_then(|state| state.filename.as_deref()), Some("b.rs"));
+
+        // Ids stay with their buffers while tabs move around
+        assert_eq!((list.active_id(), list.index_of(1)), (3, Some(2)));
+        let next = list.close_active().unwrap();
+        assert_eq!((next.filename.as_deref(), list.active()), (Some("b.rs"), 1));
+        assert_eq!((list.active_id(), list.index_of(3)), (1, None));
+        let next = list.close_active().unwrap();
+        assert_eq!((next.filename.as_deref(), list.active()), (Some("c.rs"), 1));
+        // Closing the last tab activates the one to its left
+        let last = list.close_active().unwrap();
+        assert_eq!((last.filename.as_deref(), list.active()), (Some("a.rs"), 0));
+        assert!(list.close_active().is_none());
+        assert_eq!(tab_title("src/main.rs"), "main.rs");
+    }
+
+    #[test]
+    fn test_visible_tabs() {
+        let widths = [4, 4, 4, 4];
+        assert_eq!(visible_tabs(&widths, 0, 100), 0..4);
+        assert_eq!(visible_tabs(&widths, 0, 9), 0..2);
+        assert_eq!(visible_tabs(&widths, 3, 9), 2..4);
+        assert_eq!(visible_tabs(&widths, 2, 12), 1..4);
+        // The active tab is shown even when it is too wide on its own
+        assert_eq!(visible_tabs(&widths, 1, 2), 1..2);
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::VecDeque;
+use std::io::{self, stdout, Write};
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+/// Largest payload sent over OSC 52; many terminals drop anything bigger
+const OSC52_MAX_BYTES: usize = 100_000;
+
+/// Clipboard with an internal register and an optional OSC 52 terminal backend.
+///
+/// The register always holds the last copied text, so copy/paste works
+/// without any platform clipboard daemon. When OSC 52 is enabled, copies are
+/// also forwarded to the terminal, which places them on the system clipboard.
+pub struct Clipboard {
+    register: Option<String>,
+    osc52_enabled: bool,
+}
+
+impl Clipboard {
+    /// Create a clipboard with the OSC 52 backend enabled
+    pub fn new() -> Self {
+        Self {
+            register: None,
+            osc52_enabled: true,
+        }
+    }
+
+    /// Create a clipboard that only uses the internal register
+    pub fn internal_only() -> Self {
+        Self {
+            register: None,
+            osc52_enabled: false,
+        }
+    }
+
+    /// Enable or disable forwarding copies to the terminal clipboard
+    pub fn set_osc52_enabled(&mut self, enabled: bool) {
+        self.osc52_enabled = enabled;
+    }
+
+    /// Check whether copies are forwarded to the terminal clipboard
+    pub fn is_osc52_enabled(&self) -> bool {
+        self.osc52_enabled
+    }
+
+    /// Store text in the register and forward it to the terminal if enabled.
+    ///
+    /// Returns false if the text was too large to forward and only the register holds it.
+    pub fn copy(&mut self, text: &str) -> io::Result<bool> {
+        self.copy_to(text, &mut stdout())
+    }
+
+    /// Store text in the register and write the OSC 52 sequence to `writer`
+    pub fn copy_to<W: Write>(&mut self, text: &str, writer: &mut W) -> io::Result<bool> {
+        self.register = Some(text.to_string());
+        if !self.osc52_enabled {
+            return Ok(true);
+        }
+        // The limit applies to the base64 payload, a third larger than the text
+        if text.len().div_ceil(3) * 4 > OSC52_MAX_BYTES {
+            return Ok(false);
+        }
+
+        let in_tmux = std::env::var_os("TMUX").is_some();
+        writer.write_all(osc52_sequence(text, in_tmux).as_bytes())?;
+        writer.flush()?;
+        Ok(true)
+    }
+
+    /// Get the text to paste, if anything was copied
+    pub fn paste(&self) -> Option<&str> {
+        self.register.as_deref()
+    }
+
+    /// Check whether the register holds any text
+    pub fn is_empty(&self) -> bool {
+        self.register.as_deref().is_none_or(str::is_empty)
+    }
+
+    /// Empty the register
+    pub fn clear(&mut self) {
+        self.register = None;
+    }
+}
+
+impl Default for Clipboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Number of kills an Emacs kill ring remembers by default
+const KILL_RING_CAPACITY: usize = 60;
+
+/// Emacs-style kill ring: the most recent kills, newest first.
+///
+/// Yanking inserts the entry under the yank pointer; yank-pop rotates the
+/// pointer to older kills so the text just yanked can be swapped out.
+pub struct KillRing {
+    entries: VecDeque<String>,
+    capacity: usize,
+    yank_index: usize,
+}
+
+impl KillRing {
+    /// Create an empty kill ring with the default capacity
+    pub fn new() -> Self {
+        Self::with_capacity(KILL_RING_CAPACITY)
+    }
+
+    /// Create an empty kill ring holding at most `capacity` kills
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity: capacity.max(1),
+            yank_index: 0,
+        }
+    }
+
+    /// Add a kill as the newest entry and point the next yank at it
+    pub fn push(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        self.entries.push_front(text.to_string());
+        self.entries.truncate(self.capacity);
+        self.yank_index = 0;
+    }
+
+    /// Text the next yank inserts
+    pub fn yank(&self) -> Option<&str> {
+        self.entries.get(self.yank_index).map(String::as_str)
+    }
+
+    /// Move the yank pointer to the next older kill, wrapping around, and return it
+    pub fn rotate(&mut self) -> Option<&str> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        self.yank_index = (self.yank_index + 1) % self.entries.len();
+        self.yank()
+    }
+
+    /// Number of kills in the ring
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether nothing has been killed yet
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Default for KillRing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Build the OSC 52 escape sequence that sets the terminal clipboard.
+///
+/// Inside tmux the sequence is wrapped in a DCS passthrough so that it
+/// reaches the outer terminal.
+pub fn osc52_sequence(text: &str, tmux_passthrough: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+    if tmux_passthrough {
+        format!("\x1bPtmux;\x1b{}\x1b\\", sequence)
+    } else {
+        sequence
+    }
+}
+
+/// Normalize pasted text so that every line break becomes `\n`
+pub fn normalize_line_endings(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_copy_and_paste() {
+        let mut clipboard = Clipboard::internal_only();
+        assert!(clipboard.is_empty());
+        assert_eq!(clipboard.paste(), None);
+
+        let mut output = Vec::new();
+        clipboard.copy_to("hello\nworld", &mut output).unwrap();
+        assert_eq!(clipboard.paste(), Some("hello\nworld"));
+        // Internal-only mode never talks to the terminal
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_osc52_size_limit() {
+        let mut clipboard = Clipboard::new();
+        let mut output = Vec::new();
+        // Base64 turns 75,000 bytes into exactly the 100,000 allowed
+        let text = "x".repeat(75_000);
+        assert!(clipboard.copy_to(&text, &mut output).unwrap());
+        assert!(!output.is_empty());
+
+        output.clear();
+        let text = "x".repeat(75_001);
+        assert!(!clipboard.copy_to(&text, &mut output).unwrap());
+        assert!(output.is_empty());
+        assert_eq!(clipboard.paste(), Some(text.as_str()));
+    }
+
+    #[test]
+    fn test_kill_ring_yank_and_rotate() {
+        let mut ring = KillRing::with_capacity(2);
+        assert_eq!(ring.yank(), None);
+        assert_eq!(ring.rotate(), None);
+
+        ring.push("one");
+        ring.push("two");
+        ring.push("");
+        ring.push("three");
+        assert_eq!(ring.len(), 2);
+        assert_eq!(ring.yank(), Some("three"));
+        assert_eq!(ring.rotate(), Some("two"));
+        assert_eq!(ring.rotate(), Some("three"));
+
+        // A new kill resets the yank pointer
+        ring.rotate();
+        ring.push("four");
+        assert_eq!(ring.yank(), Some("four"));
+    }
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(osc52_sequence("hi", false), "\x1b]52;c;aGk=\x07");
+        assert_eq!(osc52_sequence("hi", true), "\x1bPtmux;\x1b\x1b]52;c;aGk=\x07\x1b\\");
+    }
+
+    #[test]
+    fn test_normalize_line_endings() {
+        assert_eq!(normalize_line_endings("a\r\nb\rc\nd"), "a\nb\nc\nd");
+    }
+}
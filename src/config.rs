@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+
+/// Directory holding user configuration (`$XDG_CONFIG_HOME/hello_tui` or `~/.config/hello_tui`)
+pub fn config_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))?;
+    Some(base.join("hello_tui"))
+}
+
+/// Path of a file inside the configuration directory
+pub fn config_file(name: &str) -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(name))
+}
+
+/// File holding choices made from within the editor
+const SETTINGS_FILE: &str = "settings.toml";
+
+/// Preferences the editor remembers between sessions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Settings {
+    /// Name of the syntax theme picked last
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    /// Languages picked by hand, by absolute file path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub languages: BTreeMap<String, String>,
+}
+
+impl Settings {
+    /// Read the settings file; a missing or unreadable file gives the defaults
+    pub fn load() -> Self {
+        config_file(SETTINGS_FILE)
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|text| toml::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    /// Language picked for the file at `path`, if any
+    pub fn language_for(&self, path: &str) -> Option<String> {
+        self.languages.get(&settings_key(path)).cloned()
+    }
+
+    /// Remember the language picked for the file at `path`; `None` goes back to detection
+    pub fn set_language(&mut self, path: &str, language: Option<String>) {
+        let key = settings_key(path);
+        match language {
+            Some(language) => self.languages.insert(key, language),
+            None => self.languages.remove(&key),
+        };
+    }
+
+    /// Write the settings file, creating the configuration directory if needed
+    pub fn save(&self) -> io::Result<()> {
+        let path = config_file(SETTINGS_FILE)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no configuration directory"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let text = toml::to_string(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, text)
+    }
+}
+
+/// Key a file is remembered under: its absolute path when it exists
+fn settings_key(path: &str) -> String {
+    std::fs::canonicalize(path)
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_round_trip() {
+        let mut settings = Settings { theme: Some("Solarized (dark)".to_string()), ..Settings::default() };
+        settings.set_language("notes.txt", Some("JSON".to_string()));
+        let text = toml::to_string(&settings).unwrap();
+        assert_eq!(toml::from_str::<Settings>(&text).unwrap(), settings);
+        assert_eq!(settings.language_for("notes.txt").as_deref(), Some("JSON"));
+        settings.set_language("notes.txt", None);
+        assert_eq!(settings.language_for("notes.txt"), None);
+        // Missing entries fall back to their defaults
+        assert_eq!(toml::from_str::<Settings>("").unwrap(), Settings::default());
+    }
+}
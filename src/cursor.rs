@@ -1,7 +1,11 @@
 use crate::keys::Direction;
+use crate::buffer::TextBuffer;
+use crate::mouse::TextSelection;
+use crate::undo::Edit;
+use crate::text_metrics;
 
 /// Cursor position and movement management for the text editor
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct CursorPosition {
     pub row: usize,
     pub col: usize,
@@ -17,21 +21,97 @@ impl CursorPosition {
     }
 }
 
-/// Cursor controller for managing cursor movement and positioning
+/// Secondary caret, with the selection it owns (if any)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Caret {
+    pub position: CursorPosition,
+    pub selection: Option<TextSelection>,
+}
+
+impl Caret {
+    pub fn new(row: usize, col: usize, selection: Option<TextSelection>) -> Self {
+        Self {
+            position: CursorPosition::new(row, col),
+            selection,
+        }
+    }
+}
+
+/// Cursor controller for managing cursor movement and positioning.
+///
+/// The primary caret is the one every single-cursor operation works on;
+/// secondary carets are added for simultaneous edits and follow movement.
+#[derive(Clone)]
 pub struct CursorController {
     position: CursorPosition,
+    extra: Vec<Caret>,
 }
 
 impl CursorController {
     pub fn new() -> Self {
         Self {
             position: CursorPosition::zero(),
+            extra: Vec::new(),
         }
     }
 
     pub fn with_position(row: usize, col: usize) -> Self {
         Self {
             position: CursorPosition::new(row, col),
+            extra: Vec::new(),
+        }
+    }
+
+    /// Secondary carets, in the order they were added
+    pub fn extra_carets(&self) -> &[Caret] {
+        &self.extra
+    }
+
+    pub fn has_extra_carets(&self) -> bool {
+        !self.extra.is_empty()
+    }
+
+    /// Number of carets, including the primary one
+    pub fn caret_count(&self) -> usize {
+        self.extra.len() + 1
+    }
+
+    /// Add a secondary caret; returns false if a caret is already there
+    pub fn add_caret(&mut self, caret: Caret) -> bool {
+        if caret.position == self.position || self.extra.iter().any(|c| c.position == caret.position) {
+            return false;
+        }
+        self.extra.push(caret);
+        true
+    }
+
+    /// Replace all secondary carets
+    pub fn set_extra_carets(&mut self, carets: Vec<Caret>) {
+        self.extra.clear();
+        for caret in carets {
+            self.add_caret(caret);
+        }
+    }
+
+    /// Drop every secondary caret, keeping only the primary one
+    pub fn clear_extra_carets(&mut self) {
+        self.extra.clear();
+    }
+
+    /// Row span covered by all carets
+    pub fn caret_rows(&self) -> (usize, usize) {
+        self.extra.iter().fold((self.position.row, self.position.row), |(top, bottom), caret| {
+            (top.min(caret.position.row), bottom.max(caret.position.row))
+        })
+    }
+
+    /// Move every secondary caret with `motion`, dropping their selections and any duplicates
+    pub fn move_extra_carets(&mut self, mut motion: impl FnMut(&mut CursorPosition)) {
+        let carets = std::mem::take(&mut self.extra);
+        for mut caret in carets {
+            motion(&mut caret.position);
+            caret.selection = None;
+            self.add_caret(caret);
         }
     }
 
@@ -57,57 +137,25 @@ impl CursorController {
     }
 
     /// Move cursor in a specific direction
-    pub fn move_cursor(&mut self, direction: Direction, lines: &[String]) -> bool {
+    pub fn move_cursor(&mut self, direction: Direction, buffer: &TextBuffer) -> bool {
         let old_position = self.position.clone();
         
-        match direction {
-            Direction::Up => {
-                if self.position.row > 0 {
-                    self.position.row -= 1;
-                    let line_len = lines[self.position.row].len();
-                    self.position.col = self.position.col.min(line_len);
-                }
-            }
-            Direction::Down => {
-                if self.position.row < lines.len() - 1 {
-                    self.position.row += 1;
-                    let line_len = lines[self.position.row].len();
-                    self.position.col = self.position.col.min(line_len);
-                }
-            }
-            Direction::Left => {
-                if self.position.col > 0 {
-                    self.position.col -= 1;
-                } else if self.position.row > 0 {
-                    self.position.row -= 1;
-                    self.position.col = lines[self.position.row].len();
-                }
-            }
-            Direction::Right => {
-                let line_len = lines[self.position.row].len();
-                if self.position.col < line_len {
-                    self.position.col += 1;
-                } else if self.position.row < lines.len() - 1 {
-                    self.position.row += 1;
-                    self.position.col = 0;
-                }
-            }
-        }
+        cursor_utils::step_position(&mut self.position, &direction, buffer);
 
         // Return true if position changed
         old_position.row != self.position.row || old_position.col != self.position.col
     }
 
     /// Move cursor to a specific position with scroll offset adjustment
-    pub fn move_to_position(&mut self, row: usize, col: usize, scroll_offset: usize, lines: &[String]) -> bool {
+    pub fn move_to_position(&mut self, row: usize, col: usize, scroll_offset: usize, buffer: &TextBuffer) -> bool {
         let old_position = self.position.clone();
         
         // Adjust for scroll offset
         let actual_row = row + scroll_offset;
         
-        if actual_row < lines.len() {
+        if actual_row < buffer.len_lines() {
             self.position.row = actual_row;
-            let line_len = lines[self.position.row].len();
+            let line_len = buffer.line_len(self.position.row);
             self.position.col = col.min(line_len);
         }
 
@@ -123,10 +171,10 @@ impl CursorController {
     }
 
     /// Move cursor to the end of the current line
-    pub fn move_to_line_end(&mut self, lines: &[String]) -> bool {
+    pub fn move_to_line_end(&mut self, buffer: &TextBuffer) -> bool {
         let old_col = self.position.col;
-        if self.position.row < lines.len() {
-            self.position.col = lines[self.position.row].len();
+        if self.position.row < buffer.len_lines() {
+            self.position.col = buffer.line_len(self.position.row);
         }
         old_col != self.position.col
     }
@@ -140,28 +188,26 @@ impl CursorController {
     }
 
     /// Move cursor to the end of the document
-    pub fn move_to_document_end(&mut self, lines: &[String]) -> bool {
+    pub fn move_to_document_end(&mut self, buffer: &TextBuffer) -> bool {
         let old_position = self.position.clone();
-        self.position.row = if lines.is_empty() { 0 } else { lines.len() - 1 };
-        self.position.col = if lines.is_empty() { 0 } else { lines[self.position.row].len() };
+        self.position.row = buffer.len_lines() - 1;
+        self.position.col = buffer.line_len(self.position.row);
         old_position.row != self.position.row || old_position.col != self.position.col
     }
 
     /// Validate and adjust cursor position to ensure it's within bounds
-    pub fn validate_position(&mut self, lines: &[String]) -> bool {
+    pub fn validate_position(&mut self, buffer: &TextBuffer) -> bool {
         let old_position = self.position.clone();
         
         // Ensure row is within bounds
-        if self.position.row >= lines.len() {
-            self.position.row = if lines.is_empty() { 0 } else { lines.len() - 1 };
+        if self.position.row >= buffer.len_lines() {
+            self.position.row = buffer.len_lines() - 1;
         }
 
         // Ensure column is within bounds for the current line
-        if self.position.row < lines.len() {
-            let line_len = lines[self.position.row].len();
-            if self.position.col > line_len {
-                self.position.col = line_len;
-            }
+        let line_len = buffer.line_len(self.position.row);
+        if self.position.col > line_len {
+            self.position.col = line_len;
         }
 
         // Return true if position was adjusted
@@ -200,6 +246,23 @@ impl CursorController {
         new_scroll_offset.min(max_scroll)
     }
 
+    /// Adjust horizontal scroll offset (in screen cells) to keep the cursor visible on `line`
+    pub fn adjust_horizontal_scroll(&self, scroll_offset_x: usize, viewport_width: usize, line: &str) -> usize {
+        let viewport_width = viewport_width.max(1);
+        let cursor_x = text_metrics::display_col(line, self.position.col);
+
+        // If cursor is left of the viewport, scroll left
+        if cursor_x < scroll_offset_x {
+            cursor_x
+        }
+        // If cursor is right of the viewport, scroll right
+        else if cursor_x >= scroll_offset_x + viewport_width {
+            cursor_x - (viewport_width - 1)
+        } else {
+            scroll_offset_x
+        }
+    }
+
     /// Get cursor position for mouse click (adjusted for scroll)
     pub fn position_for_mouse(&self, scroll_offset: usize) -> (usize, usize) {
         (self.position.row.saturating_sub(scroll_offset), self.position.col)
@@ -216,9 +279,67 @@ impl Default for CursorController {
 pub mod cursor_utils {
     use super::*;
 
-    /// Calculate the preferred column for vertical movement
-    pub fn calculate_preferred_column(current_col: usize, target_line: &str) -> usize {
-        current_col.min(target_line.len())
+    /// Move a position one step in a direction, wrapping across line ends.
+    ///
+    /// Left and right step over whole grapheme clusters; up and down keep the
+    /// screen column, so the cursor stays aligned across wide characters.
+    pub fn step_position(position: &mut CursorPosition, direction: &Direction, buffer: &TextBuffer) {
+        match direction {
+            Direction::Up => {
+                if position.row > 0 {
+                    let current_line = buffer.line(position.row);
+                    position.row -= 1;
+                    position.col = calculate_preferred_column(&current_line, position.col, &buffer.line(position.row));
+                }
+            }
+            Direction::Down => {
+                if position.row < buffer.len_lines() - 1 {
+                    let current_line = buffer.line(position.row);
+                    position.row += 1;
+                    position.col = calculate_preferred_column(&current_line, position.col, &buffer.line(position.row));
+                }
+            }
+            Direction::Left => {
+                if position.col > 0 {
+                    position.col = text_metrics::prev_boundary(&buffer.line(position.row), position.col);
+                } else if position.row > 0 {
+                    position.row -= 1;
+                    position.col = buffer.line_len(position.row);
+                }
+            }
+            Direction::Right => {
+                let line_len = buffer.line_len(position.row);
+                if position.col < line_len {
+                    position.col = text_metrics::next_boundary(&buffer.line(position.row), position.col);
+                } else if position.row < buffer.len_lines() - 1 {
+                    position.row += 1;
+                    position.col = 0;
+                }
+            }
+        }
+    }
+
+    /// Move `position` to the next or previous word boundary, crossing line ends
+    pub fn step_word(position: &mut CursorPosition, direction: &Direction, buffer: &TextBuffer) {
+        let line = buffer.line(position.row);
+        match direction {
+            Direction::Right if position.col >= buffer.line_len(position.row) && position.row + 1 < buffer.len_lines() => {
+                position.row += 1;
+                position.col = 0;
+            }
+            Direction::Right => position.col = find_next_word_boundary(&line, position.col),
+            Direction::Left if position.col == 0 && position.row > 0 => {
+                position.row -= 1;
+                position.col = buffer.line_len(position.row);
+            }
+            Direction::Left => position.col = find_prev_word_boundary(&line, position.col),
+            _ => step_position(position, direction, buffer),
+        }
+    }
+
+    /// Calculate the column on `target_line` at the same screen column as `current_col` on `current_line`
+    pub fn calculate_preferred_column(current_line: &str, current_col: usize, target_line: &str) -> usize {
+        text_metrics::col_at_display(target_line, text_metrics::display_col(current_line, current_col))
     }
 
     /// Find the next word boundary for cursor navigation
@@ -267,14 +388,57 @@ pub mod cursor_utils {
         col
     }
 
+    /// End of the next word at or after `start_col` (Emacs `forward-word`), if the line has one.
+    ///
+    /// Words are runs of alphanumeric characters and underscores.
+    pub fn find_word_end(line: &str, start_col: usize) -> Option<usize> {
+        let chars: Vec<char> = line.chars().collect();
+        let is_word = |ch: char| ch.is_alphanumeric() || ch == '_';
+        let mut col = start_col.min(chars.len());
+
+        while col < chars.len() && !is_word(chars[col]) {
+            col += 1;
+        }
+        if col == chars.len() {
+            return None;
+        }
+        while col < chars.len() && is_word(chars[col]) {
+            col += 1;
+        }
+        Some(col)
+    }
+
+    /// Start of the previous word before `start_col` (Emacs `backward-word`), if the line has one
+    pub fn find_word_start(line: &str, start_col: usize) -> Option<usize> {
+        let chars: Vec<char> = line.chars().collect();
+        let is_word = |ch: char| ch.is_alphanumeric() || ch == '_';
+        let mut col = start_col.min(chars.len());
+
+        while col > 0 && !is_word(chars[col - 1]) {
+            col -= 1;
+        }
+        if col == 0 {
+            return None;
+        }
+        while col > 0 && is_word(chars[col - 1]) {
+            col -= 1;
+        }
+        Some(col)
+    }
+
+    /// Column of the first non-whitespace char, or the line length for blank lines
+    pub fn first_non_blank(line: &str) -> usize {
+        line.chars().take_while(|ch| ch.is_whitespace()).count()
+    }
+
     /// Check if cursor is at the start of a line
     pub fn is_at_line_start(cursor: &CursorPosition) -> bool {
         cursor.col == 0
     }
 
     /// Check if cursor is at the end of a line
-    pub fn is_at_line_end(cursor: &CursorPosition, lines: &[String]) -> bool {
-        cursor.row < lines.len() && cursor.col == lines[cursor.row].len()
+    pub fn is_at_line_end(cursor: &CursorPosition, buffer: &TextBuffer) -> bool {
+        cursor.row < buffer.len_lines() && cursor.col == buffer.line_len(cursor.row)
     }
 
     /// Check if cursor is at the start of the document
@@ -283,10 +447,211 @@ pub mod cursor_utils {
     }
 
     /// Check if cursor is at the end of the document
-    pub fn is_at_document_end(cursor: &CursorPosition, lines: &[String]) -> bool {
-        if lines.is_empty() {
-            return cursor.row == 0 && cursor.col == 0;
+    pub fn is_at_document_end(cursor: &CursorPosition, buffer: &TextBuffer) -> bool {
+        cursor.row == buffer.len_lines() - 1 && cursor.col == buffer.line_len(cursor.row)
+    }
+
+    /// Parse Go To Line input into a 0-based position.
+    ///
+    /// Accepts `line`, `line:col`, `+N`/`-N` relative to `current_row` and
+    /// `N%` of the document. Lines are 1-based and clamp to the buffer;
+    /// a missing column means the start of the line.
+    pub fn parse_goto_target(input: &str, current_row: usize, buffer: &TextBuffer) -> Option<CursorPosition> {
+        let input = input.trim();
+        let last_row = buffer.len_lines().saturating_sub(1);
+
+        let (line_part, col_part) = match input.split_once(':') {
+            Some((line, col)) => (line.trim(), Some(col.trim())),
+            None => (input, None),
+        };
+
+        let row = if let Some(percent) = line_part.strip_suffix('%') {
+            let percent: usize = percent.trim().parse().ok()?;
+            last_row * percent.min(100) / 100
+        } else if let Some(offset) = line_part.strip_prefix('+') {
+            current_row.saturating_add(offset.parse().ok()?)
+        } else if let Some(offset) = line_part.strip_prefix('-') {
+            current_row.saturating_sub(offset.parse().ok()?)
+        } else {
+            let line: usize = line_part.parse().ok()?;
+            line.saturating_sub(1)
         }
-        cursor.row == lines.len() - 1 && cursor.col == lines[cursor.row].len()
+        .min(last_row);
+
+        let col = match col_part.filter(|col| !col.is_empty()) {
+            Some(col) => {
+                let col: usize = col.parse().ok()?;
+                col.saturating_sub(1).min(buffer.line_len(row))
+            }
+            None => 0,
+        };
+
+        Some(CursorPosition::new(row, col))
+    }
+}
+
+/// Edit applied at every caret at once
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CaretEdit<'a> {
+    /// Replace the selection (or insert at the caret) with text
+    Insert(&'a str),
+    /// Delete the selection, or the grapheme cluster before the caret
+    DeleteBackward,
+    /// Delete the selection, or the grapheme cluster after the caret
+    DeleteForward,
+}
+
+/// Apply an edit at several carets, given as char ranges (empty when nothing is selected).
+///
+/// Carets are processed from the end of the buffer backwards so earlier
+/// positions stay valid. Returns the edits in the order they were applied
+/// and the new char index of each caret, in the order of `ranges`.
+pub fn edit_at_carets(buffer: &mut TextBuffer, ranges: &[(usize, usize)], edit: CaretEdit) -> (Vec<Edit>, Vec<usize>) {
+    let mut order: Vec<usize> = (0..ranges.len()).collect();
+    order.sort_by_key(|&idx| std::cmp::Reverse(ranges[idx].0));
+
+    let mut edits = Vec::new();
+    let mut carets = vec![0; ranges.len()];
+    let mut deltas = vec![0isize; ranges.len()];
+    // Everything at or after `limit` belongs to carets that were already edited
+    let mut limit = buffer.len_chars();
+
+    for &idx in &order {
+        let (start, end) = ranges[idx];
+        let end = end.min(limit);
+        let start = start.min(end);
+        let len_before = buffer.len_chars() as isize;
+
+        let mut caret_edits = Vec::new();
+        if start < end {
+            caret_edits.push(Edit::delete(start, buffer.slice(start, end)));
+        }
+        let caret = match edit {
+            CaretEdit::Insert(text) => {
+                if !text.is_empty() {
+                    caret_edits.push(Edit::insert(start, text));
+                }
+                start + text.chars().count()
+            }
+            CaretEdit::DeleteBackward if start == end && start > 0 => {
+                let prev = buffer.prev_grapheme_boundary(start);
+                caret_edits.push(Edit::delete(prev, buffer.slice(prev, start)));
+                prev
+            }
+            CaretEdit::DeleteForward if start == end && start < limit => {
+                let next = buffer.next_grapheme_boundary(start).min(limit);
+                caret_edits.push(Edit::delete(start, buffer.slice(start, next)));
+                start
+            }
+            _ => start,
+        };
+
+        for caret_edit in caret_edits {
+            caret_edit.apply(buffer);
+            edits.push(caret_edit);
+        }
+        carets[idx] = caret;
+        deltas[idx] = buffer.len_chars() as isize - len_before;
+        limit = caret.min(start);
+    }
+
+    // Shift each caret by the length changes made before it in the document
+    let mut shift = 0isize;
+    for &idx in order.iter().rev() {
+        carets[idx] = (carets[idx] as isize + shift) as usize;
+        shift += deltas[idx];
+    }
+
+    (edits, carets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::cursor_utils::{self, parse_goto_target};
+    use super::{edit_at_carets, Caret, CaretEdit, CursorController, CursorPosition};
+    use crate::keys::Direction;
+    use crate::buffer::TextBuffer;
+
+    #[test]
+    fn test_parse_goto_target() {
+        let buffer = TextBuffer::from_lines(&["one", "two", "three", "four", "five"]);
+        let target = |input: &str| parse_goto_target(input, 2, &buffer).map(|pos| (pos.row, pos.col));
+
+        assert_eq!(target("4"), Some((3, 0)));
+        assert_eq!(target("3:4"), Some((2, 3)));
+        assert_eq!(target("3:"), Some((2, 0)));
+        assert_eq!(target("+1"), Some((3, 0)));
+        assert_eq!(target("-5"), Some((0, 0)));
+        assert_eq!(target("50%"), Some((2, 0)));
+        assert_eq!(target("100%"), Some((4, 0)));
+        // Out-of-range lines and columns clamp to the buffer
+        assert_eq!(target("99:99"), Some((4, 4)));
+        assert_eq!(target("abc"), None);
+        assert_eq!(target(""), None);
+    }
+
+    #[test]
+    fn test_emacs_word_motions() {
+        let line = "  foo_bar, baz!";
+        assert_eq!(cursor_utils::find_word_end(line, 0), Some(9));
+        assert_eq!(cursor_utils::find_word_end(line, 9), Some(14));
+        assert_eq!(cursor_utils::find_word_end(line, 14), None);
+        assert_eq!(cursor_utils::find_word_start(line, 15), Some(11));
+        assert_eq!(cursor_utils::find_word_start(line, 11), Some(2));
+        assert_eq!(cursor_utils::find_word_start(line, 2), None);
+    }
+
+    #[test]
+    fn test_horizontal_scroll() {
+        let mut cursor = CursorController::with_position(0, 5);
+        let line = "x".repeat(30);
+        assert_eq!(cursor.adjust_horizontal_scroll(0, 10, &line), 0);
+        cursor.set_position(0, 25);
+        assert_eq!(cursor.adjust_horizontal_scroll(0, 10, &line), 16);
+        assert_eq!(cursor.adjust_horizontal_scroll(20, 10, &line), 20);
+        cursor.set_position(0, 3);
+        assert_eq!(cursor.adjust_horizontal_scroll(20, 10, &line), 3);
+        // Scrolling is measured in screen cells, so wide chars count twice
+        cursor.set_position(0, 8);
+        assert_eq!(cursor.adjust_horizontal_scroll(0, 10, &"日".repeat(10)), 7);
+    }
+
+    #[test]
+    fn test_extra_carets() {
+        let buffer = TextBuffer::from_lines(&["abc", "d", "efg"]);
+        let mut cursor = CursorController::with_position(0, 2);
+        assert!(cursor.add_caret(Caret::new(2, 2, None)));
+        assert!(!cursor.add_caret(Caret::new(0, 2, None)));
+        assert_eq!(cursor.caret_count(), 2);
+        assert_eq!(cursor.caret_rows(), (0, 2));
+
+        cursor.move_extra_carets(|position| cursor_utils::step_position(position, &Direction::Up, &buffer));
+        assert_eq!(cursor.extra_carets()[0].position, CursorPosition::new(1, 1));
+
+        cursor.move_extra_carets(|position| cursor_utils::step_word(position, &Direction::Left, &buffer));
+        assert_eq!(cursor.extra_carets()[0].position, CursorPosition::new(1, 0));
+        cursor.move_extra_carets(|position| cursor_utils::step_word(position, &Direction::Left, &buffer));
+        assert_eq!(cursor.extra_carets()[0].position, CursorPosition::new(0, 3));
+
+        cursor.clear_extra_carets();
+        assert!(!cursor.has_extra_carets());
+    }
+
+    #[test]
+    fn test_edit_at_carets() {
+        let mut buffer = TextBuffer::from("ab\ncd");
+        let (edits, carets) = edit_at_carets(&mut buffer, &[(4, 4), (1, 1)], CaretEdit::Insert("X"));
+        assert_eq!(buffer.to_string(), "aXb\ncXd");
+        assert_eq!(carets, vec![6, 2]);
+        assert_eq!(edits.len(), 2);
+
+        let (_, carets) = edit_at_carets(&mut buffer, &[(2, 2), (6, 6)], CaretEdit::DeleteBackward);
+        assert_eq!(buffer.to_string(), "ab\ncd");
+        assert_eq!(carets, vec![1, 4]);
+
+        // Selections are replaced, empty carets just receive the text
+        let (_, carets) = edit_at_carets(&mut buffer, &[(0, 2), (3, 3)], CaretEdit::Insert("--"));
+        assert_eq!(buffer.to_string(), "--\n--cd");
+        assert_eq!(carets, vec![2, 5]);
     }
 }
@@ -1,5 +1,5 @@
 use std::io;
-use hello_tui::{file_io, syntax};
+use hello_tui::{file_io, syntax, text_metrics, cursor::cursor_utils, buffer::TextBuffer};
 use crossterm::{
     terminal::{enable_raw_mode, disable_raw_mode, Clear, ClearType},
     cursor::{MoveTo, Show, Hide},
@@ -10,7 +10,7 @@ use std::io::{stdout, Write};
 
 #[derive(Default)]
 struct QuickEditor {
-    lines: Vec<String>,
+    buffer: TextBuffer,
     cursor_row: usize,
     cursor_col: usize,
     filename: Option<String>,
@@ -31,7 +31,7 @@ enum EditorMode {
 impl QuickEditor {
     fn new(filename: Option<String>) -> io::Result<Self> {
         let mut editor = Self {
-            lines: vec!["".to_string()],
+            buffer: TextBuffer::new(),
             cursor_row: 0,
             cursor_col: 0,
             filename: filename.clone(),
@@ -43,25 +43,25 @@ impl QuickEditor {
         };
 
         if let Some(filename) = filename {
-            if let Err(_) = editor.load_file(&filename) {
-                editor.lines = vec!["".to_string()];
+            if editor.load_file(&filename).is_err() {
+                editor.buffer = TextBuffer::new();
                 editor.status_message = format!("New file: {}", filename);
             }
         } else {
-            editor.lines = vec![
-                "🚀 Quick Text Editor".to_string(),
-                "".to_string(),
-                "Press 'i' to start editing".to_string(),
-                "Press 'q' to quit, 's' to save".to_string(),
-                "".to_string(),
-            ];
+            editor.buffer = TextBuffer::from_lines(&[
+                "🚀 Quick Text Editor",
+                "",
+                "Press 'i' to start editing",
+                "Press 'q' to quit, 's' to save",
+                "",
+            ]);
         }
 
         Ok(editor)
     }
 
     fn load_file(&mut self, filename: &str) -> io::Result<()> {
-        self.lines = file_io::FileIO::load_file(filename)?;
+        self.buffer = file_io::FileIO::load_buffer(filename)?;
         self.filename = Some(filename.to_string());
         self.status_message = format!("Loaded: {}", filename);
         Ok(())
@@ -74,48 +74,39 @@ impl QuickEditor {
             "untitled.txt".to_string()
         };
 
-        file_io::FileIO::save_file(&filename, &self.lines)?;
+        file_io::FileIO::save_buffer(&filename, &self.buffer)?;
         self.filename = Some(filename.clone());
         self.status_message = format!("Saved: {}", filename);
         Ok(())
     }
 
     fn insert_char(&mut self, ch: char) {
-        if self.cursor_row < self.lines.len() {
-            let line = &mut self.lines[self.cursor_row];
-            let mut chars: Vec<char> = line.chars().collect();
-            chars.insert(self.cursor_col, ch);
-            *line = chars.into_iter().collect();
+        if self.cursor_row < self.buffer.len_lines() {
+            self.buffer.insert_char_at(self.cursor_row, self.cursor_col, ch);
             self.cursor_col += 1;
         }
     }
 
     fn delete_char(&mut self) {
-        if self.cursor_col > 0 && self.cursor_row < self.lines.len() {
-            let line = &mut self.lines[self.cursor_row];
-            let mut chars: Vec<char> = line.chars().collect();
-            if self.cursor_col <= chars.len() && self.cursor_col > 0 {
-                chars.remove(self.cursor_col - 1);
-                *line = chars.into_iter().collect();
-                self.cursor_col -= 1;
+        if self.cursor_col > 0 && self.cursor_row < self.buffer.len_lines() {
+            if self.cursor_col <= self.buffer.line_len(self.cursor_row) {
+                // Remove the whole grapheme cluster before the cursor
+                let start = text_metrics::prev_boundary(&self.buffer.line(self.cursor_row), self.cursor_col);
+                self.buffer.remove_range(self.cursor_row, start, self.cursor_row, self.cursor_col);
+                self.cursor_col = start;
             }
         } else if self.cursor_row > 0 && self.cursor_col == 0 {
-            let current_line = self.lines.remove(self.cursor_row);
             self.cursor_row -= 1;
-            self.cursor_col = self.lines[self.cursor_row].len();
-            self.lines[self.cursor_row].push_str(&current_line);
+            self.cursor_col = self.buffer.line_len(self.cursor_row);
+            self.buffer.join_with_next(self.cursor_row);
         }
     }
 
     fn insert_newline(&mut self) {
-        if self.cursor_row < self.lines.len() {
-            let cursor_col = self.cursor_col;
-            let line = self.lines[self.cursor_row].clone();
-            let (left, right) = line.split_at(cursor_col);
-            self.lines[self.cursor_row] = left.to_string();
-            self.lines.insert(self.cursor_row + 1, right.to_string());
+        if self.cursor_row < self.buffer.len_lines() {
+            self.buffer.split_line(self.cursor_row, self.cursor_col);
         } else {
-            self.lines.push(String::new());
+            self.buffer.insert_line(self.buffer.len_lines(), "");
         }
         self.cursor_row += 1;
         self.cursor_col = 0;
@@ -125,31 +116,31 @@ impl QuickEditor {
         match direction {
             Direction::Up => {
                 if self.cursor_row > 0 {
+                    let current_line = self.buffer.line(self.cursor_row);
                     self.cursor_row -= 1;
-                    let line_len = self.lines[self.cursor_row].len();
-                    self.cursor_col = self.cursor_col.min(line_len);
+                    self.cursor_col = cursor_utils::calculate_preferred_column(&current_line, self.cursor_col, &self.buffer.line(self.cursor_row));
                 }
             }
             Direction::Down => {
-                if self.cursor_row < self.lines.len() - 1 {
+                if self.cursor_row < self.buffer.len_lines() - 1 {
+                    let current_line = self.buffer.line(self.cursor_row);
                     self.cursor_row += 1;
-                    let line_len = self.lines[self.cursor_row].len();
-                    self.cursor_col = self.cursor_col.min(line_len);
+                    self.cursor_col = cursor_utils::calculate_preferred_column(&current_line, self.cursor_col, &self.buffer.line(self.cursor_row));
                 }
             }
             Direction::Left => {
                 if self.cursor_col > 0 {
-                    self.cursor_col -= 1;
+                    self.cursor_col = text_metrics::prev_boundary(&self.buffer.line(self.cursor_row), self.cursor_col);
                 } else if self.cursor_row > 0 {
                     self.cursor_row -= 1;
-                    self.cursor_col = self.lines[self.cursor_row].len();
+                    self.cursor_col = self.buffer.line_len(self.cursor_row);
                 }
             }
             Direction::Right => {
-                let line_len = self.lines[self.cursor_row].len();
+                let line_len = self.buffer.line_len(self.cursor_row);
                 if self.cursor_col < line_len {
-                    self.cursor_col += 1;
-                } else if self.cursor_row < self.lines.len() - 1 {
+                    self.cursor_col = text_metrics::next_boundary(&self.buffer.line(self.cursor_row), self.cursor_col);
+                } else if self.cursor_row < self.buffer.len_lines() - 1 {
                     self.cursor_row += 1;
                     self.cursor_col = 0;
                 }
@@ -201,33 +192,40 @@ impl QuickEditor {
         execute!(stdout(), Clear(ClearType::All), MoveTo(0, 0), Hide)?;
 
         let syntax_name = if self.syntax_enabled {
-            self.syntax_highlighter.get_syntax_name(self.filename.as_deref())
+            self.syntax_highlighter.get_syntax_name(self.filename.as_deref(), &self.buffer)
         } else {
             "Plain Text".to_string()
         };
 
         // Render content
-        for (row, line) in self.lines.iter().enumerate() {
+        for (row, line) in self.buffer.lines().enumerate() {
             if row == self.cursor_row {
-                let (before_cursor, after_cursor) = if self.cursor_col <= line.len() {
-                    (
-                        &line[..self.cursor_col.min(line.len())],
-                        &line[self.cursor_col.min(line.len())..]
-                    )
-                } else {
-                    (line.as_str(), "")
-                };
+                // Cursor columns are char indices, so find the matching byte offset
+                let split = line.char_indices().nth(self.cursor_col).map(|(idx, _)| idx).unwrap_or(line.len());
+                let (before_cursor, after_cursor) = line.split_at(split);
+                let spans = self.syntax_highlighter.highlight_line(&line, &syntax_name);
 
                 let highlighted_before = if self.syntax_enabled {
-                    self.syntax_highlighter.highlight_line(before_cursor, &syntax_name)
+                    syntax::to_ansi(before_cursor, 0, &spans)
                 } else {
                     before_cursor.to_string()
                 };
 
-                let highlighted_after = if self.syntax_enabled && !after_cursor.is_empty() {
-                    self.syntax_highlighter.highlight_line(after_cursor, &syntax_name)
+                // Normal mode draws the cursor over the grapheme cluster it sits on
+                let cluster_end = match self.mode {
+                    EditorMode::Normal => {
+                        let len = text_metrics::next_boundary(after_cursor, 0);
+                        after_cursor.char_indices().nth(len).map(|(idx, _)| idx).unwrap_or(after_cursor.len())
+                    }
+                    EditorMode::Insert => 0,
+                };
+                let (under_cursor, rest) = after_cursor.split_at(cluster_end);
+
+                let highlighted_rest = if self.syntax_enabled && !rest.is_empty() {
+                    let rest_col = line.len() - rest.len();
+                    syntax::to_ansi(rest, line[..rest_col].chars().count(), &spans)
                 } else {
-                    after_cursor.to_string()
+                    rest.to_string()
                 };
 
                 print!("{}", highlighted_before);
@@ -235,18 +233,14 @@ impl QuickEditor {
                 match self.mode {
                     EditorMode::Insert => print!("\x1b[7m \x1b[0m"),
                     EditorMode::Normal => print!("\x1b[7m{}\x1b[0m", 
-                        after_cursor.chars().next().unwrap_or(' ')),
+                        if under_cursor.is_empty() { " " } else { under_cursor }),
                 }
 
-                if self.mode == EditorMode::Normal && !after_cursor.is_empty() {
-                    print!("{}", &highlighted_after[1..]);
-                } else if self.mode == EditorMode::Insert {
-                    print!("{}", highlighted_after);
-                }
+                print!("{}", highlighted_rest);
             } else {
                 if self.syntax_enabled {
-                    let highlighted = self.syntax_highlighter.highlight_line(line, &syntax_name);
-                    print!("{}", highlighted);
+                    let spans = self.syntax_highlighter.highlight_line(&line, &syntax_name);
+                    print!("{}", syntax::to_ansi(&line, 0, &spans));
                 } else {
                     print!("{}", line);
                 }
@@ -1,9 +1,9 @@
-use iocraft::prelude::*;
+use hello_tui::{text_metrics, cursor::cursor_utils, buffer::TextBuffer};
 use std::io;
 
 #[derive(Default)]
 struct TextEditor {
-    lines: Vec<String>,
+    buffer: TextBuffer,
     cursor_row: usize,
     cursor_col: usize,
 }
@@ -11,65 +11,56 @@ struct TextEditor {
 impl TextEditor {
     fn new() -> Self {
         Self {
-            lines: vec![
-                "Welcome to IOCraft Text Editor!".to_string(),
-                "This is a simple yet functional text editor.".to_string(),
-                "".to_string(),
-                "Features:".to_string(),
-                "- Multi-line text editing".to_string(),
-                "- Cursor movement with arrow keys".to_string(),
-                "- Insert and delete text".to_string(),
-                "- Enter to create new lines".to_string(),
-                "- Backspace to delete characters".to_string(),
-                "".to_string(),
-                "Start editing here...".to_string(),
-            ],
+            buffer: TextBuffer::from_lines(&[
+                "Welcome to IOCraft Text Editor!",
+                "This is a simple yet functional text editor.",
+                "",
+                "Features:",
+                "- Multi-line text editing",
+                "- Cursor movement with arrow keys",
+                "- Insert and delete text",
+                "- Enter to create new lines",
+                "- Backspace to delete characters",
+                "",
+                "Start editing here...",
+            ]),
             cursor_row: 10,
             cursor_col: 21, // Fixed to be within the line length
         }
     }
 
     fn insert_char(&mut self, ch: char) {
-        if self.cursor_row < self.lines.len() {
-            let line = &mut self.lines[self.cursor_row];
-            let mut chars: Vec<char> = line.chars().collect();
-            chars.insert(self.cursor_col, ch);
-            *line = chars.into_iter().collect();
+        if self.cursor_row < self.buffer.len_lines() {
+            self.buffer.insert_char_at(self.cursor_row, self.cursor_col, ch);
             self.cursor_col += 1;
         } else {
-            self.lines.push(ch.to_string());
-            self.cursor_row = self.lines.len() - 1;
+            self.buffer.insert_line(self.buffer.len_lines(), &ch.to_string());
+            self.cursor_row = self.buffer.len_lines() - 1;
             self.cursor_col = 1;
         }
     }
 
     fn delete_char(&mut self) {
-        if self.cursor_col > 0 && self.cursor_row < self.lines.len() {
-            let line = &mut self.lines[self.cursor_row];
-            let mut chars: Vec<char> = line.chars().collect();
-            if self.cursor_col <= chars.len() && self.cursor_col > 0 {
-                chars.remove(self.cursor_col - 1);
-                *line = chars.into_iter().collect();
-                self.cursor_col -= 1;
+        if self.cursor_col > 0 && self.cursor_row < self.buffer.len_lines() {
+            if self.cursor_col <= self.buffer.line_len(self.cursor_row) {
+                // Remove the whole grapheme cluster before the cursor
+                let start = text_metrics::prev_boundary(&self.buffer.line(self.cursor_row), self.cursor_col);
+                self.buffer.remove_range(self.cursor_row, start, self.cursor_row, self.cursor_col);
+                self.cursor_col = start;
             }
         } else if self.cursor_row > 0 && self.cursor_col == 0 {
             // Join with previous line
-            let current_line = self.lines.remove(self.cursor_row);
             self.cursor_row -= 1;
-            self.cursor_col = self.lines[self.cursor_row].len();
-            self.lines[self.cursor_row].push_str(&current_line);
+            self.cursor_col = self.buffer.line_len(self.cursor_row);
+            self.buffer.join_with_next(self.cursor_row);
         }
     }
 
     fn new_line(&mut self) {
-        if self.cursor_row < self.lines.len() {
-            let cursor_col = self.cursor_col;
-            let line = self.lines[self.cursor_row].clone();
-            let (left, right) = line.split_at(cursor_col);
-            self.lines[self.cursor_row] = left.to_string();
-            self.lines.insert(self.cursor_row + 1, right.to_string());
+        if self.cursor_row < self.buffer.len_lines() {
+            self.buffer.split_line(self.cursor_row, self.cursor_col);
         } else {
-            self.lines.push(String::new());
+            self.buffer.insert_line(self.buffer.len_lines(), "");
         }
         self.cursor_row += 1;
         self.cursor_col = 0;
@@ -79,31 +70,31 @@ impl TextEditor {
         match direction {
             Direction::Up => {
                 if self.cursor_row > 0 {
+                    let current_line = self.buffer.line(self.cursor_row);
                     self.cursor_row -= 1;
-                    let line_len = self.lines[self.cursor_row].len();
-                    self.cursor_col = self.cursor_col.min(line_len);
+                    self.cursor_col = cursor_utils::calculate_preferred_column(&current_line, self.cursor_col, &self.buffer.line(self.cursor_row));
                 }
             }
             Direction::Down => {
-                if self.cursor_row < self.lines.len() - 1 {
+                if self.cursor_row < self.buffer.len_lines() - 1 {
+                    let current_line = self.buffer.line(self.cursor_row);
                     self.cursor_row += 1;
-                    let line_len = self.lines[self.cursor_row].len();
-                    self.cursor_col = self.cursor_col.min(line_len);
+                    self.cursor_col = cursor_utils::calculate_preferred_column(&current_line, self.cursor_col, &self.buffer.line(self.cursor_row));
                 }
             }
             Direction::Left => {
                 if self.cursor_col > 0 {
-                    self.cursor_col -= 1;
+                    self.cursor_col = text_metrics::prev_boundary(&self.buffer.line(self.cursor_row), self.cursor_col);
                 } else if self.cursor_row > 0 {
                     self.cursor_row -= 1;
-                    self.cursor_col = self.lines[self.cursor_row].len();
+                    self.cursor_col = self.buffer.line_len(self.cursor_row);
                 }
             }
             Direction::Right => {
-                let line_len = self.lines[self.cursor_row].len();
+                let line_len = self.buffer.line_len(self.cursor_row);
                 if self.cursor_col < line_len {
-                    self.cursor_col += 1;
-                } else if self.cursor_row < self.lines.len() - 1 {
+                    self.cursor_col = text_metrics::next_boundary(&self.buffer.line(self.cursor_row), self.cursor_col);
+                } else if self.cursor_row < self.buffer.len_lines() - 1 {
                     self.cursor_row += 1;
                     self.cursor_col = 0;
                 }
@@ -112,10 +103,11 @@ impl TextEditor {
     }
 
     fn get_content(&self) -> String {
-        self.lines.join("\n")
+        self.buffer.to_string()
     }
 }
 
+#[allow(dead_code)]
 #[derive(Debug)]
 enum Direction {
     Up,
@@ -138,7 +130,7 @@ fn main() -> io::Result<()> {
     
     // Move cursor to end and add some text
     editor.cursor_row = 10;
-    editor.cursor_col = editor.lines[10].len(); // Move to end of line
+    editor.cursor_col = editor.buffer.line_len(10); // Move to end of line
     
     // Insert some characters
     for ch in " Here I am typing!".chars() {
@@ -179,7 +171,7 @@ fn main() -> io::Result<()> {
     
     println!("\n💾 Final editor content:");
     println!("Content length: {} characters", editor.get_content().len());
-    println!("Number of lines: {}", editor.lines.len());
+    println!("Number of lines: {}", editor.buffer.len_lines());
     
     println!("\n✅ Text editor demo completed successfully!");
     println!("📦 The IOCraft library has been successfully integrated.");
@@ -190,7 +182,7 @@ fn main() -> io::Result<()> {
 fn display_editor(editor: &TextEditor) {
     println!("┌─────────────────────────────────────────────┐");
     
-    for (i, line) in editor.lines.iter().enumerate() {
+    for (i, line) in editor.buffer.lines().enumerate() {
         let display = if i == editor.cursor_row {
             let mut chars: Vec<char> = line.chars().collect();
             if editor.cursor_col <= chars.len() {
@@ -198,7 +190,7 @@ fn display_editor(editor: &TextEditor) {
             }
             chars.into_iter().collect()
         } else {
-            line.clone()
+            line
         };
         println!("│ {:<43} │", display.get(0..43).unwrap_or(&display));
     }
@@ -207,7 +199,7 @@ fn display_editor(editor: &TextEditor) {
     println!("│ Row: {}, Col: {} | {} lines{} │", 
              editor.cursor_row + 1, 
              editor.cursor_col + 1, 
-             editor.lines.len(),
+             editor.buffer.len_lines(),
              " ".repeat(22));
     println!("└─────────────────────────────────────────────┘");
 }
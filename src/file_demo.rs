@@ -1,9 +1,9 @@
 use std::io;
-use hello_tui::file_io;
+use hello_tui::{file_io, text_metrics, buffer::TextBuffer};
 
 #[derive(Default)]
 struct SimpleTextEditor {
-    lines: Vec<String>,
+    buffer: TextBuffer,
     cursor_row: usize,
     cursor_col: usize,
     filename: Option<String>,
@@ -15,7 +15,7 @@ impl SimpleTextEditor {
     }
 
     fn load_file(&mut self, filename: &str) -> io::Result<()> {
-        self.lines = file_io::FileIO::load_file(filename)?;
+        self.buffer = file_io::FileIO::load_buffer(filename)?;
         self.filename = Some(filename.to_string());
         self.cursor_row = 0;
         self.cursor_col = 0;
@@ -23,7 +23,7 @@ impl SimpleTextEditor {
     }
 
     fn save_file(&mut self, filename: &str) -> io::Result<()> {
-        file_io::FileIO::save_file(filename, &self.lines)?;
+        file_io::FileIO::save_buffer(filename, &self.buffer)?;
         self.filename = Some(filename.to_string());
         Ok(())
     }
@@ -31,7 +31,7 @@ impl SimpleTextEditor {
     fn display(&self) {
         println!("┌─────────────────────────────────────────────────────────────┐");
         
-        for (i, line) in self.lines.iter().enumerate() {
+        for (i, line) in self.buffer.lines().enumerate() {
             let display = if i == self.cursor_row {
                 let mut chars: Vec<char> = line.chars().collect();
                 if self.cursor_col <= chars.len() {
@@ -39,11 +39,11 @@ impl SimpleTextEditor {
                 }
                 chars.into_iter().collect()
             } else {
-                line.clone()
+                line
             };
             
-            let truncated = if display.len() > 59 {
-                format!("{}...", &display[0..56])
+            let truncated = if text_metrics::str_width(&display) > 59 {
+                format!("{}...", text_metrics::truncate_to_width(&display, 56))
             } else {
                 display
             };
@@ -58,7 +58,7 @@ impl SimpleTextEditor {
                  filename_text,
                  self.cursor_row + 1, 
                  self.cursor_col + 1,
-                 self.lines.len(),
+                 self.buffer.len_lines(),
                  " ".repeat(25 - filename_text.len().min(20)));
         println!("└─────────────────────────────────────────────────────────────┘");
     }
@@ -68,7 +68,7 @@ impl SimpleTextEditor {
         
         // Move to end of first line and add text
         self.cursor_row = 0;
-        self.cursor_col = self.lines[0].len();
+        self.cursor_col = self.buffer.line_len(0);
         
         let additional_text = " [EDITED WITH IOCRAFT]";
         for ch in additional_text.chars() {
@@ -86,24 +86,17 @@ impl SimpleTextEditor {
     }
 
     fn insert_char(&mut self, ch: char) {
-        if self.cursor_row < self.lines.len() {
-            let line = &mut self.lines[self.cursor_row];
-            let mut chars: Vec<char> = line.chars().collect();
-            chars.insert(self.cursor_col, ch);
-            *line = chars.into_iter().collect();
+        if self.cursor_row < self.buffer.len_lines() {
+            self.buffer.insert_char_at(self.cursor_row, self.cursor_col, ch);
             self.cursor_col += 1;
         }
     }
 
     fn insert_newline(&mut self) {
-        if self.cursor_row < self.lines.len() {
-            let cursor_col = self.cursor_col;
-            let line = self.lines[self.cursor_row].clone();
-            let (left, right) = line.split_at(cursor_col);
-            self.lines[self.cursor_row] = left.to_string();
-            self.lines.insert(self.cursor_row + 1, right.to_string());
+        if self.cursor_row < self.buffer.len_lines() {
+            self.buffer.split_line(self.cursor_row, self.cursor_col);
         } else {
-            self.lines.push(String::new());
+            self.buffer.insert_line(self.buffer.len_lines(), "");
         }
         self.cursor_row += 1;
         self.cursor_col = 0;
@@ -159,16 +152,16 @@ fn main() -> io::Result<()> {
     } else {
         println!("📝 No file provided - creating demo content");
         
-        editor.lines = vec![
-            "Welcome to IOCraft Text Editor!".to_string(),
-            "This is a demonstration of file I/O capabilities.".to_string(),
-            "".to_string(),
-            "Features demonstrated:".to_string(),
-            "- Loading text files".to_string(),
-            "- Editing text content".to_string(),
-            "- Saving modified files".to_string(),
-            "- Cursor positioning".to_string(),
-        ];
+        editor.buffer = TextBuffer::from_lines(&[
+            "Welcome to IOCraft Text Editor!",
+            "This is a demonstration of file I/O capabilities.",
+            "",
+            "Features demonstrated:",
+            "- Loading text files",
+            "- Editing text content",
+            "- Saving modified files",
+            "- Cursor positioning",
+        ]);
         editor.cursor_row = 0;
         editor.cursor_col = 0;
         
@@ -1,5 +1,6 @@
 use std::fs;
-use std::io;
+use std::io::{self, BufReader, BufWriter, Write};
+use crate::buffer::TextBuffer;
 
 /// File I/O operations for the text editor
 pub struct FileIO;
@@ -23,6 +24,19 @@ impl FileIO {
         Ok(())
     }
 
+    /// Load a file into a rope-backed text buffer, preserving line endings
+    pub fn load_buffer(filename: &str) -> io::Result<TextBuffer> {
+        let file = fs::File::open(filename)?;
+        TextBuffer::from_reader(BufReader::new(file))
+    }
+
+    /// Save a text buffer to a file exactly as it is stored
+    pub fn save_buffer(filename: &str, buffer: &TextBuffer) -> io::Result<()> {
+        let mut writer = BufWriter::new(fs::File::create(filename)?);
+        buffer.write_to(&mut writer)?;
+        writer.flush()
+    }
+
     /// Check if a file exists
     pub fn file_exists(filename: &str) -> bool {
         std::path::Path::new(filename).exists()
@@ -66,6 +80,20 @@ mod tests {
         let _ = fs::remove_file(test_filename);
     }
 
+    #[test]
+    fn test_load_and_save_buffer() {
+        let test_filename = "test_buffer_file.txt";
+        let buffer = TextBuffer::from("Hello\nWorld\n");
+
+        FileIO::save_buffer(test_filename, &buffer).unwrap();
+
+        let loaded = FileIO::load_buffer(test_filename).unwrap();
+        assert_eq!(loaded, buffer);
+        assert_eq!(loaded.len_lines(), 3);
+
+        let _ = fs::remove_file(test_filename);
+    }
+
     #[test]
     fn test_file_exists() {
         let test_filename = "test_exists.txt";
@@ -11,6 +11,12 @@ pub struct I18nManager {
     fallback_locale: LanguageIdentifier,
 }
 
+impl Default for I18nManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl I18nManager {
     /// Creates a new I18nManager with default English locale
     pub fn new() -> Self {
@@ -57,38 +63,36 @@ impl I18nManager {
         let locale_str = self.current_locale.to_string();
         
         // Try current locale
-        if let Some(bundle) = self.bundles.get(&locale_str) {
-            if let Some(msg) = bundle.get_message(key) {
-                if let Some(pattern) = msg.value() {
-                    let mut errors = Vec::new();
-                    let fluent_args = args.as_ref().map(|a| {
-                        let mut fa = FluentArgs::new();
-                        for (k, v) in a.iter() {
-                            fa.set(k, v.clone());
-                        }
-                        fa
-                    });
-                    return bundle.format_pattern(pattern, fluent_args.as_ref(), &mut errors).to_string();
+        if let Some(bundle) = self.bundles.get(&locale_str)
+            && let Some(msg) = bundle.get_message(key)
+            && let Some(pattern) = msg.value()
+        {
+            let mut errors = Vec::new();
+            let fluent_args = args.as_ref().map(|a| {
+                let mut fa = FluentArgs::new();
+                for (k, v) in a.iter() {
+                    fa.set(k, v.clone());
                 }
-            }
+                fa
+            });
+            return bundle.format_pattern(pattern, fluent_args.as_ref(), &mut errors).to_string();
         }
         
         // Fallback to English
         let fallback_str = self.fallback_locale.to_string();
-        if let Some(bundle) = self.bundles.get(&fallback_str) {
-            if let Some(msg) = bundle.get_message(key) {
-                if let Some(pattern) = msg.value() {
-                    let mut errors = Vec::new();
-                    let fluent_args = args.as_ref().map(|a| {
-                        let mut fa = FluentArgs::new();
-                        for (k, v) in a.iter() {
-                            fa.set(k, v.clone());
-                        }
-                        fa
-                    });
-                    return bundle.format_pattern(pattern, fluent_args.as_ref(), &mut errors).to_string();
+        if let Some(bundle) = self.bundles.get(&fallback_str)
+            && let Some(msg) = bundle.get_message(key)
+            && let Some(pattern) = msg.value()
+        {
+            let mut errors = Vec::new();
+            let fluent_args = args.as_ref().map(|a| {
+                let mut fa = FluentArgs::new();
+                for (k, v) in a.iter() {
+                    fa.set(k, v.clone());
                 }
-            }
+                fa
+            });
+            return bundle.format_pattern(pattern, fluent_args.as_ref(), &mut errors).to_string();
         }
         
         // Final fallback to key itself
@@ -158,7 +162,7 @@ pub fn with_i18n<T, F>(f: F) -> T
 where 
     F: FnOnce(&I18nManager) -> T 
 {
-    I18N_MANAGER.with(|m| f(&*m.borrow()))
+    I18N_MANAGER.with(|m| f(&m.borrow()))
 }
 
 /// Access the I18nManager for writing
@@ -166,7 +170,7 @@ pub fn with_i18n_mut<T, F>(f: F) -> T
 where 
     F: FnOnce(&mut I18nManager) -> T 
 {
-    I18N_MANAGER.with(|m| f(&mut *m.borrow_mut()))
+    I18N_MANAGER.with(|m| f(&mut m.borrow_mut()))
 }
 
 /// Convenience function to get a localized message
@@ -183,7 +187,7 @@ pub fn t_with_args(key: &str, args: HashMap<String, FluentValue>) -> String {
 #[macro_export]
 macro_rules! t {
     ($key:expr) => {
-        crate::i18n::t($key)
+        $crate::i18n::t($key)
     };
     ($key:expr, $($arg_name:ident = $arg_value:expr),+) => {
         {
@@ -194,7 +198,7 @@ macro_rules! t {
                     fluent_bundle::FluentValue::from($arg_value)
                 );
             )+
-            crate::i18n::t_with_args($key, args)
+            $crate::i18n::t_with_args($key, args)
         }
     };
 }
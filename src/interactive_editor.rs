@@ -2,17 +2,88 @@ use std::io::{self, stdout, stdin, Write};
 use crossterm::{
     terminal::{enable_raw_mode, disable_raw_mode, Clear, ClearType},
     cursor::{MoveTo, Show},
-    event::{read, Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, EnableMouseCapture, DisableMouseCapture},
+    event::{poll, read, Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, EnableMouseCapture, DisableMouseCapture, EnableBracketedPaste, DisableBracketedPaste},
     execute,
 };
-use hello_tui::{syntax, iocraft_file, mouse, linter, keys::{KeyHandler, Direction}, render, cursor, i18n};
+use hello_tui::{syntax, language, lint_config, config, iocraft_file, mouse, linter, keys::{KeyAction, Direction}, keymap::{Keymap, KeymapProfile}, vim, render, wrap, text_metrics, cursor, i18n, buffer::TextBuffer, buffer_list::{self, BufferState, BufferList, ViewState}, layout::{self, Layout, Rect, SplitDirection}, undo, clipboard, search};
 use fluent_bundle::FluentValue;
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Pause in editing after which the active buffer is linted again
+const LINT_DELAY: Duration = Duration::from_millis(300);
+
+/// Field of the search prompt that receives typed text
+#[derive(PartialEq)]
+enum SearchField {
+    Query,
+    Replacement,
+}
+
+/// Find/replace prompt shown on the status line
+struct SearchPrompt {
+    field: SearchField,
+    replace_mode: bool,
+    backward: bool,
+    replacement: String,
+    // Restored when the prompt is canceled
+    origin: undo::CursorState,
+    origin_scroll: usize,
+}
+
+/// Go To Line prompt shown on the status line
+struct GotoPrompt {
+    input: String,
+    // Restored when the prompt is canceled
+    origin: undo::CursorState,
+    origin_scroll: usize,
+}
+
+/// Theme picker shown on the status line; moving through the list previews each theme
+struct ThemePicker {
+    themes: Vec<String>,
+    selected: usize,
+    // Restored when the picker is canceled
+    origin: String,
+}
+
+/// Language picker shown on the status line; typing narrows the list of syntaxes
+struct LanguagePicker {
+    query: String,
+    languages: Vec<String>,
+    // Languages matching the query, best first; `None` goes back to detection
+    matches: Vec<Option<String>>,
+    selected: usize,
+}
+
+impl LanguagePicker {
+    fn refilter(&mut self) {
+        self.matches = language::fuzzy_filter(&self.query, &self.languages).into_iter().cloned().map(Some).collect();
+        if self.query.is_empty() {
+            self.matches.insert(0, None);
+        }
+        self.selected = 0;
+    }
+}
+
+/// Fixes for the lint issues on the cursor line, offered on the status line
+struct QuickFixMenu {
+    fixes: Vec<linter::LintFix>,
+    selected: usize,
+}
+
+/// What an unfocused pane shows: a buffer and its own view of it
+struct PaneView {
+    buffer: buffer_list::BufferId,
+    view: ViewState,
+}
 
 struct InteractiveTextEditor {
-    lines: Vec<String>,
+    buffer: TextBuffer,
     cursor: cursor::CursorController,
     filename: Option<String>,
+    // Language picked by hand for the active buffer
+    language: Option<String>,
     status_message: String,
     quit: bool,
     syntax_highlighter: syntax::SyntaxHighlighter,
@@ -21,26 +92,61 @@ struct InteractiveTextEditor {
     modified: bool,
     mouse_controller: mouse::MouseController,
     text_selection: Option<mouse::TextSelection>,
+    // Fixed end of a keyboard selection; the cursor is the moving end
+    selection_anchor: Option<(usize, usize)>,
     scroll_offset: usize,
+    // First visible column when soft wrap is off
+    scroll_offset_x: usize,
+    soft_wrap: bool,
     linter: linter::Linter,
     lint_issues: Vec<linter::LintIssue>,
-    content: String,
-    // Anti-flicker optimization fields
-    last_render_time: std::time::Instant,
-    render_throttle_ms: u64,
-    needs_full_render: bool,
+    // Language the active buffer is linted as, detected once rather than per edit
+    lint_language: Option<String>,
+    // When the pending lint of the active buffer runs, if edits are waiting for one
+    lint_due: Option<Instant>,
+    // Cursor position lint issues were last looked up at, and the message shown for them
+    issue_status: Option<((usize, usize), Option<String>)>,
+    highlights: syntax::HighlightCache,
+    undo_stack: undo::UndoStack,
+    // Undo revision of the text on disk; `None` if it was never saved
+    saved_revision: Option<u64>,
+    clipboard: clipboard::Clipboard,
+    search: search::SearchState,
+    search_prompt: Option<SearchPrompt>,
+    search_active: bool,
+    goto_prompt: Option<GotoPrompt>,
+    theme_picker: Option<ThemePicker>,
+    language_picker: Option<LanguagePicker>,
+    quick_fix_menu: Option<QuickFixMenu>,
+    settings: config::Settings,
     // Key handling
-    key_handler: KeyHandler,
+    keymap: Keymap,
+    vim: Option<vim::Vim>,
+    // Emacs mark: while active, motions extend the selection from `selection_anchor`
+    mark_active: bool,
+    kill_ring: clipboard::KillRing,
+    // Char range inserted by the last yank, replaced by yank-pop
+    last_yank: Option<(usize, usize)>,
+    // Open buffers; the active one's state lives in the fields above
+    buffers: BufferList,
+    // Quit or close that was refused because of unsaved changes; repeating it goes ahead
+    pending_discard: Option<KeyAction>,
+    // Split panes; the focused one shows the active buffer through the fields above
+    layout: Layout,
+    focused_pane: layout::PaneId,
+    pane_views: HashMap<layout::PaneId, PaneView>,
     // Rendering
     renderer: render::EditorRenderer,
 }
 
 impl InteractiveTextEditor {
-    fn new(filename: Option<String>) -> io::Result<Self> {
+    fn new(filenames: Vec<String>) -> io::Result<Self> {
+        let (keymap, keymap_errors) = Keymap::load();
         let mut editor = Self {
-            lines: vec!["".to_string()],
+            buffer: TextBuffer::new(),
             cursor: cursor::CursorController::new(),
-            filename: filename.clone(),
+            filename: None,
+            language: None,
             status_message: i18n::t("help-status-message"),
             quit: false,
             syntax_highlighter: syntax::SyntaxHighlighter::new(),
@@ -49,24 +155,58 @@ impl InteractiveTextEditor {
             modified: false,
             mouse_controller: mouse::MouseController::new(),
             text_selection: None,
+            selection_anchor: None,
             scroll_offset: 0,
+            scroll_offset_x: 0,
+            soft_wrap: false,
             linter: linter::Linter::new(),
             lint_issues: Vec::new(),
-            content: String::new(),
-            // Anti-flicker optimization initialization
-            last_render_time: std::time::Instant::now(),
-            render_throttle_ms: 16, // ~60 FPS throttling
-            needs_full_render: true,
+            lint_language: None,
+            lint_due: None,
+            issue_status: None,
+            highlights: syntax::HighlightCache::new(),
+            undo_stack: undo::UndoStack::new(),
+            saved_revision: Some(0),
+            clipboard: clipboard::Clipboard::new(),
+            search: search::SearchState::new(),
+            search_prompt: None,
+            search_active: false,
+            goto_prompt: None,
+            theme_picker: None,
+            language_picker: None,
+            quick_fix_menu: None,
+            settings: config::Settings::load(),
             // Key handling initialization
-            key_handler: KeyHandler::new(),
+            vim: (keymap.profile() == KeymapProfile::Vim).then(vim::Vim::new),
+            keymap,
+            mark_active: false,
+            kill_ring: clipboard::KillRing::new(),
+            last_yank: None,
+            buffers: BufferList::new(),
+            pending_discard: None,
+            layout: Layout::new(),
+            focused_pane: 0,
+            pane_views: HashMap::new(),
             // Rendering initialization
             renderer: render::EditorRenderer::new(),
         };
 
-        if let Some(filename) = filename {
-            editor.load_file(&filename)?;
+        // Language mappings and syntaxes apply to the files opened below
+        let language_errors = editor.syntax_highlighter.languages_mut().load_user_config();
+
+        if !filenames.is_empty() {
+            for filename in &filenames {
+                editor.load_file(filename)?;
+            }
+            // Start on the first file given
+            if editor.buffers.len() > 1 {
+                editor.switch_buffer(0);
+                let mut args = HashMap::new();
+                args.insert("count".to_string(), FluentValue::from(editor.buffers.len()));
+                editor.status_message = i18n::t_with_args("status-files-opened", args);
+            }
         } else {
-            editor.lines = vec![
+            editor.buffer = TextBuffer::from_lines(&[
                 i18n::t("welcome-title"),
                 String::new(),
                 i18n::t("welcome-features"),
@@ -85,17 +225,60 @@ impl InteractiveTextEditor {
                 format!("  {}", i18n::t("welcome-shortcuts-quit")),
                 String::new(),
                 i18n::t("welcome-start-editing"),
-            ];
+            ]);
+        }
+
+        // User themes load before the saved choice, which may be one of them
+        let theme_errors = editor.syntax_highlighter.load_user_themes();
+        if let Some(theme) = editor.settings.theme.clone() {
+            editor.syntax_highlighter.set_theme(&theme);
+        }
+        if let Some(error) = theme_errors.first() {
+            let mut args = HashMap::new();
+            args.insert("error".to_string(), FluentValue::from(error.clone()));
+            args.insert("count".to_string(), FluentValue::from(theme_errors.len()));
+            editor.status_message = i18n::t_with_args("status-theme-error", args);
+        }
+        if let Some(error) = language_errors.first() {
+            let mut args = HashMap::new();
+            args.insert("error".to_string(), FluentValue::from(error.clone()));
+            args.insert("count".to_string(), FluentValue::from(language_errors.len()));
+            editor.status_message = i18n::t_with_args("status-language-error", args);
+        }
+
+        // Keymap problems are reported once the editor opens; valid bindings still apply
+        if let Some(error) = keymap_errors.first() {
+            let mut args = HashMap::new();
+            args.insert("error".to_string(), FluentValue::from(error.to_string()));
+            args.insert("count".to_string(), FluentValue::from(keymap_errors.len()));
+            editor.status_message = i18n::t_with_args("status-keymap-error", args);
         }
 
         Ok(editor)
     }
 
+    /// Open a file in its own tab, or switch to its tab if it is already open
     fn load_file(&mut self, filename: &str) -> io::Result<()> {
-        match self.iocraft_handler.load_file(filename) {
-            Ok(lines) => {
-                self.lines = lines;
-                self.filename = Some(filename.to_string());
+        if let Some(idx) = self.buffers.find(filename) {
+            self.switch_buffer(idx);
+            return Ok(());
+        }
+
+        match self.iocraft_handler.load_buffer(filename) {
+            Ok(buffer) => {
+                if self.filename.as_deref() == Some(filename) {
+                    // Reload the active buffer in place
+                    self.buffer = buffer;
+                    self.lint_language = None;
+                    self.undo_stack.clear();
+                    self.saved_revision = Some(self.undo_stack.revision());
+                    self.cursor.clear_extra_carets();
+                    self.modified = false;
+                } else {
+                    let language = self.settings.language_for(filename);
+                    self.open_buffer(BufferState { language, ..BufferState::new(buffer, Some(filename.to_string())) });
+                }
+                self.search_active = false;
                 
                 let mut args = HashMap::new();
                 args.insert("filename".to_string(), FluentValue::from(filename));
@@ -116,6 +299,235 @@ impl InteractiveTextEditor {
         }
     }
 
+    /// Move the active buffer's state out of the editor
+    fn take_buffer_state(&mut self) -> BufferState {
+        // Parked buffers are not linted on a timer, so finish a pending lint first
+        if self.lint_due.is_some() {
+            self.run_linting();
+        }
+        BufferState {
+            buffer: std::mem::take(&mut self.buffer),
+            filename: self.filename.take(),
+            language: self.language.take(),
+            modified: std::mem::take(&mut self.modified),
+            lint_issues: std::mem::take(&mut self.lint_issues),
+            lint_language: self.lint_language.take(),
+            lint_stale: false,
+            highlights: std::mem::take(&mut self.highlights),
+            undo_stack: std::mem::take(&mut self.undo_stack),
+            saved_revision: self.saved_revision,
+            view: self.take_view(),
+        }
+    }
+
+    /// Make `state` the active buffer
+    fn restore_buffer_state(&mut self, state: BufferState) {
+        self.buffer = state.buffer;
+        self.filename = state.filename;
+        self.language = state.language;
+        self.modified = state.modified;
+        self.lint_issues = state.lint_issues;
+        self.lint_language = state.lint_language;
+        self.highlights = state.highlights;
+        self.undo_stack = state.undo_stack;
+        self.saved_revision = state.saved_revision;
+        self.restore_view(state.view);
+        if state.lint_stale && self.linter.is_enabled() {
+            self.run_linting();
+        }
+
+        // Modes tied to the previous buffer's text do not carry over
+        self.mark_active = false;
+        self.last_yank = None;
+        if let Some(vim) = self.vim.as_mut() {
+            vim.reset();
+        }
+        if self.search_active {
+            self.search.update(&self.buffer);
+        }
+    }
+
+    /// Move the focused view's cursor, selection and scroll position out of the editor
+    fn take_view(&mut self) -> ViewState {
+        ViewState {
+            cursor: std::mem::take(&mut self.cursor),
+            text_selection: self.text_selection.take(),
+            selection_anchor: self.selection_anchor.take(),
+            scroll_offset: std::mem::take(&mut self.scroll_offset),
+            scroll_offset_x: std::mem::take(&mut self.scroll_offset_x),
+        }
+    }
+
+    /// Copy of the focused view, e.g. for a new pane
+    fn current_view(&self) -> ViewState {
+        ViewState {
+            cursor: self.cursor.clone(),
+            text_selection: self.text_selection.clone(),
+            selection_anchor: self.selection_anchor,
+            scroll_offset: self.scroll_offset,
+            scroll_offset_x: self.scroll_offset_x,
+        }
+    }
+
+    /// Show `view` in the focused pane
+    fn restore_view(&mut self, mut view: ViewState) {
+        // Another pane may have edited the buffer since the view was parked
+        view.clamp(&self.buffer);
+        self.cursor = view.cursor;
+        self.text_selection = view.text_selection;
+        self.selection_anchor = view.selection_anchor;
+        self.scroll_offset = view.scroll_offset;
+        self.scroll_offset_x = view.scroll_offset_x;
+    }
+
+    /// Show `state` in a new tab after the active one; an untouched unnamed buffer is replaced instead
+    fn open_buffer(&mut self, state: BufferState) {
+        if self.filename.is_some() || self.modified {
+            let current = self.take_buffer_state();
+            self.buffers.open(current);
+        }
+        self.restore_buffer_state(state);
+    }
+
+    /// Make buffer `idx` the active one
+    fn switch_buffer(&mut self, idx: usize) {
+        if idx == self.buffers.active() {
+            return;
+        }
+        let current = self.take_buffer_state();
+        let next = self.buffers.switch_to(idx, current);
+        self.restore_buffer_state(next);
+
+        let mut args = HashMap::new();
+        args.insert("filename".to_string(), FluentValue::from(self.buffer_title()));
+        args.insert("index".to_string(), FluentValue::from(idx + 1));
+        args.insert("count".to_string(), FluentValue::from(self.buffers.len()));
+        self.status_message = i18n::t_with_args("status-buffer-switched", args);
+    }
+
+    /// Close the active buffer, asking for confirmation first if it has unsaved changes
+    fn close_buffer(&mut self, confirmed: bool) {
+        let mut args = HashMap::new();
+        args.insert("filename".to_string(), FluentValue::from(self.buffer_title()));
+        if self.modified && !confirmed {
+            self.status_message = i18n::t_with_args("status-buffer-unsaved", args);
+            self.pending_discard = Some(KeyAction::CloseBuffer);
+            return;
+        }
+
+        // Closing the last buffer leaves an empty one behind
+        let next = self.buffers.close_active().unwrap_or_default();
+        self.restore_buffer_state(next);
+        // Other panes showing the closed buffer switch to the one now active
+        let active = self.buffers.active_id();
+        let view = self.current_view();
+        for pane in self.pane_views.values_mut() {
+            if self.buffers.index_of(pane.buffer).is_none() {
+                *pane = PaneView { buffer: active, view: view.clone() };
+            }
+        }
+        self.status_message = i18n::t_with_args("status-buffer-closed", args);
+    }
+
+    /// Quit, asking for confirmation first if any buffer has unsaved changes
+    fn quit(&mut self, confirmed: bool) {
+        let parked: Vec<usize> = self.buffers.modified().collect();
+        let count = parked.len() + usize::from(self.modified);
+        if count == 0 || confirmed {
+            self.quit = true;
+            return;
+        }
+
+        // Bring an unsaved buffer into view
+        if !self.modified {
+            self.switch_buffer(parked[0]);
+        }
+        self.status_message = if count == 1 {
+            i18n::t("file-unsaved-changes")
+        } else {
+            let mut args = HashMap::new();
+            args.insert("count".to_string(), FluentValue::from(count));
+            i18n::t_with_args("status-buffers-unsaved", args)
+        };
+        self.pending_discard = Some(KeyAction::Quit);
+    }
+
+    /// Screen area of every pane
+    fn pane_rects(&self) -> Vec<(layout::PaneId, Rect)> {
+        let area = render::editor_area().unwrap_or(Rect::new(0, 0, 80, 23));
+        self.layout.rects(area)
+    }
+
+    /// Give the keyboard to `pane`, parking the view of the pane that had it
+    fn focus_pane(&mut self, pane: layout::PaneId) {
+        if pane == self.focused_pane || !self.pane_views.contains_key(&pane) {
+            return;
+        }
+        let parked = PaneView { buffer: self.buffers.active_id(), view: self.current_view() };
+        self.pane_views.insert(self.focused_pane, parked);
+        self.show_pane(pane);
+    }
+
+    /// Make `pane` the focused one, bringing back its buffer and view
+    fn show_pane(&mut self, pane: layout::PaneId) {
+        let Some(PaneView { buffer, view }) = self.pane_views.remove(&pane) else {
+            return;
+        };
+        if let Some(idx) = self.buffers.index_of(buffer) {
+            self.switch_buffer(idx);
+        }
+        self.restore_view(view);
+        self.focused_pane = pane;
+        self.mark_active = false;
+    }
+
+    /// Split the focused pane; the new pane starts with the same view and gets the focus
+    fn split_pane(&mut self, direction: SplitDirection) {
+        let Some(pane) = self.layout.split(self.focused_pane, direction) else {
+            return;
+        };
+        self.pane_views.insert(pane, PaneView { buffer: self.buffers.active_id(), view: self.current_view() });
+        self.focus_pane(pane);
+        self.scroll_to_cursor();
+        self.status_message = i18n::t("status-pane-split");
+    }
+
+    /// Close the focused pane and focus the one that takes over its space
+    fn close_pane(&mut self) {
+        match self.layout.remove(self.focused_pane) {
+            Some(next) => {
+                self.show_pane(next);
+                self.scroll_to_cursor();
+                self.status_message = i18n::t("status-pane-closed");
+            }
+            None => self.status_message = i18n::t("status-last-pane"),
+        }
+    }
+
+    /// Move the focus to the nearest pane in `direction`
+    fn focus_pane_towards(&mut self, direction: Direction) {
+        let area = render::editor_area().unwrap_or(Rect::new(0, 0, 80, 23));
+        if let Some(pane) = self.layout.neighbor(area, self.focused_pane, direction) {
+            self.focus_pane(pane);
+        }
+    }
+
+    /// Move the divider next to the focused pane
+    fn resize_pane(&mut self, direction: Direction) {
+        if self.layout.resize(self.focused_pane, direction) {
+            self.scroll_to_cursor();
+        }
+    }
+
+    /// Name of the active buffer as shown on its tab
+    fn buffer_title(&self) -> String {
+        self.filename
+            .as_deref()
+            .map(buffer_list::tab_title)
+            .map(str::to_string)
+            .unwrap_or_else(|| i18n::t("ui-no-file"))
+    }
+
     fn save_file(&mut self) -> io::Result<()> {
         let filename = if let Some(ref name) = self.filename {
             name.clone()
@@ -123,9 +535,17 @@ impl InteractiveTextEditor {
             "untitled.txt".to_string()
         };
 
-        match self.iocraft_handler.save_file(&filename, &self.lines) {
+        match self.iocraft_handler.save_buffer(&filename, &self.buffer) {
             Ok(()) => {
                 self.filename = Some(filename.clone());
+                self.saved_revision = Some(self.undo_stack.revision());
+                // The file name may now imply another language
+                self.lint_language = None;
+                // Other buffers may be linted under the configuration just saved
+                if lint_config::is_config_file(std::path::Path::new(&filename)) {
+                    self.linter.reload_config();
+                    self.lint_all_buffers();
+                }
                 let mut args = HashMap::new();
                 args.insert("filename".to_string(), FluentValue::from(filename));
                 self.status_message = i18n::t_with_args("file-saved", args);
@@ -141,101 +561,1546 @@ impl InteractiveTextEditor {
         }
     }
 
-    fn move_cursor(&mut self, direction: Direction) {
-        self.cursor.move_cursor(direction, &self.lines);
+    fn move_cursor(&mut self, direction: Direction) {
+        let buffer = &self.buffer;
+        self.cursor.move_extra_carets(|position| cursor::cursor_utils::step_position(position, &direction, buffer));
+        self.step_cursor(direction);
+        self.undo_stack.seal();
+    }
+
+    /// Move the primary caret one step; up and down go by visual row when soft wrap is on
+    fn step_cursor(&mut self, direction: Direction) {
+        if self.soft_wrap && matches!(direction, Direction::Up | Direction::Down) {
+            let (row, col) = (self.cursor.row(), self.cursor.col());
+            let width = self.viewport_size().0;
+            if let Some((row, col)) = wrap::move_visual(&self.buffer, row, col, width, direction == Direction::Down) {
+                self.cursor.set_position(row, col);
+            }
+        } else {
+            self.cursor.move_cursor(direction, &self.buffer);
+        }
+    }
+
+    /// Move to the next or previous word boundary, crossing line ends
+    fn move_word(&mut self, direction: Direction) {
+        let buffer = &self.buffer;
+        self.cursor.move_extra_carets(|position| cursor::cursor_utils::step_word(position, &direction, buffer));
+        let mut position = self.cursor.position().clone();
+        cursor::cursor_utils::step_word(&mut position, &direction, &self.buffer);
+        self.cursor.set_position(position.row, position.col);
+        self.undo_stack.seal();
+    }
+
+    /// Run a cursor motion and stretch the selection from its anchor to the new cursor position
+    ///
+    /// Only the primary caret has a selection anchor, so secondary carets are dropped.
+    fn extend_selection<T>(&mut self, motion: impl FnOnce(&mut Self) -> T) {
+        self.clear_extra_carets();
+        let cursor = (self.cursor.row(), self.cursor.col());
+        let anchor = match (&self.text_selection, self.selection_anchor) {
+            (None, _) => cursor,
+            (Some(_), Some(anchor)) => anchor,
+            // Mouse selections have no anchor; keep the end the cursor is not on
+            (Some(sel), None) if (sel.start_row, sel.start_col) == cursor => (sel.end_row, sel.end_col),
+            (Some(sel), None) => (sel.start_row, sel.start_col),
+        };
+
+        motion(self);
+        self.undo_stack.seal();
+
+        let (row, col) = (self.cursor.row(), self.cursor.col());
+        if (row, col) == anchor {
+            self.text_selection = None;
+            self.selection_anchor = None;
+        } else {
+            self.text_selection = Some(mouse::TextSelection::new(anchor.0, anchor.1, row, col));
+            self.selection_anchor = Some(anchor);
+        }
+        self.scroll_to_cursor();
+    }
+
+    fn select_all(&mut self) {
+        let last_row = self.buffer.len_lines().saturating_sub(1);
+        let last_col = self.buffer.line_len(last_row);
+        self.text_selection = Some(mouse::TextSelection::new(0, 0, last_row, last_col));
+        self.selection_anchor = Some((0, 0));
+        self.cursor.set_position(last_row, last_col);
+        self.undo_stack.seal();
+        self.scroll_to_cursor();
+
+        let mut args = HashMap::new();
+        args.insert("count".to_string(), FluentValue::from(self.buffer.len_chars()));
+        self.status_message = i18n::t_with_args("status-text-selected", args);
+    }
+
+    /// Snapshot the cursor and selection for the undo history
+    fn cursor_state(&self) -> undo::CursorState {
+        undo::CursorState::new(self.cursor.row(), self.cursor.col(), self.text_selection.clone())
+    }
+
+    /// Record an edit that was just applied to the buffer
+    fn record_edit(&mut self, edit: undo::Edit, before: undo::CursorState, coalesce: bool) {
+        let after = self.cursor_state();
+        if coalesce {
+            self.undo_stack.record_coalescing(edit, before, after);
+        } else {
+            self.undo_stack.record(edit, before, after);
+        }
+        self.buffer_changed();
+    }
+
+    /// Refresh everything derived from the buffer after an edit
+    fn buffer_changed(&mut self) {
+        self.modified = true;
+        self.schedule_lint();
+        if self.search_active {
+            self.search.update(&self.buffer);
+        }
+    }
+
+    /// Char range covered by a caret: its selection, or an empty range at the caret
+    fn caret_range(&self, row: usize, col: usize, selection: Option<&mouse::TextSelection>) -> (usize, usize) {
+        match selection {
+            Some(sel) => (
+                self.buffer.pos_to_char(sel.start_row, sel.start_col),
+                self.buffer.pos_to_char(sel.end_row, sel.end_col),
+            ),
+            None => {
+                let idx = self.buffer.pos_to_char(row, col);
+                (idx, idx)
+            }
+        }
+    }
+
+    /// Apply an edit at the primary and every secondary caret as a single undo step
+    fn edit_all_carets(&mut self, edit: cursor::CaretEdit) {
+        let mut ranges = vec![self.caret_range(self.cursor.row(), self.cursor.col(), self.text_selection.as_ref())];
+        for caret in self.cursor.extra_carets() {
+            ranges.push(self.caret_range(caret.position.row, caret.position.col, caret.selection.as_ref()));
+        }
+
+        let before = self.cursor_state();
+        self.undo_stack.begin_group(before.clone());
+        let (edits, carets) = cursor::edit_at_carets(&mut self.buffer, &ranges, edit);
+        let changed = !edits.is_empty();
+        for edit in edits {
+            self.undo_stack.record(edit, before.clone(), before.clone());
+        }
+
+        let (row, col) = self.buffer.char_to_pos(carets[0]);
+        self.cursor.set_position(row, col);
+        self.cursor.set_extra_carets(
+            carets[1..]
+                .iter()
+                .map(|idx| {
+                    let (row, col) = self.buffer.char_to_pos(*idx);
+                    cursor::Caret::new(row, col, None)
+                })
+                .collect(),
+        );
+        self.text_selection = None;
+        self.selection_anchor = None;
+        self.undo_stack.end_group(self.cursor_state());
+
+        if changed {
+            self.buffer_changed();
+        }
+        self.scroll_to_cursor();
+    }
+
+    /// Add a caret on the line above the topmost caret, or below the bottommost one
+    fn add_caret_vertical(&mut self, above: bool) {
+        let (top, bottom) = self.cursor.caret_rows();
+        let row = if above {
+            match top.checked_sub(1) {
+                Some(row) => row,
+                None => return,
+            }
+        } else if bottom + 1 < self.buffer.len_lines() {
+            bottom + 1
+        } else {
+            return;
+        };
+
+        let col = self.cursor.col().min(self.buffer.line_len(row));
+        self.cursor.add_caret(cursor::Caret::new(row, col, None));
+        self.report_caret_count();
+    }
+
+    /// Select the word under the cursor, or add a caret on the next occurrence of the selection
+    fn add_next_occurrence(&mut self) {
+        let Some(selection) = self.text_selection.clone() else {
+            let row = self.cursor.row();
+            let line = self.buffer.line(row);
+            let (start, end) = mouse::find_word_boundaries(&line, self.cursor.col());
+            if start < end {
+                self.text_selection = Some(mouse::TextSelection::new(row, start, row, end));
+                self.selection_anchor = Some((row, start));
+                self.cursor.set_position(row, end);
+                self.status_message = i18n::t("status-word-selected");
+            }
+            return;
+        };
+
+        let needle = selection.get_selected_text(&self.buffer);
+        if needle.is_empty() {
+            return;
+        }
+
+        // Continue after the last selected occurrence, wrapping around to the start
+        let selected: Vec<(usize, usize)> = std::iter::once(&selection)
+            .chain(self.cursor.extra_carets().iter().filter_map(|caret| caret.selection.as_ref()))
+            .map(|sel| self.caret_range(0, 0, Some(sel)))
+            .collect();
+        let from = selected.iter().map(|(_, end)| *end).max().unwrap_or(0);
+        let text = self.buffer.to_string();
+        let from_byte = self.buffer.char_to_byte(from);
+        let found = text[from_byte..]
+            .find(&needle)
+            .map(|offset| from_byte + offset)
+            .or_else(|| text.find(&needle));
+
+        let Some(byte_start) = found else {
+            return;
+        };
+        let start = self.buffer.byte_to_char(byte_start);
+        let end = start + needle.chars().count();
+        if selected.contains(&(start, end)) {
+            self.status_message = i18n::t("status-all-occurrences-selected");
+            return;
+        }
+
+        let (start_row, start_col) = self.buffer.char_to_pos(start);
+        let (end_row, end_col) = self.buffer.char_to_pos(end);
+        let occurrence = mouse::TextSelection::new(start_row, start_col, end_row, end_col);
+        self.cursor.add_caret(cursor::Caret::new(end_row, end_col, Some(occurrence)));
+        self.report_caret_count();
+    }
+
+    /// Turn a rectangular block into one caret per row, each selecting its slice of the block
+    fn select_block(&mut self, start_row: usize, start_col: usize, end_row: usize, end_col: usize) {
+        let (first, _) = self.screen_to_buffer(start_row, start_col);
+        let (last, _) = self.screen_to_buffer(end_row, end_col);
+        let (first, last) = (first.min(last), first.max(last));
+        // Block edges are screen columns, mapped to each row's own chars
+        let (start_col, end_col) = (start_col.saturating_sub(render::GUTTER_WIDTH), end_col.saturating_sub(render::GUTTER_WIDTH));
+        let (left, right) = (start_col.min(end_col), start_col.max(end_col));
+
+        let mut carets: Vec<cursor::Caret> = (first..=last)
+            .map(|row| {
+                let (from, to) = (self.screen_col_to_col(row, left), self.screen_col_to_col(row, right));
+                let selection = (from < to).then(|| mouse::TextSelection::new(row, from, row, to));
+                cursor::Caret::new(row, to, selection)
+            })
+            .collect();
+
+        // The caret on the row where the drag ended becomes the primary one
+        let primary_idx = if end_row >= start_row { carets.len() - 1 } else { 0 };
+        let primary = carets.remove(primary_idx);
+        self.cursor.set_position(primary.position.row, primary.position.col);
+        self.text_selection = primary.selection;
+        self.selection_anchor = None;
+        self.cursor.set_extra_carets(carets);
+        self.undo_stack.seal();
+        self.report_caret_count();
+    }
+
+    /// Collapse back to the primary caret
+    fn clear_extra_carets(&mut self) {
+        if self.cursor.has_extra_carets() {
+            self.cursor.clear_extra_carets();
+        }
+    }
+
+    fn report_caret_count(&mut self) {
+        let mut args = HashMap::new();
+        args.insert("count".to_string(), FluentValue::from(self.cursor.caret_count()));
+        self.status_message = i18n::t_with_args("status-carets", args);
+    }
+
+    fn insert_char(&mut self, ch: char) {
+        let cursor_row = self.cursor.row();
+        let cursor_col = self.cursor.col();
+        
+        if cursor_row < self.buffer.len_lines() {
+            let before = self.cursor_state();
+            let edit = undo::Edit::insert(self.buffer.pos_to_char(cursor_row, cursor_col), ch.to_string());
+            edit.apply(&mut self.buffer);
+            
+            // Move cursor right after inserting character
+            self.cursor.move_cursor(Direction::Right, &self.buffer);
+            self.record_edit(edit, before, true);
+        }
+    }
+
+    fn delete_char(&mut self) {
+        let cursor_row = self.cursor.row();
+        let cursor_col = self.cursor.col();
+        
+        if cursor_col > 0 && cursor_row < self.buffer.len_lines() {
+            if cursor_col <= self.buffer.line_len(cursor_row) {
+                let before = self.cursor_state();
+                let end = self.buffer.pos_to_char(cursor_row, cursor_col);
+                // Remove the whole grapheme cluster, e.g. a letter with its accents
+                let start = self.buffer.prev_grapheme_boundary(end);
+                let edit = undo::Edit::delete(start, self.buffer.slice(start, end));
+                edit.apply(&mut self.buffer);
+                let (row, col) = self.buffer.char_to_pos(start);
+                self.cursor.set_position(row, col);
+                self.record_edit(edit, before, true);
+            }
+        } else if cursor_row > 0 && cursor_col == 0 {
+            // Join with previous line
+            let before = self.cursor_state();
+            let new_cursor_col = self.buffer.line_len(cursor_row - 1);
+            let start = self.buffer.pos_to_char(cursor_row - 1, new_cursor_col);
+            let end = self.buffer.line_to_char(cursor_row);
+            let edit = undo::Edit::delete(start, self.buffer.slice(start, end));
+            edit.apply(&mut self.buffer);
+            self.cursor.set_position(cursor_row - 1, new_cursor_col);
+            self.record_edit(edit, before, false);
+        }
+    }
+
+    fn delete_char_forward(&mut self) {
+        let cursor_row = self.cursor.row();
+        let cursor_col = self.cursor.col();
+        let line_len = self.buffer.line_len(cursor_row);
+
+        if cursor_row < self.buffer.len_lines() && (cursor_col < line_len || cursor_row < self.buffer.len_lines() - 1) {
+            let before = self.cursor_state();
+            let start = self.buffer.pos_to_char(cursor_row, cursor_col);
+            // At the end of a line this removes the line break, joining with the next line
+            let end = self.buffer.next_grapheme_boundary(start);
+            let edit = undo::Edit::delete(start, self.buffer.slice(start, end));
+            edit.apply(&mut self.buffer);
+            self.record_edit(edit, before, cursor_col < line_len);
+        }
+    }
+
+    fn insert_newline(&mut self) {
+        let cursor_row = self.cursor.row();
+        let cursor_col = self.cursor.col();
+        
+        let before = self.cursor_state();
+        let edit = undo::Edit::insert(self.buffer.pos_to_char(cursor_row, cursor_col), "\n");
+        edit.apply(&mut self.buffer);
+        
+        self.cursor.set_position(cursor_row + 1, 0);
+        self.record_edit(edit, before, false);
+    }
+
+    fn duplicate_line(&mut self) {
+        self.clear_extra_carets();
+        let cursor_row = self.cursor.row();
+        if cursor_row < self.buffer.len_lines() {
+            let before = self.cursor_state();
+            let line_end = self.buffer.pos_to_char(cursor_row, self.buffer.line_len(cursor_row));
+            let edit = undo::Edit::insert(line_end, format!("\n{}", self.buffer.line(cursor_row)));
+            edit.apply(&mut self.buffer);
+            self.cursor.set_position(cursor_row + 1, self.cursor.col());
+            self.record_edit(edit, before, false);
+            self.status_message = i18n::t("status-line-duplicated");
+        }
+    }
+
+    fn delete_line(&mut self) {
+        self.clear_extra_carets();
+        let cursor_row = self.cursor.row();
+        if cursor_row >= self.buffer.len_lines() {
+            return;
+        }
+
+        let before = self.cursor_state();
+        let was_last_line = self.buffer.len_lines() == 1;
+        let (start, end) = self.buffer.line_range(cursor_row);
+        let edit = undo::Edit::delete(start, self.buffer.slice(start, end));
+        edit.apply(&mut self.buffer);
+
+        let new_row = cursor_row.min(self.buffer.len_lines() - 1);
+        self.cursor.set_position(new_row, 0);
+        self.record_edit(edit, before, false);
+        self.status_message = if was_last_line {
+            i18n::t("status-line-cleared")
+        } else {
+            i18n::t("status-line-deleted")
+        };
+    }
+
+    fn undo(&mut self) {
+        match self.undo_stack.undo(&mut self.buffer) {
+            Some(state) => {
+                self.restore_cursor_state(state);
+                self.status_message = i18n::t("status-undo");
+            }
+            None => self.status_message = i18n::t("status-nothing-to-undo"),
+        }
+    }
+
+    fn redo(&mut self) {
+        match self.undo_stack.redo(&mut self.buffer) {
+            Some(state) => {
+                self.restore_cursor_state(state);
+                self.status_message = i18n::t("status-redo");
+            }
+            None => self.status_message = i18n::t("status-nothing-to-redo"),
+        }
+    }
+
+    /// Text of every caret's selection, in document order, one per line
+    fn selected_text(&self) -> Option<String> {
+        let mut selections: Vec<&mouse::TextSelection> = self.text_selection
+            .iter()
+            .chain(self.cursor.extra_carets().iter().filter_map(|caret| caret.selection.as_ref()))
+            .collect();
+        if selections.is_empty() {
+            return None;
+        }
+        selections.sort_by_key(|sel| (sel.start_row, sel.start_col));
+
+        let text = selections
+            .iter()
+            .map(|sel| sel.get_selected_text(&self.buffer))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(text)
+    }
+
+    /// Copy the selected text to the clipboard; false if nothing was copied
+    fn copy_selection(&mut self) -> bool {
+        let Some(text) = self.selected_text() else {
+            self.status_message = i18n::t("status-nothing-selected");
+            return false;
+        };
+        let forwarded = match self.clipboard.copy(&text) {
+            Ok(forwarded) => forwarded,
+            Err(e) => {
+                let mut args = HashMap::new();
+                args.insert("error".to_string(), FluentValue::from(e.to_string()));
+                self.status_message = i18n::t_with_args("status-clipboard-error", args);
+                return false;
+            }
+        };
+        // Keep Vim's `+` register in step with the clipboard
+        if let Some(vim) = self.vim.as_mut() {
+            vim.registers_mut().set('+', vim::Register::new(text.clone(), false));
+        }
+
+        let mut args = HashMap::new();
+        args.insert("count".to_string(), FluentValue::from(text.chars().count()));
+        let message = if forwarded { "status-copied" } else { "status-copied-internal" };
+        self.status_message = i18n::t_with_args(message, args);
+        true
+    }
+
+    /// Copy the selection and delete it; on a failed copy the text and the error are kept
+    fn cut_selection(&mut self) {
+        if self.cursor.has_extra_carets() {
+            if !self.copy_selection() {
+                return;
+            }
+            self.edit_all_carets(cursor::CaretEdit::Insert(""));
+            self.status_message = i18n::t("status-cut");
+            return;
+        }
+        if self.text_selection.is_none() {
+            self.status_message = i18n::t("status-nothing-selected");
+            return;
+        }
+
+        if !self.copy_selection() {
+            return;
+        }
+        self.delete_selected_text();
+        self.status_message = i18n::t("status-cut");
+    }
+
+    fn paste_from_clipboard(&mut self) {
+        match self.clipboard.paste().map(str::to_string) {
+            Some(text) if !text.is_empty() => self.paste_text(&text),
+            _ => self.status_message = i18n::t("status-clipboard-empty"),
+        }
+    }
+
+    /// Insert pasted text at the cursor, replacing any selection, as one undo step
+    fn paste_text(&mut self, text: &str) {
+        let text = clipboard::normalize_line_endings(text);
+
+        if self.cursor.has_extra_carets() {
+            self.edit_all_carets(cursor::CaretEdit::Insert(&text));
+            let mut args = HashMap::new();
+            args.insert("count".to_string(), FluentValue::from(text.lines().count().max(1)));
+            self.status_message = i18n::t_with_args("status-pasted", args);
+            return;
+        }
+
+        self.undo_stack.begin_group(self.cursor_state());
+        self.remove_selected_text();
+
+        let before = self.cursor_state();
+        let at = self.buffer.pos_to_char(self.cursor.row(), self.cursor.col());
+        let edit = undo::Edit::insert(at, text.as_str());
+        edit.apply(&mut self.buffer);
+
+        // Multi-line text is split into lines by the buffer itself
+        let (row, col) = self.buffer.char_to_pos(at + text.chars().count());
+        self.cursor.set_position(row, col);
+        self.undo_stack.record(edit, before, self.cursor_state());
+        self.undo_stack.end_group(self.cursor_state());
+        self.buffer_changed();
+
+        let mut args = HashMap::new();
+        args.insert("count".to_string(), FluentValue::from(text.lines().count().max(1)));
+        self.status_message = i18n::t_with_args("status-pasted", args);
+    }
+
+    /// Put the cursor and selection back after an undo or redo step
+    fn restore_cursor_state(&mut self, state: undo::CursorState) {
+        self.cursor.set_position(state.row, state.col);
+        self.cursor.validate_position(&self.buffer);
+        self.text_selection = state.selection;
+        self.selection_anchor = None;
+        self.cursor.clear_extra_carets();
+        // Undoing back to the saved text leaves nothing unsaved
+        self.modified = self.saved_revision != Some(self.undo_stack.revision());
+        self.schedule_lint();
+        if self.search_active {
+            self.search.update(&self.buffer);
+        }
+    }
+
+    /// Text area size as (columns, rows)
+    fn viewport_size(&self) -> (usize, usize) {
+        let rect = self
+            .pane_rects()
+            .into_iter()
+            .find(|(pane, _)| *pane == self.focused_pane)
+            .map_or(Rect::new(0, 0, 80, 23), |(_, rect)| rect);
+        (render::content_width(rect.width).max(1), render::visible_lines(rect.height).max(1))
+    }
+
+    /// Scroll just enough to bring the cursor into view
+    fn scroll_to_cursor(&mut self) {
+        let (width, visible_lines) = self.viewport_size();
+        if self.soft_wrap {
+            self.scroll_offset = wrap::scroll_to_show(&self.buffer, self.scroll_offset, self.cursor.row(), self.cursor.col(), width, visible_lines);
+            self.scroll_offset_x = 0;
+        } else {
+            self.scroll_offset = self.cursor.adjust_scroll_for_visibility(
+                self.scroll_offset,
+                visible_lines,
+                self.buffer.len_lines(),
+            );
+            let line = self.buffer.line(self.cursor.row());
+            self.scroll_offset_x = self.cursor.adjust_horizontal_scroll(self.scroll_offset_x, width, &line);
+        }
+    }
+
+    /// Buffer position under a screen cell, accounting for the header, gutter, scrolling and wrapping
+    fn screen_to_buffer(&self, screen_row: usize, screen_col: usize) -> (usize, usize) {
+        let visual_row = screen_row.saturating_sub(render::HEADER_HEIGHT);
+        let col = screen_col.saturating_sub(render::GUTTER_WIDTH);
+        if self.soft_wrap {
+            let (width, height) = self.viewport_size();
+            let (row, cursor_col) = (self.cursor.row(), self.cursor.col());
+            let hidden = wrap::hidden_rows(&self.buffer, self.scroll_offset, row, cursor_col, width, height);
+            return wrap::position_at(&self.buffer, self.scroll_offset, visual_row + hidden, col, width);
+        }
+        let row = (self.scroll_offset + visual_row).min(self.buffer.len_lines().saturating_sub(1));
+        (row, self.screen_col_to_col(row, col))
+    }
+
+    /// Char column on `row` under a screen column of the text area, with wrapping off
+    fn screen_col_to_col(&self, row: usize, col: usize) -> usize {
+        let line = self.buffer.line(row);
+        // Rendering starts at the cluster that covers the scroll offset
+        let first_col = text_metrics::col_at_display(&line, self.scroll_offset_x);
+        text_metrics::col_at_display(&line, text_metrics::display_col(&line, first_col) + col)
+    }
+
+    fn open_goto_prompt(&mut self) {
+        self.clear_extra_carets();
+        self.goto_prompt = Some(GotoPrompt {
+            input: String::new(),
+            origin: self.cursor_state(),
+            origin_scroll: self.scroll_offset,
+        });
+        self.text_selection = None;
+        self.update_goto_status();
+    }
+
+    /// Live preview: move to the target as it is typed, or back to the origin if it doesn't parse
+    fn preview_goto_target(&mut self) {
+        let Some(prompt) = &self.goto_prompt else {
+            return;
+        };
+
+        match cursor::cursor_utils::parse_goto_target(&prompt.input, prompt.origin.row, &self.buffer) {
+            Some(target) => {
+                self.cursor.set_position(target.row, target.col);
+                self.scroll_to_cursor();
+            }
+            None => {
+                self.cursor.set_position(prompt.origin.row, prompt.origin.col);
+                self.scroll_offset = prompt.origin_scroll;
+            }
+        }
+        self.update_goto_status();
+    }
+
+    fn update_goto_status(&mut self) {
+        if let Some(prompt) = &self.goto_prompt {
+            let mut args = HashMap::new();
+            args.insert("input".to_string(), FluentValue::from(format!("{}▏", prompt.input)));
+            self.status_message = i18n::t_with_args("goto-prompt", args);
+        }
+    }
+
+    fn handle_goto_prompt_key(&mut self, key_event: KeyEvent) {
+        let Some(prompt) = self.goto_prompt.as_mut() else {
+            return;
+        };
+
+        match key_event.code {
+            // Esc - Cancel and restore the original position
+            KeyCode::Esc => {
+                if let Some(prompt) = self.goto_prompt.take() {
+                    self.cursor.set_position(prompt.origin.row, prompt.origin.col);
+                    self.scroll_offset = prompt.origin_scroll;
+                }
+                self.status_message = i18n::t("goto-canceled");
+            }
+
+            // Enter - Jump to the target
+            KeyCode::Enter => {
+                let input = prompt.input.clone();
+                let origin_row = prompt.origin.row;
+                match cursor::cursor_utils::parse_goto_target(&input, origin_row, &self.buffer) {
+                    Some(target) => {
+                        self.goto_prompt = None;
+                        self.cursor.set_position(target.row, target.col);
+                        self.undo_stack.seal();
+                        self.scroll_to_cursor();
+
+                        let mut args = HashMap::new();
+                        args.insert("row".to_string(), FluentValue::from(target.row + 1));
+                        args.insert("col".to_string(), FluentValue::from(target.col + 1));
+                        self.status_message = i18n::t_with_args("status-cursor-moved", args);
+                    }
+                    None => {
+                        // Keep the prompt open so the input can be fixed
+                        let mut args = HashMap::new();
+                        args.insert("input".to_string(), FluentValue::from(input));
+                        self.status_message = i18n::t_with_args("goto-invalid", args);
+                    }
+                }
+            }
+
+            KeyCode::Backspace => {
+                prompt.input.pop();
+                self.preview_goto_target();
+            }
+
+            KeyCode::Char(ch) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                prompt.input.push(ch);
+                self.preview_goto_target();
+            }
+
+            _ => {}
+        }
+    }
+
+    fn open_theme_picker(&mut self) {
+        let themes: Vec<String> = self.syntax_highlighter.get_available_themes().into_iter().cloned().collect();
+        let origin = self.syntax_highlighter.current_theme().to_string();
+        let selected = themes.iter().position(|theme| *theme == origin).unwrap_or(0);
+        self.theme_picker = Some(ThemePicker { themes, selected, origin });
+        self.update_theme_picker_status();
+    }
+
+    fn update_theme_picker_status(&mut self) {
+        if let Some(picker) = &self.theme_picker {
+            let mut args = HashMap::new();
+            args.insert("name".to_string(), FluentValue::from(picker.themes[picker.selected].clone()));
+            args.insert("index".to_string(), FluentValue::from(picker.selected + 1));
+            args.insert("count".to_string(), FluentValue::from(picker.themes.len()));
+            self.status_message = i18n::t_with_args("theme-picker-prompt", args);
+        }
+    }
+
+    fn handle_theme_picker_key(&mut self, key_event: KeyEvent) {
+        let Some(picker) = self.theme_picker.as_mut() else {
+            return;
+        };
+        let count = picker.themes.len();
+
+        match key_event.code {
+            // Esc - Go back to the theme in use before the picker opened
+            KeyCode::Esc => {
+                if let Some(picker) = self.theme_picker.take() {
+                    self.syntax_highlighter.set_theme(&picker.origin);
+                }
+                self.status_message = i18n::t("theme-picker-canceled");
+                return;
+            }
+
+            // Enter - Keep the previewed theme and remember it
+            KeyCode::Enter => {
+                let name = picker.themes[picker.selected].clone();
+                self.theme_picker = None;
+                self.settings.theme = Some(name.clone());
+                let mut args = HashMap::new();
+                args.insert("name".to_string(), FluentValue::from(name));
+                self.status_message = match self.settings.save() {
+                    Ok(()) => i18n::t_with_args("theme-applied", args),
+                    Err(e) => {
+                        args.insert("error".to_string(), FluentValue::from(e.to_string()));
+                        i18n::t_with_args("theme-not-saved", args)
+                    }
+                };
+                return;
+            }
+
+            KeyCode::Up | KeyCode::Left | KeyCode::BackTab => picker.selected = (picker.selected + count - 1) % count,
+            KeyCode::Down | KeyCode::Right | KeyCode::Tab => picker.selected = (picker.selected + 1) % count,
+            KeyCode::Home => picker.selected = 0,
+            KeyCode::End => picker.selected = count - 1,
+            _ => return,
+        }
+
+        // Live preview of the selected theme
+        let theme = picker.themes[picker.selected].clone();
+        self.syntax_highlighter.set_theme(&theme);
+        self.update_theme_picker_status();
+    }
+
+    fn open_language_picker(&mut self) {
+        let mut picker = LanguagePicker {
+            query: String::new(),
+            languages: self.syntax_highlighter.languages().languages(),
+            matches: Vec::new(),
+            selected: 0,
+        };
+        picker.refilter();
+        // Start on the language picked before, if any
+        if let Some(current) = &self.language {
+            picker.selected = picker.matches.iter().position(|name| name.as_ref() == Some(current)).unwrap_or(0);
+        }
+        self.language_picker = Some(picker);
+        self.update_language_picker_status();
+    }
+
+    fn update_language_picker_status(&mut self) {
+        if let Some(picker) = &self.language_picker {
+            let mut args = HashMap::new();
+            args.insert("query".to_string(), FluentValue::from(format!("{}▏", picker.query)));
+            self.status_message = match picker.matches.get(picker.selected) {
+                Some(choice) => {
+                    let name = choice.clone().unwrap_or_else(|| i18n::t("language-picker-detect"));
+                    args.insert("name".to_string(), FluentValue::from(name));
+                    args.insert("index".to_string(), FluentValue::from(picker.selected + 1));
+                    args.insert("count".to_string(), FluentValue::from(picker.matches.len()));
+                    i18n::t_with_args("language-picker-prompt", args)
+                }
+                None => i18n::t_with_args("language-picker-no-match", args),
+            };
+        }
+    }
+
+    fn handle_language_picker_key(&mut self, key_event: KeyEvent) {
+        let Some(picker) = self.language_picker.as_mut() else {
+            return;
+        };
+        let count = picker.matches.len().max(1);
+
+        match key_event.code {
+            KeyCode::Esc => {
+                self.language_picker = None;
+                self.status_message = i18n::t("language-picker-canceled");
+                return;
+            }
+
+            // Enter - Use the selected language for this buffer and remember it for the file
+            KeyCode::Enter => {
+                let Some(choice) = picker.matches.get(picker.selected).cloned() else {
+                    return;
+                };
+                self.language_picker = None;
+                self.set_language(choice);
+                return;
+            }
+
+            KeyCode::Up | KeyCode::BackTab => picker.selected = (picker.selected + count - 1) % count,
+            KeyCode::Down | KeyCode::Tab => picker.selected = (picker.selected + 1) % count,
+            KeyCode::Home => picker.selected = 0,
+            KeyCode::End => picker.selected = count - 1,
+            KeyCode::Backspace => {
+                picker.query.pop();
+                picker.refilter();
+            }
+            KeyCode::Char(ch) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                picker.query.push(ch);
+                picker.refilter();
+            }
+            _ => return,
+        }
+        self.update_language_picker_status();
+    }
+
+    /// Highlight and lint the active buffer as `language`; `None` goes back to detection
+    fn set_language(&mut self, language: Option<String>) {
+        self.language = language.clone();
+        self.lint_language = None;
+        if self.linter.is_enabled() {
+            self.run_linting();
+        }
+
+        let name = self.syntax_highlighter.syntax_name_for(self.language.as_deref(), self.filename.as_deref(), &self.buffer);
+        let mut args = HashMap::new();
+        args.insert("name".to_string(), FluentValue::from(name));
+        let message = if language.is_some() { "language-set" } else { "language-detected" };
+        let Some(filename) = self.filename.clone() else {
+            self.status_message = i18n::t_with_args(message, args);
+            return;
+        };
+        self.settings.set_language(&filename, language);
+        self.status_message = match self.settings.save() {
+            Ok(()) => i18n::t_with_args(message, args),
+            Err(e) => {
+                args.insert("error".to_string(), FluentValue::from(e.to_string()));
+                i18n::t_with_args("language-not-saved", args)
+            }
+        };
+    }
+
+    /// Show the message of the lint issue under the cursor when the cursor lands on one
+    fn show_issue_at_cursor(&mut self) {
+        let (row, col) = (self.cursor.row(), self.cursor.col());
+        if self.issue_status.as_ref().is_some_and(|(position, _)| *position == (row, col)) {
+            return;
+        }
+        let shown = self.issue_status.take().and_then(|(_, message)| message);
+        // Prompts and pickers own the status line while they are open
+        let prompt_open = self.search_prompt.is_some()
+            || self.goto_prompt.is_some()
+            || self.theme_picker.is_some()
+            || self.language_picker.is_some()
+            || self.quick_fix_menu.is_some()
+            || self.keymap.pending_keys().is_some();
+        // Issue positions are out of date until the pending lint has run
+        if prompt_open || self.lint_due.is_some() {
+            return;
+        }
+
+        let message = linter::issue_at(&self.lint_issues, row, col, self.buffer.line_len(row)).map(|issue| {
+            let mut args = HashMap::new();
+            args.insert("rule".to_string(), FluentValue::from(issue.rule_name.clone()));
+            args.insert("message".to_string(), FluentValue::from(issue.message.clone()));
+            i18n::t_with_args("lint-issue-at-cursor", args)
+        });
+        match &message {
+            Some(message) => self.status_message = message.clone(),
+            // Moving off an issue takes its message away
+            None if shown.as_ref() == Some(&self.status_message) => self.status_message.clear(),
+            None => {}
+        }
+        self.issue_status = Some(((row, col), message));
+    }
+
+    fn open_quick_fix_menu(&mut self) {
+        self.run_pending_lint();
+        let line = self.cursor.row() + 1;
+        let fixes: Vec<linter::LintFix> = self
+            .lint_issues
+            .iter()
+            .filter(|issue| issue.line == line)
+            .flat_map(|issue| issue.fixes.iter().cloned())
+            .collect();
+        if fixes.is_empty() {
+            self.status_message = i18n::t("quick-fix-none");
+            return;
+        }
+        self.quick_fix_menu = Some(QuickFixMenu { fixes, selected: 0 });
+        self.update_quick_fix_status();
+    }
+
+    fn update_quick_fix_status(&mut self) {
+        if let Some(menu) = &self.quick_fix_menu {
+            let mut args = HashMap::new();
+            args.insert("title".to_string(), FluentValue::from(menu.fixes[menu.selected].title.clone()));
+            args.insert("index".to_string(), FluentValue::from(menu.selected + 1));
+            args.insert("count".to_string(), FluentValue::from(menu.fixes.len()));
+            self.status_message = i18n::t_with_args("quick-fix-prompt", args);
+        }
+    }
+
+    fn handle_quick_fix_key(&mut self, key_event: KeyEvent) {
+        let Some(menu) = self.quick_fix_menu.as_mut() else {
+            return;
+        };
+        let count = menu.fixes.len();
+
+        match key_event.code {
+            KeyCode::Esc => {
+                self.quick_fix_menu = None;
+                self.status_message = i18n::t("quick-fix-canceled");
+                return;
+            }
+
+            KeyCode::Enter => {
+                let fix = menu.fixes[menu.selected].clone();
+                self.quick_fix_menu = None;
+                self.apply_text_edits(&fix.edits);
+                let mut args = HashMap::new();
+                args.insert("title".to_string(), FluentValue::from(fix.title));
+                self.status_message = i18n::t_with_args("quick-fix-applied", args);
+                return;
+            }
+
+            KeyCode::Up | KeyCode::BackTab => menu.selected = (menu.selected + count - 1) % count,
+            KeyCode::Down | KeyCode::Tab => menu.selected = (menu.selected + 1) % count,
+            KeyCode::Home => menu.selected = 0,
+            KeyCode::End => menu.selected = count - 1,
+            _ => return,
+        }
+        self.update_quick_fix_status();
+    }
+
+    /// Apply the preferred fix of every lint issue in the buffer
+    fn fix_all(&mut self) {
+        self.run_pending_lint();
+        let edits = linter::fix_all_edits(&self.lint_issues);
+        if edits.is_empty() {
+            self.status_message = i18n::t("fix-all-none");
+            return;
+        }
+        self.apply_text_edits(&edits);
+        let mut args = HashMap::new();
+        args.insert("count".to_string(), FluentValue::from(edits.len()));
+        self.status_message = i18n::t_with_args("fix-all-applied", args);
+    }
+
+    /// Apply non-overlapping lint edits as a single undo step
+    fn apply_text_edits(&mut self, edits: &[linter::TextEdit]) {
+        self.clear_extra_carets();
+        self.text_selection = None;
+        self.selection_anchor = None;
+        let mut ranges: Vec<(search::SearchMatch, &str)> = edits
+            .iter()
+            .map(|edit| {
+                let start = self.buffer.pos_to_char(edit.start.0, edit.start.1);
+                let end = self.buffer.pos_to_char(edit.end.0, edit.end.1);
+                (search::SearchMatch { start, end }, edit.text.as_str())
+            })
+            .collect();
+        ranges.sort_by_key(|(range, _)| range.start);
+
+        // Edit from the end so earlier char positions stay valid; the buffer
+        // is linted once afterwards rather than after every edit
+        self.undo_stack.begin_group(self.cursor_state());
+        for (range, text) in ranges.iter().rev() {
+            self.apply_replacement(*range, text);
+        }
+        self.cursor.validate_position(&self.buffer);
+        self.undo_stack.end_group(self.cursor_state());
+        self.buffer_changed();
+    }
+
+    fn open_search_prompt(&mut self, replace_mode: bool, backward: bool) {
+        self.clear_extra_carets();
+        self.search_prompt = Some(SearchPrompt {
+            field: if replace_mode && !self.search.query().is_empty() {
+                SearchField::Replacement
+            } else {
+                SearchField::Query
+            },
+            replace_mode,
+            backward,
+            replacement: String::new(),
+            origin: self.cursor_state(),
+            origin_scroll: self.scroll_offset,
+        });
+        self.search_active = true;
+        self.text_selection = None;
+        self.search.update(&self.buffer);
+        self.search_from_origin();
+    }
+
+    /// Incremental search: jump to the nearest match from where the prompt was opened
+    fn search_from_origin(&mut self) {
+        let Some(prompt) = &self.search_prompt else {
+            return;
+        };
+        let origin = prompt.origin.clone();
+        let origin_scroll = prompt.origin_scroll;
+        let backward = prompt.backward;
+
+        let origin_idx = self.buffer.pos_to_char(origin.row, origin.col);
+        match self.search.select_from(origin_idx, !backward) {
+            Some(found) => self.jump_to_match(found),
+            None => {
+                self.cursor.set_position(origin.row, origin.col);
+                self.scroll_offset = origin_scroll;
+            }
+        }
+        self.update_search_status();
+    }
+
+    fn jump_to_match(&mut self, found: search::SearchMatch) {
+        let (row, col) = self.buffer.char_to_pos(found.start);
+        self.cursor.set_position(row, col);
+        self.undo_stack.seal();
+        self.scroll_to_cursor();
+    }
+
+    /// Go to the next or previous match outside the prompt (F3 / Shift+F3)
+    fn find_next(&mut self, backward: bool) {
+        if self.search.query().is_empty() {
+            self.open_search_prompt(false, backward);
+            return;
+        }
+
+        if !self.search_active {
+            self.search_active = true;
+            self.search.update(&self.buffer);
+        }
+
+        let cursor_idx = self.buffer.pos_to_char(self.cursor.row(), self.cursor.col());
+        let found = if backward {
+            self.search.select_from(cursor_idx, false)
+        } else {
+            self.search.select_from(cursor_idx + 1, true)
+        };
+        if let Some(found) = found {
+            self.jump_to_match(found);
+        }
+        self.update_search_status();
+    }
+
+    /// Move to the adjacent match while the prompt is open
+    fn step_search_match(&mut self, backward: bool) {
+        if let Some(prompt) = self.search_prompt.as_mut() {
+            prompt.backward = backward;
+        }
+        let found = if backward { self.search.prev_match() } else { self.search.next_match() };
+        if let Some(found) = found {
+            self.jump_to_match(found);
+        }
+        self.update_search_status();
+    }
+
+    fn close_search_prompt(&mut self, cancel: bool) {
+        if let Some(prompt) = self.search_prompt.take() {
+            if cancel {
+                // Replacements already moved text around, so only a plain search jumps back
+                if !prompt.replace_mode {
+                    self.cursor.set_position(prompt.origin.row, prompt.origin.col);
+                    self.scroll_offset = prompt.origin_scroll;
+                }
+                self.search_active = false;
+                self.status_message = i18n::t("search-canceled");
+            } else {
+                self.update_search_status();
+            }
+        }
+    }
+
+    fn replace_current_match(&mut self) {
+        let Some(found) = self.search.current_match() else {
+            self.update_search_status();
+            return;
+        };
+        let template = self.search_prompt.as_ref().map(|p| p.replacement.clone()).unwrap_or_default();
+        let replacement = self.search.replacement_for(&self.buffer, found, &template);
+
+        self.undo_stack.begin_group(self.cursor_state());
+        self.apply_replacement(found, &replacement);
+        let next_idx = found.start + replacement.chars().count();
+        let (row, col) = self.buffer.char_to_pos(next_idx);
+        self.cursor.set_position(row, col);
+        self.undo_stack.end_group(self.cursor_state());
+        self.buffer_changed();
+
+        // Continue with the match after the replaced text
+        if let Some(next) = self.search.select_from(next_idx, true) {
+            self.jump_to_match(next);
+        }
+        self.update_search_status();
+    }
+
+    fn replace_all_matches(&mut self) {
+        let template = self.search_prompt.as_ref().map(|p| p.replacement.clone()).unwrap_or_default();
+        let plan = self.search.replace_all_plan(&self.buffer, &template);
+        if plan.is_empty() {
+            self.update_search_status();
+            return;
+        }
+        self.search_prompt = None;
+
+        // Replace from the end so earlier char positions stay valid
+        self.undo_stack.begin_group(self.cursor_state());
+        for (found, replacement) in plan.iter().rev() {
+            self.apply_replacement(*found, replacement);
+        }
+        self.cursor.validate_position(&self.buffer);
+        self.undo_stack.end_group(self.cursor_state());
+        self.buffer_changed();
+
+        let mut args = HashMap::new();
+        args.insert("count".to_string(), FluentValue::from(plan.len()));
+        self.status_message = i18n::t_with_args("search-replaced-all", args);
+    }
+
+    /// Replace a match, recording the edits in the undo history.
+    ///
+    /// Lint and search results are left for the caller to refresh with
+    /// [`Self::buffer_changed`] once all its replacements are done.
+    fn apply_replacement(&mut self, found: search::SearchMatch, replacement: &str) {
+        let before = self.cursor_state();
+        let delete = undo::Edit::delete(found.start, self.buffer.slice(found.start, found.end));
+        delete.apply(&mut self.buffer);
+        self.undo_stack.record(delete, before.clone(), self.cursor_state());
+
+        if !replacement.is_empty() {
+            let insert = undo::Edit::insert(found.start, replacement);
+            insert.apply(&mut self.buffer);
+            self.undo_stack.record(insert, before, self.cursor_state());
+        }
+    }
+
+    /// Show the prompt (or the match position) on the status line
+    fn update_search_status(&mut self) {
+        let position = if let Some(error) = self.search.error() {
+            let mut args = HashMap::new();
+            args.insert("error".to_string(), FluentValue::from(error.lines().last().unwrap_or(error).trim()));
+            i18n::t_with_args("search-invalid-regex", args)
+        } else if self.search.query().is_empty() {
+            String::new()
+        } else if let Some(idx) = self.search.current_index() {
+            let mut args = HashMap::new();
+            args.insert("current".to_string(), FluentValue::from(idx + 1));
+            args.insert("total".to_string(), FluentValue::from(self.search.match_count()));
+            i18n::t_with_args("search-match-position", args)
+        } else {
+            i18n::t("search-no-matches")
+        };
+
+        let Some(prompt) = &self.search_prompt else {
+            self.status_message = position;
+            return;
+        };
+
+        let options = self.search.options();
+        let mut flags = String::new();
+        if options.case_sensitive { flags.push_str("[Aa]"); }
+        if options.whole_word { flags.push_str("[W]"); }
+        if options.use_regex { flags.push_str("[.*]"); }
+
+        let (query_marker, replacement_marker) = match prompt.field {
+            SearchField::Query => ("▏", ""),
+            SearchField::Replacement => ("", "▏"),
+        };
+        let mut args = HashMap::new();
+        args.insert("query".to_string(), FluentValue::from(format!("{}{}", self.search.query(), query_marker)));
+        let text = if prompt.replace_mode {
+            args.insert("replacement".to_string(), FluentValue::from(format!("{}{}", prompt.replacement, replacement_marker)));
+            i18n::t_with_args("replace-prompt", args)
+        } else if prompt.backward {
+            i18n::t_with_args("search-prompt-backward", args)
+        } else {
+            i18n::t_with_args("search-prompt", args)
+        };
+
+        self.status_message = [text, flags, position]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" · ");
+    }
+
+    fn handle_search_prompt_key(&mut self, key_event: KeyEvent) {
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key_event.modifiers.contains(KeyModifiers::ALT);
+        let shift = key_event.modifiers.contains(KeyModifiers::SHIFT);
+        let Some(prompt) = self.search_prompt.as_mut() else {
+            return;
+        };
+
+        match key_event.code {
+            // Esc - Cancel and go back to where the search started
+            KeyCode::Esc => {
+                self.close_search_prompt(true);
+                return;
+            }
+
+            // Enter - Accept the match, or replace it in replace mode
+            KeyCode::Enter => {
+                if prompt.replace_mode {
+                    self.replace_current_match();
+                } else {
+                    self.close_search_prompt(false);
+                }
+                return;
+            }
+
+            // Tab - Switch between query and replacement
+            KeyCode::Tab if prompt.replace_mode => {
+                prompt.field = match prompt.field {
+                    SearchField::Query => SearchField::Replacement,
+                    SearchField::Replacement => SearchField::Query,
+                };
+                self.update_search_status();
+                return;
+            }
+
+            // Alt+C/W/R - Toggle case sensitivity, whole word and regex
+            KeyCode::Char('c') if alt => self.search.toggle_case_sensitive(&self.buffer),
+            KeyCode::Char('w') if alt => self.search.toggle_whole_word(&self.buffer),
+            KeyCode::Char('r') if alt => self.search.toggle_regex(&self.buffer),
+
+            // Alt+A - Replace all
+            KeyCode::Char('a') if alt && prompt.replace_mode => {
+                self.replace_all_matches();
+                return;
+            }
+
+            // Down/Ctrl+F/F3 - Next match
+            KeyCode::Down => {
+                self.step_search_match(false);
+                return;
+            }
+            KeyCode::Char('f') if ctrl => {
+                self.step_search_match(false);
+                return;
+            }
+            KeyCode::F(3) if !shift => {
+                self.step_search_match(false);
+                return;
+            }
+
+            // Up/Shift+F3 - Previous match
+            KeyCode::Up | KeyCode::F(3) => {
+                self.step_search_match(true);
+                return;
+            }
+
+            KeyCode::Backspace => match prompt.field {
+                SearchField::Query => {
+                    let mut query = self.search.query().to_string();
+                    query.pop();
+                    self.search.set_query(&query, &self.buffer);
+                }
+                SearchField::Replacement => {
+                    prompt.replacement.pop();
+                    self.update_search_status();
+                    return;
+                }
+            },
+
+            KeyCode::Char(ch) if !ctrl && !alt => match prompt.field {
+                SearchField::Query => {
+                    let mut query = self.search.query().to_string();
+                    query.push(ch);
+                    self.search.set_query(&query, &self.buffer);
+                }
+                SearchField::Replacement => {
+                    prompt.replacement.push(ch);
+                    self.update_search_status();
+                    return;
+                }
+            },
+
+            _ => return,
+        }
+
+        // Query or options changed - search again from the starting point
+        self.search_from_origin();
+    }
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) {
+        // Status line prompts take all keys while they are open
+        if self.search_prompt.is_some() {
+            self.handle_search_prompt_key(key_event);
+            return;
+        }
+        if self.goto_prompt.is_some() {
+            self.handle_goto_prompt_key(key_event);
+            return;
+        }
+        if self.theme_picker.is_some() {
+            self.handle_theme_picker_key(key_event);
+            return;
+        }
+        if self.language_picker.is_some() {
+            self.handle_language_picker_key(key_event);
+            return;
+        }
+        if self.quick_fix_menu.is_some() {
+            self.handle_quick_fix_key(key_event);
+            return;
+        }
+
+        // Vim gets first pick unless a keymap sequence is half typed
+        if self.vim.is_some() && self.keymap.pending_keys().is_none() {
+            let before = self.cursor_state();
+            if let Some(vim) = self.vim.as_mut() {
+                let previous_mode = vim.mode();
+                let response = vim.handle_key(key_event, &mut self.buffer, &mut self.cursor);
+                if response.handled {
+                    self.apply_vim_response(response, before, previous_mode);
+                    return;
+                }
+            }
+        }
+
+        let action = self.keymap.handle_key_event(key_event);
+
+        // Show the first keys of an unfinished sequence (e.g. Ctrl+K Ctrl+C)
+        if let Some(keys) = self.keymap.pending_keys() {
+            let mut args = HashMap::new();
+            args.insert("keys".to_string(), FluentValue::from(keys));
+            self.status_message = i18n::t_with_args("status-keys-pending", args);
+            return;
+        }
+
+        self.handle_action(action);
+        self.scroll_to_cursor();
+
+        // Shortcuts such as Ctrl+C end Vim's visual mode like its own operators do
+        if let Some(vim) = self.vim.as_mut()
+            && matches!(vim.mode(), vim::VimMode::Visual | vim::VimMode::VisualLine)
+        {
+            vim.reset();
+        }
+    }
+
+    /// Record the edits of a Vim key and carry out what it asked the editor to do
+    fn apply_vim_response(&mut self, response: vim::VimResponse, before: undo::CursorState, previous_mode: vim::VimMode) {
+        let Some(vim) = self.vim.as_ref() else {
+            return;
+        };
+        let mode = vim.mode();
+        self.text_selection = vim.selection(&self.cursor, &self.buffer);
+        self.selection_anchor = None;
+
+        // A change and the insert session it starts are one undo step
+        let after = self.cursor_state();
+        if previous_mode != vim::VimMode::Insert {
+            self.undo_stack.begin_group(before.clone());
+        }
+        let changed = !response.edits.is_empty();
+        for edit in response.edits {
+            self.undo_stack.record(edit, before.clone(), after.clone());
+        }
+        if mode != vim::VimMode::Insert {
+            self.undo_stack.end_group(after);
+        }
+        if changed {
+            self.buffer_changed();
+        }
+
+        if let Some(text) = response.clipboard {
+            match self.clipboard.copy(&text) {
+                Ok(true) => {}
+                Ok(false) => {
+                    let mut args = HashMap::new();
+                    args.insert("count".to_string(), FluentValue::from(text.chars().count()));
+                    self.status_message = i18n::t_with_args("status-copied-internal", args);
+                }
+                Err(e) => {
+                    let mut args = HashMap::new();
+                    args.insert("error".to_string(), FluentValue::from(e.to_string()));
+                    self.status_message = i18n::t_with_args("status-clipboard-error", args);
+                }
+            }
+        }
+
+        match response.command {
+            Some(vim::VimCommand::Undo(count)) => (0..count).for_each(|_| self.undo()),
+            Some(vim::VimCommand::Redo(count)) => (0..count).for_each(|_| self.redo()),
+            None => {}
+        }
+
+        self.update_vim_status(previous_mode);
+        self.scroll_to_cursor();
+    }
+
+    /// Show the Vim mode when it changes, and the keys of an unfinished command
+    fn update_vim_status(&mut self, previous_mode: vim::VimMode) {
+        let Some(vim) = self.vim.as_ref() else {
+            return;
+        };
+        if !vim.pending_keys().is_empty() {
+            self.status_message = vim.pending_keys().to_string();
+            return;
+        }
+        if vim.mode() != previous_mode {
+            self.status_message = match vim.mode() {
+                vim::VimMode::Normal => String::new(),
+                vim::VimMode::Insert => i18n::t("vim-mode-insert"),
+                vim::VimMode::Visual => i18n::t("vim-mode-visual"),
+                vim::VimMode::VisualLine => i18n::t("vim-mode-visual-line"),
+            };
+        }
+    }
+
+    /// Switch the Vim layer on or off
+    fn set_vim_enabled(&mut self, enabled: bool) {
+        self.vim = enabled.then(vim::Vim::new);
+        self.text_selection = None;
+        self.selection_anchor = None;
+        self.status_message = if enabled { i18n::t("status-vim-enabled") } else { i18n::t("status-vim-disabled") };
     }
 
-    fn insert_char(&mut self, ch: char) {
-        let cursor_row = self.cursor.row();
-        let cursor_col = self.cursor.col();
-        
-        if cursor_row < self.lines.len() {
-            let line = &mut self.lines[cursor_row];
-            let mut chars: Vec<char> = line.chars().collect();
-            chars.insert(cursor_col, ch);
-            *line = chars.into_iter().collect();
-            
-            // Move cursor right after inserting character
-            self.cursor.move_cursor(Direction::Right, &self.lines);
-            self.modified = true;
-            if self.linter.is_enabled() {
-                self.run_linting();
-            }
+    /// Switch to another keymap profile, turning the Vim layer on or off to match
+    fn set_keymap_profile(&mut self, profile: KeymapProfile) {
+        self.keymap.set_profile(profile);
+        if (profile == KeymapProfile::Vim) != self.vim.is_some() {
+            self.vim = (profile == KeymapProfile::Vim).then(vim::Vim::new);
         }
+        self.mark_active = false;
     }
 
-    fn delete_char(&mut self) {
-        let cursor_row = self.cursor.row();
-        let cursor_col = self.cursor.col();
-        
-        if cursor_col > 0 && cursor_row < self.lines.len() {
-            let line = &mut self.lines[cursor_row];
-            let mut chars: Vec<char> = line.chars().collect();
-            if cursor_col <= chars.len() && cursor_col > 0 {
-                chars.remove(cursor_col - 1);
-                *line = chars.into_iter().collect();
-                self.cursor.move_cursor(Direction::Left, &self.lines);
-                self.modified = true;
-                if self.linter.is_enabled() {
-                    self.run_linting();
-                }
-            }
-        } else if cursor_row > 0 && cursor_col == 0 {
-            // Join with previous line
-            let current_line = self.lines.remove(cursor_row);
-            let new_cursor_col = self.lines[cursor_row - 1].len();
-            self.lines[cursor_row - 1].push_str(&current_line);
-            self.cursor.set_position(cursor_row - 1, new_cursor_col);
-            self.modified = true;
-            if self.linter.is_enabled() {
-                self.run_linting();
-            }
-        }
+    /// Set the Emacs mark at the cursor; motions then extend the region from it
+    fn set_mark(&mut self) {
+        self.text_selection = None;
+        self.selection_anchor = Some((self.cursor.row(), self.cursor.col()));
+        self.mark_active = true;
+        self.status_message = i18n::t("status-mark-set");
     }
 
-    fn insert_newline(&mut self) {
-        let cursor_row = self.cursor.row();
-        let cursor_col = self.cursor.col();
-        
-        if cursor_row < self.lines.len() {
-            let line = self.lines[cursor_row].clone();
-            let (left, right) = line.split_at(cursor_col);
-            self.lines[cursor_row] = left.to_string();
-            self.lines.insert(cursor_row + 1, right.to_string());
+    /// Kill (cut) or copy the region, saving it on the kill ring as well as the clipboard
+    fn kill_region(&mut self, delete: bool) {
+        if let Some(text) = self.selected_text() {
+            self.kill_ring.push(&text);
+        }
+        if delete {
+            self.cut_selection();
         } else {
-            self.lines.push(String::new());
+            self.copy_selection();
+            self.text_selection = None;
+            self.selection_anchor = None;
         }
-        
-        self.cursor.set_position(cursor_row + 1, 0);
-        self.modified = true;
-        if self.linter.is_enabled() {
-            self.run_linting();
+    }
+
+    /// Insert the newest kill at the cursor
+    fn yank(&mut self) {
+        let Some(text) = self.kill_ring.yank().map(str::to_string) else {
+            self.status_message = i18n::t("status-kill-ring-empty");
+            return;
+        };
+        self.paste_text(&text);
+        if !self.cursor.has_extra_carets() {
+            let end = self.buffer.pos_to_char(self.cursor.row(), self.cursor.col());
+            self.last_yank = Some((end - text.chars().count(), end));
         }
     }
 
-    fn handle_key_event(&mut self, key_event: KeyEvent) {
-        // Clear text selection when typing (except for selection-related keys)
-        if !key_event.modifiers.contains(KeyModifiers::SHIFT) && 
-           !key_event.modifiers.contains(KeyModifiers::CONTROL) {
-            match key_event.code {
-                KeyCode::Left | KeyCode::Right | KeyCode::Up | KeyCode::Down => {},
-                _ => self.text_selection = None,
+    /// Replace the text just yanked with the next older kill
+    fn yank_pop(&mut self, last_yank: Option<(usize, usize)>) {
+        let Some((start, end)) = last_yank else {
+            self.status_message = i18n::t("status-yank-pop-without-yank");
+            return;
+        };
+        let Some(text) = self.kill_ring.rotate().map(str::to_string) else {
+            return;
+        };
+
+        self.undo_stack.begin_group(self.cursor_state());
+        let before = self.cursor_state();
+        let removed = undo::Edit::delete(start, self.buffer.slice(start, end));
+        removed.apply(&mut self.buffer);
+        let (row, col) = self.buffer.char_to_pos(start);
+        self.cursor.set_position(row, col);
+        self.undo_stack.record(removed, before, self.cursor_state());
+
+        let before = self.cursor_state();
+        let inserted = undo::Edit::insert(start, text.as_str());
+        inserted.apply(&mut self.buffer);
+        let end = start + text.chars().count();
+        let (row, col) = self.buffer.char_to_pos(end);
+        self.cursor.set_position(row, col);
+        self.undo_stack.record(inserted, before, self.cursor_state());
+        self.undo_stack.end_group(self.cursor_state());
+        self.buffer_changed();
+
+        self.last_yank = Some((start, end));
+        self.scroll_to_cursor();
+    }
+
+    /// Emacs word motion: to the end of the next word or the start of the previous one, across lines
+    fn emacs_word(&mut self, forward: bool) {
+        let (mut row, mut col) = (self.cursor.row(), self.cursor.col());
+        loop {
+            let line = self.buffer.line(row);
+            let target = if forward {
+                cursor::cursor_utils::find_word_end(&line, col)
+            } else {
+                cursor::cursor_utils::find_word_start(&line, col)
+            };
+            if let Some(target) = target {
+                col = target;
+                break;
+            }
+            if forward && row + 1 < self.buffer.len_lines() {
+                row += 1;
+                col = 0;
+            } else if !forward && row > 0 {
+                row -= 1;
+                col = self.buffer.line_len(row);
+            } else {
+                col = if forward { self.buffer.line_len(row) } else { 0 };
+                break;
             }
         }
+        self.cursor.set_position(row, col);
+        self.cursor.clear_extra_carets();
+        self.undo_stack.seal();
+    }
 
-        match (key_event.modifiers.contains(KeyModifiers::CONTROL), key_event.code) {
-            // Ctrl+Q - Quit
-            (true, KeyCode::Char('q')) => {
-                if self.modified {
-                    self.status_message = i18n::t("file-unsaved-changes");
-                    self.modified = false; // Allow quit on second Ctrl+Q
-                } else {
-                    self.quit = true;
-                }
-            }
-            
-            // Ctrl+S - Save
-            (true, KeyCode::Char('s')) => {
+    /// Perform an action resolved by the keymap
+    fn handle_action(&mut self, action: KeyAction) {
+        let is_motion = matches!(
+            action,
+            KeyAction::MoveCursor(_)
+                | KeyAction::MoveWord(_)
+                | KeyAction::ForwardWord
+                | KeyAction::BackwardWord
+                | KeyAction::GoToLineStart
+                | KeyAction::GoToLineEnd
+                | KeyAction::GoToDocumentStart
+                | KeyAction::GoToDocumentEnd
+        );
+        // Yank-pop only applies right after a yank
+        let last_yank = self.last_yank.take();
+        // A refused quit or close goes ahead when it is repeated right away
+        let confirmed = action != KeyAction::None && self.pending_discard.take().is_some_and(|pending| pending == action);
+        // Any command other than a motion deactivates the mark, leaving its region selected
+        if !is_motion && action != KeyAction::SetMark {
+            self.mark_active = false;
+        }
+
+        // With the mark active, motions stretch the region from it
+        let action = match action {
+            KeyAction::MoveCursor(direction) if self.mark_active => KeyAction::SelectMove(direction),
+            KeyAction::MoveWord(direction) if self.mark_active => KeyAction::SelectWord(direction),
+            KeyAction::GoToLineStart if self.mark_active => KeyAction::SelectToLineStart,
+            KeyAction::GoToLineEnd if self.mark_active => KeyAction::SelectToLineEnd,
+            KeyAction::GoToDocumentStart if self.mark_active => KeyAction::SelectToDocumentStart,
+            KeyAction::GoToDocumentEnd if self.mark_active => KeyAction::SelectToDocumentEnd,
+            action => action,
+        };
+
+        // Plain cursor movement drops the selection; typing replaces it below
+        if is_motion && !self.mark_active {
+            self.text_selection = None;
+            self.selection_anchor = None;
+        }
+
+        match action {
+            KeyAction::Quit => self.quit(confirmed),
+
+            KeyAction::Save => {
                 if let Err(e) = self.save_file() {
                     let mut args = HashMap::new();
                     args.insert("error".to_string(), FluentValue::from(e.to_string()));
@@ -244,225 +2109,287 @@ impl InteractiveTextEditor {
                     self.status_message = i18n::t("status-file-saved");
                     self.modified = false;
                 }
-                self.mark_for_full_render(); // Save affects status bar
             }
-            
-            // Ctrl+O - Open file
-            (true, KeyCode::Char('o')) => {
+
+            KeyAction::Open => {
                 self.open_file_dialog();
-                self.mark_for_full_render(); // File open changes everything
-            }
-            
-            // Ctrl+H - Toggle syntax highlighting
-            (true, KeyCode::Char('h')) => {
-                self.syntax_enabled = !self.syntax_enabled;
-                self.status_message = if self.syntax_enabled { 
-                    i18n::t("status-syntax-enabled")
-                } else { 
-                    i18n::t("status-syntax-disabled")
-                };
-                if self.linter.is_enabled() {
-                    self.run_linting();
-                }
-                self.mark_for_full_render(); // Highlighting affects all content
-            }
-            
-            // Ctrl+E - Toggle linting
-            (true, KeyCode::Char('e')) => {
-                self.linter.toggle();
-                self.status_message = if self.linter.is_enabled() { 
-                    i18n::t("status-linting-enabled")
-                } else { 
-                    i18n::t("status-linting-disabled")
-                };
-                if self.linter.is_enabled() {
-                    self.run_linting();
-                } else {
-                    self.lint_issues.clear();
-                }
-                self.mark_for_full_render(); // Linting affects line indicators and status
-            }
-            
-            // Ctrl+N - New file
-            (true, KeyCode::Char('n')) => {
-                if self.modified {
-                    self.status_message = i18n::t("file-save-before-new");
-                } else {
-                    self.lines = vec!["".to_string()];
-                    self.cursor.set_position(0, 0);
-                    self.filename = None;
-                    self.modified = false;
-                    self.status_message = i18n::t("status-new-file-created");
-                }
             }
-            
-            // Ctrl+Home - Go to start of document
-            (true, KeyCode::Home) => {
-                self.cursor.move_to_document_start();
-                self.status_message = i18n::t("status-document-start");
+
+            KeyAction::NewFile => {
+                self.open_buffer(BufferState::default());
+                self.status_message = i18n::t("status-new-file-created");
             }
-            
-            // Ctrl+End - Go to end of document
-            (true, KeyCode::End) => {
-                self.cursor.move_to_document_end(&self.lines);
-                self.status_message = i18n::t("status-document-end");
+
+            KeyAction::NextBuffer => self.switch_buffer(self.buffers.next_index()),
+            KeyAction::PreviousBuffer => self.switch_buffer(self.buffers.prev_index()),
+            KeyAction::CloseBuffer => self.close_buffer(confirmed),
+
+            KeyAction::SplitHorizontal => self.split_pane(SplitDirection::Horizontal),
+            KeyAction::SplitVertical => self.split_pane(SplitDirection::Vertical),
+            KeyAction::ClosePane => self.close_pane(),
+            KeyAction::FocusPane(direction) => self.focus_pane_towards(direction),
+            KeyAction::FocusNextPane => self.focus_pane(self.layout.next_pane(self.focused_pane)),
+            KeyAction::ResizePane(direction) => self.resize_pane(direction),
+
+            // With several carets every edit goes through the multi-caret path
+            KeyAction::InsertChar(ch) if self.cursor.has_extra_carets() => {
+                self.edit_all_carets(cursor::CaretEdit::Insert(&ch.to_string()));
             }
-            
-            // Ctrl+L - Go to line
-            (true, KeyCode::Char('l')) => {
-                self.status_message = i18n::t("status-goto-line-soon");
+            KeyAction::InsertNewline if self.cursor.has_extra_carets() => {
+                self.edit_all_carets(cursor::CaretEdit::Insert("\n"));
             }
-            
-            // Ctrl+T - Toggle language
-            (true, KeyCode::Char('t')) => {
-                self.toggle_language();
-                self.mark_for_full_render(); // Language change affects all content
+            KeyAction::DeleteBackward if self.cursor.has_extra_carets() => {
+                self.edit_all_carets(cursor::CaretEdit::DeleteBackward);
             }
-            
-            // Ctrl+D - Duplicate line
-            (true, KeyCode::Char('d')) => {
-                let cursor_row = self.cursor.row();
-                if cursor_row < self.lines.len() {
-                    let line_to_duplicate = self.lines[cursor_row].clone();
-                    self.lines.insert(cursor_row + 1, line_to_duplicate);
-                    self.cursor.set_position(cursor_row + 1, self.cursor.col());
-                    self.modified = true;
-                    self.status_message = i18n::t("status-line-duplicated");
-                }
+            KeyAction::DeleteForward if self.cursor.has_extra_carets() => {
+                self.edit_all_carets(cursor::CaretEdit::DeleteForward);
             }
-            
-            // Ctrl+K - Delete line
-            (true, KeyCode::Char('k')) => {
-                let cursor_row = self.cursor.row();
-                if cursor_row < self.lines.len() && self.lines.len() > 1 {
-                    self.lines.remove(cursor_row);
-                    let new_row = if cursor_row >= self.lines.len() {
-                        self.lines.len() - 1
-                    } else {
-                        cursor_row
-                    };
-                    self.cursor.set_position(new_row, 0);
-                    self.modified = true;
-                    self.status_message = i18n::t("status-line-deleted");
-                } else if self.lines.len() == 1 {
-                    self.lines[0].clear();
-                    self.cursor.set_position(cursor_row, 0);
-                    self.modified = true;
-                    self.status_message = i18n::t("status-line-cleared");
-                }
-            }
-
-            // Regular character input
-            (false, KeyCode::Char(ch)) => {
-                if self.text_selection.is_some() {
-                    self.delete_selected_text();
-                }
+
+            KeyAction::InsertChar(ch) => {
+                // The insert refreshes lint and search for both edits
+                self.remove_selected_text();
                 self.insert_char(ch);
             }
-            
-            // Enter key
-            (false, KeyCode::Enter) => {
-                if self.text_selection.is_some() {
-                    self.delete_selected_text();
-                }
+
+            KeyAction::InsertNewline => {
+                // The insert refreshes lint and search for both edits
+                self.remove_selected_text();
                 self.insert_newline();
             }
-            
-            // Backspace
-            (false, KeyCode::Backspace) => {
+
+            KeyAction::DeleteBackward => {
                 if self.text_selection.is_some() {
                     self.delete_selected_text();
                 } else {
                     self.delete_char();
                 }
             }
-            
-            // Delete key
-            (false, KeyCode::Delete) => {
+
+            KeyAction::DeleteForward => {
                 if self.text_selection.is_some() {
                     self.delete_selected_text();
                 } else {
-                    // Delete character forward
-                    let cursor_row = self.cursor.row();
-                    let cursor_col = self.cursor.col();
-                    
-                    if cursor_row < self.lines.len() {
-                        let line = &mut self.lines[cursor_row];
-                        let mut chars: Vec<char> = line.chars().collect();
-                        if cursor_col < chars.len() {
-                            chars.remove(cursor_col);
-                            *line = chars.into_iter().collect();
-                            self.modified = true;
-                        } else if cursor_row < self.lines.len() - 1 {
-                            // Join with next line
-                            let next_line = self.lines.remove(cursor_row + 1);
-                            self.lines[cursor_row].push_str(&next_line);
-                            self.modified = true;
-                        }
-                    }
+                    self.delete_char_forward();
                 }
             }
-            
-            // Home - Go to start of line
-            (false, KeyCode::Home) => {
+
+            KeyAction::MoveCursor(direction) => self.move_cursor(direction),
+
+            KeyAction::GoToLineStart => {
                 self.cursor.move_to_line_start();
+                self.cursor.move_extra_carets(|position| position.col = 0);
+                self.undo_stack.seal();
             }
-            
-            // End - Go to end of line
-            (false, KeyCode::End) => {
-                self.cursor.move_to_line_end(&self.lines);
+
+            KeyAction::GoToLineEnd => {
+                self.cursor.move_to_line_end(&self.buffer);
+                let buffer = &self.buffer;
+                self.cursor.move_extra_carets(|position| position.col = buffer.line_len(position.row));
+                self.undo_stack.seal();
             }
 
-            // Movement commands
-            (false, KeyCode::Up) => self.move_cursor(Direction::Up),
-            (false, KeyCode::Down) => self.move_cursor(Direction::Down),
-            (false, KeyCode::Left) => self.move_cursor(Direction::Left),
-            (false, KeyCode::Right) => self.move_cursor(Direction::Right),
+            KeyAction::GoToDocumentStart => {
+                self.cursor.move_to_document_start();
+                self.cursor.clear_extra_carets();
+                self.undo_stack.seal();
+                self.status_message = i18n::t("status-document-start");
+            }
 
-            _ => {}
+            KeyAction::GoToDocumentEnd => {
+                self.cursor.move_to_document_end(&self.buffer);
+                self.cursor.clear_extra_carets();
+                self.undo_stack.seal();
+                self.status_message = i18n::t("status-document-end");
+            }
+
+            KeyAction::MoveWord(direction) => self.move_word(direction),
+
+            KeyAction::ForwardWord | KeyAction::BackwardWord => {
+                let forward = action == KeyAction::ForwardWord;
+                if self.mark_active {
+                    self.extend_selection(|editor| editor.emacs_word(forward));
+                } else {
+                    self.emacs_word(forward);
+                }
+            }
+
+            KeyAction::SelectMove(direction) => {
+                self.extend_selection(|editor| editor.step_cursor(direction));
+            }
+            KeyAction::SelectWord(direction) => {
+                self.extend_selection(|editor| editor.move_word(direction));
+            }
+            KeyAction::SelectToLineStart => {
+                self.extend_selection(|editor| editor.cursor.move_to_line_start());
+            }
+            KeyAction::SelectToLineEnd => {
+                self.extend_selection(|editor| editor.cursor.move_to_line_end(&editor.buffer));
+            }
+            KeyAction::SelectToDocumentStart => {
+                self.extend_selection(|editor| editor.cursor.move_to_document_start());
+            }
+            KeyAction::SelectToDocumentEnd => {
+                self.extend_selection(|editor| editor.cursor.move_to_document_end(&editor.buffer));
+            }
+            KeyAction::SelectAll => self.select_all(),
+
+            KeyAction::AddCaretAbove => self.add_caret_vertical(true),
+            KeyAction::AddCaretBelow => self.add_caret_vertical(false),
+            KeyAction::AddNextOccurrence => self.add_next_occurrence(),
+
+            KeyAction::DuplicateLine => self.duplicate_line(),
+            KeyAction::DeleteLine => self.delete_line(),
+
+            KeyAction::Undo => self.undo(),
+            KeyAction::Redo => self.redo(),
+
+            KeyAction::Copy => {
+                self.copy_selection();
+            }
+            KeyAction::Cut => self.cut_selection(),
+            KeyAction::Paste => self.paste_from_clipboard(),
+
+            KeyAction::SetMark => self.set_mark(),
+            KeyAction::KillRegion => self.kill_region(true),
+            KeyAction::CopyRegion => self.kill_region(false),
+            KeyAction::Yank => self.yank(),
+            KeyAction::YankPop => self.yank_pop(last_yank),
+
+            KeyAction::Find => self.open_search_prompt(false, false),
+            KeyAction::FindBackward => self.open_search_prompt(false, true),
+            KeyAction::Replace => self.open_search_prompt(true, false),
+            KeyAction::FindNext => self.find_next(false),
+            KeyAction::FindPrevious => self.find_next(true),
+
+            KeyAction::ToggleSyntaxHighlighting => {
+                self.syntax_enabled = !self.syntax_enabled;
+                self.status_message = if self.syntax_enabled { 
+                    i18n::t("status-syntax-enabled")
+                } else { 
+                    i18n::t("status-syntax-disabled")
+                };
+                if self.linter.is_enabled() {
+                    self.run_linting();
+                }
+            }
+
+            KeyAction::ToggleLinting => {
+                self.linter.toggle();
+                self.status_message = if self.linter.is_enabled() { 
+                    i18n::t("status-linting-enabled")
+                } else { 
+                    i18n::t("status-linting-disabled")
+                };
+                self.lint_all_buffers();
+            }
+
+            KeyAction::ToggleSoftWrap => {
+                self.soft_wrap = !self.soft_wrap;
+                self.status_message = if self.soft_wrap {
+                    i18n::t("status-soft-wrap-enabled")
+                } else {
+                    i18n::t("status-soft-wrap-disabled")
+                };
+                self.scroll_to_cursor();
+            }
+
+            KeyAction::GoToLine => self.open_goto_prompt(),
+            KeyAction::PickTheme => self.open_theme_picker(),
+            KeyAction::SetLanguage => self.open_language_picker(),
+            KeyAction::QuickFix => self.open_quick_fix_menu(),
+            KeyAction::FixAll => self.fix_all(),
+
+            KeyAction::ToggleLanguage => {
+                self.toggle_language();
+            }
+
+            KeyAction::ToggleVimMode => {
+                self.set_vim_enabled(self.vim.is_none());
+            }
+
+            KeyAction::Cancel => {
+                if self.cursor.has_extra_carets() {
+                    // Collapse to a single caret
+                    self.clear_extra_carets();
+                    self.status_message.clear();
+                } else if self.search_active {
+                    // Hide search highlights
+                    self.search_active = false;
+                    self.status_message.clear();
+                } else if self.text_selection.is_some() || self.selection_anchor.is_some() {
+                    // Drop the selection or the Emacs region
+                    self.text_selection = None;
+                    self.selection_anchor = None;
+                    self.status_message.clear();
+                }
+            }
+
+            KeyAction::None => {}
         }
     }
 
     fn handle_mouse_event(&mut self, mouse_event: MouseEvent) {
+        self.mouse_controller.set_panes(self.pane_rects());
         let action = self.mouse_controller.handle_mouse_event(mouse_event);
+        // Clicks and the wheel act on the pane under the pointer, focusing it first
+        if let Some(pane) = self.mouse_controller.target_pane()
+            && action != mouse::MouseAction::None
+        {
+            self.focus_pane(pane);
+        }
+        // Mouse selections are not anchored like keyboard ones; scrolling keeps the mark
+        let is_scroll = matches!(
+            action,
+            mouse::MouseAction::ScrollUp | mouse::MouseAction::ScrollDown | mouse::MouseAction::ScrollLeft | mouse::MouseAction::ScrollRight
+        );
+        if !is_scroll && !matches!(action, mouse::MouseAction::None) {
+            self.selection_anchor = None;
+            self.mark_active = false;
+        }
         
         match action {
             mouse::MouseAction::Click { row, col } => {
                 self.move_cursor_to_position(row, col);
                 self.text_selection = None;
+                self.cursor.clear_extra_carets();
+                if let Some(vim) = self.vim.as_mut().filter(|vim| vim.mode() != vim::VimMode::Insert) {
+                    vim.reset();
+                }
                 
                 let mut args = HashMap::new();
                 args.insert("row".to_string(), FluentValue::from(row + 1));
                 args.insert("col".to_string(), FluentValue::from(col + 1));
                 self.status_message = i18n::t_with_args("status-cursor-moved", args);
                 
-                self.mark_for_full_render(); // Click needs full render for cursor position
             }
             
             mouse::MouseAction::DoubleClick { row, col } => {
                 self.select_word_at_position(row, col);
                 self.status_message = i18n::t("status-word-selected");
-                self.mark_for_full_render(); // Selection needs full render
             }
             
             mouse::MouseAction::DragEnd { start_row, start_col, end_row, end_col } => {
-                self.text_selection = Some(mouse::TextSelection::new(start_row, start_col, end_row, end_col));
+                let (start_row, start_col) = self.screen_to_buffer(start_row, start_col);
                 self.move_cursor_to_position(end_row, end_col);
+                let (end_row, end_col) = (self.cursor.row(), self.cursor.col());
+                self.text_selection = Some(mouse::TextSelection::new(start_row, start_col, end_row, end_col));
                 if let Some(ref selection) = self.text_selection {
-                    let selected_text = selection.get_selected_text(&self.lines);
+                    let selected_text = selection.get_selected_text(&self.buffer);
                     let char_count = selected_text.chars().count();
                     
                     let mut args = HashMap::new();
                     args.insert("count".to_string(), FluentValue::from(char_count));
                     self.status_message = i18n::t_with_args("status-text-selected", args);
                 }
-                self.mark_for_full_render(); // Selection end needs full render
+            }
+            
+            mouse::MouseAction::BlockSelect { start_row, start_col, end_row, end_col } => {
+                self.select_block(start_row, start_col, end_row, end_col);
             }
             
             mouse::MouseAction::RightClick { row, col } => {
                 self.move_cursor_to_position(row, col);
                 self.show_context_menu(row, col);
-                self.mark_for_full_render(); // Context menu needs full render
             }
             
             mouse::MouseAction::ScrollUp => {
@@ -470,38 +2397,59 @@ impl InteractiveTextEditor {
                     self.scroll_offset = self.scroll_offset.saturating_sub(3);
                 }
                 self.status_message = i18n::t("status-scrolled-up");
-                self.mark_for_full_render(); // Scroll needs full render for content change
             }
             
             mouse::MouseAction::ScrollDown => {
-                let max_scroll = self.lines.len().saturating_sub(10);
+                let max_scroll = self.buffer.len_lines().saturating_sub(10);
                 if self.scroll_offset < max_scroll {
                     self.scroll_offset = (self.scroll_offset + 3).min(max_scroll);
                 }
                 self.status_message = i18n::t("status-scrolled-down");
-                self.mark_for_full_render(); // Scroll needs full render for content change
+            }
+            
+            // Soft-wrapped lines never extend past the right edge
+            mouse::MouseAction::ScrollLeft | mouse::MouseAction::ScrollRight if self.soft_wrap => {}
+            
+            mouse::MouseAction::ScrollLeft => {
+                self.scroll_offset_x = self.scroll_offset_x.saturating_sub(6);
+                self.status_message = i18n::t("status-scrolled-left");
+            }
+            
+            mouse::MouseAction::ScrollRight => {
+                // Stop once the longest visible line has scrolled into view
+                let (width, height) = self.viewport_size();
+                let last_row = (self.scroll_offset + height).min(self.buffer.len_lines());
+                let longest = (self.scroll_offset..last_row)
+                    .map(|row| text_metrics::str_width(&self.buffer.line(row)))
+                    .max()
+                    .unwrap_or(0);
+                let max_scroll = (longest + 1).saturating_sub(width);
+                self.scroll_offset_x = (self.scroll_offset_x + 6).min(max_scroll.max(self.scroll_offset_x));
+                self.status_message = i18n::t("status-scrolled-right");
             }
             
             mouse::MouseAction::Drag { start_row: _, start_col: _, current_row, current_col } => {
                 self.move_cursor_to_position(current_row, current_col);
                 self.status_message = i18n::t("status-selecting");
-                // Don't mark for full render during drag - use throttled rendering instead
             }
             
             mouse::MouseAction::None => {}
         }
     }
 
+    /// Move the cursor to the buffer position under a screen cell
     fn move_cursor_to_position(&mut self, row: usize, col: usize) {
-        self.cursor.move_to_position(row, col, self.scroll_offset, &self.lines);
+        let (row, col) = self.screen_to_buffer(row, col);
+        self.cursor.set_position(row, col);
+        self.undo_stack.seal();
     }
 
     fn select_word_at_position(&mut self, row: usize, col: usize) {
-        let actual_row = row + self.scroll_offset;
+        let (actual_row, col) = self.screen_to_buffer(row, col);
         
-        if actual_row < self.lines.len() {
-            let line = &self.lines[actual_row];
-            let (start_col, end_col) = mouse::find_word_boundaries(line, col.min(line.len()));
+        if actual_row < self.buffer.len_lines() {
+            let line = self.buffer.line(actual_row);
+            let (start_col, end_col) = mouse::find_word_boundaries(&line, col);
             
             self.text_selection = Some(mouse::TextSelection::new(
                 actual_row, start_col, actual_row, end_col
@@ -512,14 +2460,14 @@ impl InteractiveTextEditor {
     }
 
     fn show_context_menu(&mut self, row: usize, col: usize) {
-        let actual_row = row + self.scroll_offset;
+        let (actual_row, col) = self.screen_to_buffer(row, col);
         
         let mut args = HashMap::new();
         args.insert("row".to_string(), FluentValue::from(actual_row + 1));
         args.insert("col".to_string(), FluentValue::from(col + 1));
         
         if let Some(ref selection) = self.text_selection {
-            let selected_text = selection.get_selected_text(&self.lines);
+            let selected_text = selection.get_selected_text(&self.buffer);
             if !selected_text.is_empty() {
                 self.status_message = i18n::t_with_args("context-menu-copy-cut-paste", args);
             } else {
@@ -531,145 +2479,223 @@ impl InteractiveTextEditor {
     }
 
     fn delete_selected_text(&mut self) {
-        if let Some(selection) = &self.text_selection {
-            let start_row = selection.start_row;
-            let start_col = selection.start_col;
-            let end_row = selection.end_row;
-            let end_col = selection.end_col;
-
-            if start_row == end_row {
-                // Single line selection
-                let line = &mut self.lines[start_row];
-                let mut chars: Vec<char> = line.chars().collect();
-                for _ in start_col..end_col.min(chars.len()) {
-                    if start_col < chars.len() {
-                        chars.remove(start_col);
-                    }
-                }
-                *line = chars.into_iter().collect();
-            } else {
-                // Multi-line selection
-                let start_line_part = if start_col < self.lines[start_row].len() {
-                    self.lines[start_row][..start_col].to_string()
-                } else {
-                    self.lines[start_row].clone()
-                };
-                
-                let end_line_part = if end_col < self.lines[end_row].len() {
-                    self.lines[end_row][end_col..].to_string()
-                } else {
-                    String::new()
-                };
-                
-                // Remove the lines in between
-                for _ in start_row..end_row {
-                    if start_row + 1 < self.lines.len() {
-                        self.lines.remove(start_row + 1);
-                    }
-                }
-                
-                // Combine the remaining parts
-                self.lines[start_row] = start_line_part + &end_line_part;
-            }
-
-            self.cursor.set_position(start_row, start_col);
-            self.text_selection = None;
-            self.modified = true;
+        if self.remove_selected_text() {
+            self.buffer_changed();
         }
     }
 
+    /// Delete the selection and record it in the undo history; false if nothing was selected
+    ///
+    /// Lint and search results are left for the caller to refresh with
+    /// [`Self::buffer_changed`] once its other edits are done.
+    fn remove_selected_text(&mut self) -> bool {
+        let Some(selection) = &self.text_selection else {
+            return false;
+        };
+        let before = self.cursor_state();
+        let start = self.buffer.pos_to_char(selection.start_row, selection.start_col);
+        let end = self.buffer.pos_to_char(selection.end_row, selection.end_col);
+        let edit = undo::Edit::delete(start, self.buffer.slice(start, end));
+        edit.apply(&mut self.buffer);
+
+        let (row, col) = self.buffer.char_to_pos(start);
+        self.cursor.set_position(row, col);
+        self.text_selection = None;
+        self.undo_stack.record(edit, before, self.cursor_state());
+        true
+    }
+
     fn run_linting(&mut self) {
-        // Update content from lines
-        self.content = self.lines.join("\n");
-        
-        // Run linting with new API
-        self.lint_issues = self.linter.lint(&self.content, self.filename.as_deref());
+        self.lint_due = None;
+        let language = self.lint_language.get_or_insert_with(|| {
+            self.syntax_highlighter.syntax_name_for(self.language.as_deref(), self.filename.as_deref(), &self.buffer)
+        });
+        // Linting still needs contiguous text, so materialize it from the rope
+        let content = self.buffer.to_string();
+        self.lint_issues = self.linter.lint(&content, language, self.filename.as_deref());
     }
 
-    /// Throttled render that prevents excessive screen updates during mouse events
-    fn render_throttled(&mut self) -> io::Result<()> {
-        let now = std::time::Instant::now();
-        let elapsed = now.duration_since(self.last_render_time).as_millis() as u64;
-        
-        // Only render if enough time has passed or if we need a full render
-        if elapsed >= self.render_throttle_ms || self.needs_full_render {
-            self.render()?;
-            self.last_render_time = now;
-            self.needs_full_render = false;
+    /// Lint the active buffer once editing has paused for [`LINT_DELAY`]
+    fn schedule_lint(&mut self) {
+        if self.linter.is_enabled() {
+            self.lint_due = Some(Instant::now() + LINT_DELAY);
+        }
+    }
+
+    /// Run a scheduled lint now, along with parked buffers another pane shows
+    fn run_pending_lint(&mut self) {
+        if self.lint_due.is_some() {
+            self.run_linting();
+        }
+        let shown: Vec<usize> = self
+            .pane_views
+            .values()
+            .filter_map(|view| self.buffers.index_of(view.buffer))
+            .collect();
+        let (linter, highlighter, active) = (&mut self.linter, &self.syntax_highlighter, self.buffers.active());
+        for idx in shown {
+            let Some(state) = self.buffers.get_mut(idx).filter(|state| idx != active && state.lint_stale) else {
+                continue;
+            };
+            state.lint_stale = false;
+            let language = state.lint_language.get_or_insert_with(|| {
+                highlighter.syntax_name_for(state.language.as_deref(), state.filename.as_deref(), &state.buffer)
+            });
+            state.lint_issues = linter.lint(&state.buffer.to_string(), language, state.filename.as_deref());
+        }
+    }
+
+    /// Lint the active buffer again and mark the others to be linted when next shown,
+    /// or clear their issues when linting is off
+    fn lint_all_buffers(&mut self) {
+        let enabled = self.linter.is_enabled();
+        for state in self.buffers.parked_mut() {
+            state.lint_stale = enabled;
+            if !enabled {
+                state.lint_issues.clear();
+            }
+        }
+        if enabled {
+            self.run_linting();
+            self.run_pending_lint();
+        } else {
+            self.lint_due = None;
+            self.lint_issues.clear();
         }
-        
-        Ok(())
     }
 
-    /// Force a full render on the next update
-    fn mark_for_full_render(&mut self) {
-        self.needs_full_render = true;
+    /// Highlight what each pane shows, picking up where the last frame left off
+    fn update_highlights(&mut self, area: Rect) {
+        if !self.syntax_enabled {
+            return;
+        }
+        let active = self.buffers.active();
+        for (pane, rect) in self.layout.rects(area) {
+            let (idx, scroll_offset) = match self.pane_views.get(&pane) {
+                Some(PaneView { buffer, view }) if pane != self.focused_pane => {
+                    (self.buffers.index_of(*buffer).unwrap_or(active), view.scroll_offset)
+                }
+                _ => (active, self.scroll_offset),
+            };
+            let (buffer, highlights, filename, language) = match self.buffers.get_mut(idx) {
+                Some(state) if idx != active => (&mut state.buffer, &mut state.highlights, state.filename.as_deref(), state.language.as_deref()),
+                _ => (&mut self.buffer, &mut self.highlights, self.filename.as_deref(), self.language.as_deref()),
+            };
+            let syntax_name = self.syntax_highlighter.syntax_name_for(language, filename, buffer);
+            highlights.sync(buffer);
+            let rows = scroll_offset + render::visible_lines(rect.height);
+            self.syntax_highlighter.highlight_buffer(highlights, buffer, &syntax_name, rows);
+        }
     }
 
-    fn render(&self) -> io::Result<()> {
-        self.renderer.render_editor(
-            &self.lines,
-            self.cursor.row(),
-            self.cursor.col(),
-            self.filename.as_deref(),
-            self.modified,
-            &self.status_message,
-            &self.syntax_highlighter,
-            self.syntax_enabled,
-            self.scroll_offset,
-            &self.lint_issues,
-            &self.linter,
-            self.text_selection.as_ref(),
-        )
+    /// Draw every pane into a new frame and send what changed to the terminal
+    fn render(&mut self) -> io::Result<()> {
+        let titles: Vec<(String, bool)> = (0..self.buffers.len())
+            .map(|idx| match self.buffers.get(idx) {
+                Some(state) if idx != self.buffers.active() => (
+                    state.filename.as_deref().map(buffer_list::tab_title).map(str::to_string).unwrap_or_else(|| i18n::t("ui-no-file")),
+                    state.modified,
+                ),
+                _ => (self.buffer_title(), self.modified),
+            })
+            .collect();
+        let tabs: Vec<render::Tab> = titles.iter().map(|(title, modified)| render::Tab { title, modified: *modified }).collect();
+
+        let area = render::editor_area()?;
+        self.update_highlights(area);
+        let (width, height) = crossterm::terminal::size()?;
+        let mut frame = render::Frame::new(width as usize, height as usize);
+        let context = render::RenderContext {
+            tabs: &tabs,
+            syntax_highlighter: &self.syntax_highlighter,
+            syntax_enabled: self.syntax_enabled,
+            linter: &self.linter,
+            soft_wrap: self.soft_wrap,
+        };
+        let active = self.buffers.active();
+        for (pane, rect) in self.layout.rects(area) {
+            let focused = pane == self.focused_pane;
+            let (idx, mut view) = if focused {
+                (active, self.current_view())
+            } else {
+                let Some(PaneView { buffer, view }) = self.pane_views.get(&pane) else {
+                    continue;
+                };
+                // The pane may show the active buffer or a parked one
+                (self.buffers.index_of(*buffer).unwrap_or(active), view.clone())
+            };
+            let (buffer, filename, language, modified, lint_issues, highlights) = match self.buffers.get(idx) {
+                Some(state) if idx != active => (&state.buffer, state.filename.as_deref(), state.language.as_deref(), state.modified, &state.lint_issues, &state.highlights),
+                _ => (&self.buffer, self.filename.as_deref(), self.language.as_deref(), self.modified, &self.lint_issues, &self.highlights),
+            };
+            view.clamp(buffer);
+            let content = render::PaneContent {
+                buffer,
+                view: &view,
+                filename,
+                language,
+                modified,
+                lint_issues,
+                highlights,
+                tab: idx,
+                status_message: if focused { &self.status_message } else { "" },
+                search: (focused && self.search_active).then_some(&self.search),
+                focused,
+            };
+            self.renderer.render_editor(&mut frame, &context, &content, rect)?;
+        }
+        self.renderer.render_separators(&mut frame, &self.layout.separators(area))?;
+        self.renderer.present(frame)
     }
 
     fn run(&mut self) -> io::Result<()> {
         enable_raw_mode()?;
-        execute!(stdout(), EnableMouseCapture, Clear(ClearType::All))?;
+        execute!(stdout(), EnableMouseCapture, EnableBracketedPaste, Clear(ClearType::All))?;
 
         // Initial render
         self.render()?;
 
         while !self.quit {
+            // Lint once typing pauses rather than after every key
+            if let Some(due) = self.lint_due
+                && !poll(due.saturating_duration_since(Instant::now()))?
+            {
+                self.run_pending_lint();
+                self.show_issue_at_cursor();
+                self.render()?;
+                continue;
+            }
             match read()? {
                 Event::Key(key_event) => {
-                    // Handle Ctrl+C to quit
-                    if key_event.modifiers.contains(KeyModifiers::CONTROL) && key_event.code == KeyCode::Char('c') {
-                        break;
-                    }
                     self.handle_key_event(key_event);
-                    // Use optimized rendering for better performance
-                    if self.needs_full_render {
-                        self.render()?;
-                        self.last_render_time = std::time::Instant::now();
-                        self.needs_full_render = false;
-                    } else {
-                        self.render_throttled()?;
-                    }
+                    self.show_issue_at_cursor();
+                    self.render()?;
                 }
                 Event::Mouse(mouse_event) => {
                     self.handle_mouse_event(mouse_event);
-                    // Use throttled rendering for mouse events to prevent flickering
-                    self.render_throttled()?;
+                    self.show_issue_at_cursor();
+                    self.render()?;
                 }
+                Event::Paste(text) => {
+                    // Bracketed paste from the terminal arrives as one event
+                    self.paste_text(&text);
+                    self.render()?;
+                }
+                Event::Resize(_, _) => self.render()?,
                 _ => {}
             }
         }
 
-        execute!(stdout(), DisableMouseCapture)?;
+        execute!(stdout(), DisableMouseCapture, DisableBracketedPaste)?;
         disable_raw_mode()?;
         execute!(stdout(), Show, Clear(ClearType::All), MoveTo(0, 0))?;
         Ok(())
     }
 
     fn open_file_dialog(&mut self) {
-        if self.modified {
-            self.status_message = i18n::t("file-save-before-open");
-            return;
-        }
-
-        // Clear the screen for the dialog
+        // Clear the screen for the dialog; the editor is redrawn in full afterwards
         execute!(stdout(), Clear(ClearType::All), MoveTo(0, 0)).ok();
+        self.renderer.invalidate();
         
         // Show IOCraft file browser dialog with enhanced UI
         println!("┌─────────────────────────────────────────────────────────────┐");
@@ -711,7 +2737,7 @@ impl InteractiveTextEditor {
                         stdout().flush().ok();
                         
                         if let Some(filename) = self.read_filename_input() {
-                            self.process_file_open(&filename.trim());
+                            self.process_file_open(filename.trim());
                         }
                     }
                     "2" => {
@@ -725,7 +2751,7 @@ impl InteractiveTextEditor {
                         if let Some(filename) = self.read_filename_input() {
                             let filename = filename.trim();
                             if !filename.is_empty() {
-                                self.create_new_file_interactive(&filename);
+                                self.create_new_file_interactive(filename);
                             }
                         }
                     }
@@ -788,15 +2814,14 @@ impl InteractiveTextEditor {
         stdout().flush().ok();
         
         if let Some(filename) = self.read_filename_input() {
-            self.process_file_open(&filename.trim());
+            self.process_file_open(filename.trim());
         }
     }
 
     fn create_new_file_interactive(&mut self, filename: &str) {
-        self.lines = vec!["".to_string()];
-        self.cursor.set_position(0, 0);
-        self.filename = Some(filename.to_string());
+        self.open_buffer(BufferState::new(TextBuffer::new(), Some(filename.to_string())));
         self.modified = true; // Mark as modified since it's new
+        self.saved_revision = None;
         
         let mut args = HashMap::new();
         args.insert("filename".to_string(), FluentValue::from(filename));
@@ -836,10 +2861,7 @@ impl InteractiveTextEditor {
         if let Some(response) = self.read_filename_input() {
             if response.to_lowercase().starts_with('y') || response.to_lowercase().starts_with('s') || response.to_lowercase().starts_with('j') {
                 // Create new file (yes in English, sí in Spanish, ja in German, oui in French)
-                self.lines = vec!["".to_string()];
-                self.cursor.set_position(0, 0);
-                self.filename = Some(filename.to_string());
-                self.modified = false;
+                self.open_buffer(BufferState::new(TextBuffer::new(), Some(filename.to_string())));
                 
                 let mut args = HashMap::new();
                 args.insert("filename".to_string(), FluentValue::from(filename));
@@ -893,24 +2915,29 @@ impl InteractiveTextEditor {
 }
 
 fn main() -> io::Result<()> {
-    let args: Vec<String> = std::env::args().collect();
-    let filename = if args.len() > 1 {
-        Some(args[1].clone())
-    } else {
-        None
-    };
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let profile = args.iter().rev().find_map(|arg| match arg.as_str() {
+        "--vim" => Some(KeymapProfile::Vim),
+        "--emacs" => Some(KeymapProfile::Emacs),
+        _ => None,
+    });
+    let filenames: Vec<String> = args.into_iter().filter(|arg| !arg.starts_with("--")).collect();
 
     println!("{}", i18n::t("app-startup"));
-    if let Some(ref name) = filename {
+    for name in &filenames {
         let mut args = HashMap::new();
         args.insert("filename".to_string(), FluentValue::from(name.as_str()));
         println!("{}", i18n::t_with_args("file-opening", args));
-    } else {
+    }
+    if filenames.is_empty() {
         println!("{}", i18n::t("file-new"));
     }
     println!("{}\n", i18n::t("exit-prompt"));
 
-    let mut editor = InteractiveTextEditor::new(filename)?;
+    let mut editor = InteractiveTextEditor::new(filenames)?;
+    if let Some(profile) = profile {
+        editor.set_keymap_profile(profile);
+    }
     editor.run()?;
 
     println!("{}", i18n::t("app-thanks"));
@@ -1,6 +1,7 @@
 use std::io;
 use std::path::Path;
 use crate::file_io::FileIO;
+use crate::buffer::TextBuffer;
 use crate::iocraft_components::{loading_message, success_message, error_message, file_info_message, browser_header_message, recent_files_header_message};
 
 /// IOCraft-powered file I/O handler with beautiful UI components
@@ -68,6 +69,48 @@ impl IOCraftFileHandler {
         }
     }
 
+    /// Load a file into a text buffer with IOCraft UI feedback
+    pub fn load_buffer(&mut self, filename: &str) -> io::Result<TextBuffer> {
+        self.display_loading_message("Loading file", filename);
+        
+        match FileIO::load_buffer(filename) {
+            Ok(buffer) => {
+                self.current_file = Some(filename.to_string());
+                self.last_operation = format!("Loaded: {}", filename);
+                self.operation_success = true;
+                self.display_success_message("File loaded successfully", filename, buffer.len_lines());
+                Ok(buffer)
+            }
+            Err(e) => {
+                self.last_operation = format!("Error loading {}: {}", filename, e);
+                self.operation_success = false;
+                self.display_error_message("Failed to load file", filename, &e.to_string());
+                Err(e)
+            }
+        }
+    }
+
+    /// Save a text buffer with IOCraft UI feedback
+    pub fn save_buffer(&mut self, filename: &str, buffer: &TextBuffer) -> io::Result<()> {
+        self.display_loading_message("Saving file", filename);
+        
+        match FileIO::save_buffer(filename, buffer) {
+            Ok(()) => {
+                self.current_file = Some(filename.to_string());
+                self.last_operation = format!("Saved: {}", filename);
+                self.operation_success = true;
+                self.display_success_message("File saved successfully", filename, buffer.len_lines());
+                Ok(())
+            }
+            Err(e) => {
+                self.last_operation = format!("Error saving {}: {}", filename, e);
+                self.operation_success = false;
+                self.display_error_message("Failed to save file", filename, &e.to_string());
+                Err(e)
+            }
+        }
+    }
+
     /// Create a new file with IOCraft UI
     pub fn create_new_file(&mut self, filename: &str) -> io::Result<()> {
         self.display_loading_message("Creating new file", filename);
@@ -86,14 +129,14 @@ impl IOCraftFileHandler {
     }
 
     /// Display a beautiful file info panel
-    pub fn display_file_info(&self, filename: &str, lines: &[String]) {
+    pub fn display_file_info(&self, filename: &str, buffer: &TextBuffer) {
         let path = Path::new(filename);
         let extension = path.extension()
             .and_then(|ext| ext.to_str())
             .unwrap_or("txt");
         let status = if self.operation_success { "✅ OK" } else { "❌ Error" };
         
-        println!("{}", file_info_message(filename, extension, lines.len(), status));
+        println!("{}", file_info_message(filename, extension, buffer.len_lines(), status));
     }
 
     /// Display a loading message with IOCraft styling
@@ -0,0 +1,677 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use crate::config;
+use crate::keys::{Direction, KeyAction};
+
+/// Keymap files looked up in the configuration directory, in order
+const KEYMAP_FILES: &[&str] = &["keymap.toml", "keymap.json"];
+
+/// Built-in bindings; user keymap files are applied on top of these
+const DEFAULT_BINDINGS: &[(&str, KeyAction)] = &[
+    // File operations
+    ("ctrl+q", KeyAction::Quit),
+    ("ctrl+s", KeyAction::Save),
+    ("ctrl+o", KeyAction::Open),
+    ("ctrl+n", KeyAction::NewFile),
+    // Buffers
+    ("ctrl+tab", KeyAction::NextBuffer),
+    ("ctrl+pagedown", KeyAction::NextBuffer),
+    ("ctrl+shift+tab", KeyAction::PreviousBuffer),
+    ("ctrl+pageup", KeyAction::PreviousBuffer),
+    ("ctrl+w", KeyAction::CloseBuffer),
+    // Panes
+    ("alt+-", KeyAction::SplitHorizontal),
+    ("alt+\\", KeyAction::SplitVertical),
+    ("alt+w", KeyAction::ClosePane),
+    ("alt+o", KeyAction::FocusNextPane),
+    ("alt+up", KeyAction::FocusPane(Direction::Up)),
+    ("alt+down", KeyAction::FocusPane(Direction::Down)),
+    ("alt+left", KeyAction::FocusPane(Direction::Left)),
+    ("alt+right", KeyAction::FocusPane(Direction::Right)),
+    ("alt+shift+up", KeyAction::ResizePane(Direction::Up)),
+    ("alt+shift+down", KeyAction::ResizePane(Direction::Down)),
+    ("alt+shift+left", KeyAction::ResizePane(Direction::Left)),
+    ("alt+shift+right", KeyAction::ResizePane(Direction::Right)),
+    // Editing
+    ("enter", KeyAction::InsertNewline),
+    ("backspace", KeyAction::DeleteBackward),
+    ("delete", KeyAction::DeleteForward),
+    ("ctrl+d", KeyAction::DuplicateLine),
+    ("ctrl+k", KeyAction::DeleteLine),
+    // Navigation
+    ("up", KeyAction::MoveCursor(Direction::Up)),
+    ("down", KeyAction::MoveCursor(Direction::Down)),
+    ("left", KeyAction::MoveCursor(Direction::Left)),
+    ("right", KeyAction::MoveCursor(Direction::Right)),
+    ("home", KeyAction::GoToLineStart),
+    ("end", KeyAction::GoToLineEnd),
+    ("ctrl+home", KeyAction::GoToDocumentStart),
+    ("ctrl+end", KeyAction::GoToDocumentEnd),
+    ("ctrl+left", KeyAction::MoveWord(Direction::Left)),
+    ("ctrl+right", KeyAction::MoveWord(Direction::Right)),
+    ("ctrl+l", KeyAction::GoToLine),
+    // Selection
+    ("shift+up", KeyAction::SelectMove(Direction::Up)),
+    ("shift+down", KeyAction::SelectMove(Direction::Down)),
+    ("shift+left", KeyAction::SelectMove(Direction::Left)),
+    ("shift+right", KeyAction::SelectMove(Direction::Right)),
+    ("ctrl+shift+left", KeyAction::SelectWord(Direction::Left)),
+    ("ctrl+shift+right", KeyAction::SelectWord(Direction::Right)),
+    ("shift+home", KeyAction::SelectToLineStart),
+    ("shift+end", KeyAction::SelectToLineEnd),
+    ("ctrl+shift+home", KeyAction::SelectToDocumentStart),
+    ("ctrl+shift+end", KeyAction::SelectToDocumentEnd),
+    ("ctrl+a", KeyAction::SelectAll),
+    // Multiple cursors
+    ("ctrl+alt+up", KeyAction::AddCaretAbove),
+    ("ctrl+alt+down", KeyAction::AddCaretBelow),
+    ("ctrl+alt+d", KeyAction::AddNextOccurrence),
+    // History and clipboard
+    ("ctrl+z", KeyAction::Undo),
+    ("ctrl+y", KeyAction::Redo),
+    ("ctrl+c", KeyAction::Copy),
+    ("ctrl+x", KeyAction::Cut),
+    ("ctrl+v", KeyAction::Paste),
+    // Search
+    ("ctrl+f", KeyAction::Find),
+    ("ctrl+r", KeyAction::Replace),
+    ("f3", KeyAction::FindNext),
+    ("shift+f3", KeyAction::FindPrevious),
+    // View
+    ("ctrl+h", KeyAction::ToggleSyntaxHighlighting),
+    ("ctrl+e", KeyAction::ToggleLinting),
+    ("alt+z", KeyAction::ToggleSoftWrap),
+    ("ctrl+t", KeyAction::ToggleLanguage),
+    ("alt+t", KeyAction::PickTheme),
+    ("alt+l", KeyAction::SetLanguage),
+    // Lint fixes
+    ("alt+enter", KeyAction::QuickFix),
+    ("alt+shift+f", KeyAction::FixAll),
+    ("esc", KeyAction::Cancel),
+];
+
+/// Bindings the Emacs profile layers over the defaults
+const EMACS_BINDINGS: &[(&str, KeyAction)] = &[
+    // Files and buffers
+    ("ctrl+x ctrl+s", KeyAction::Save),
+    ("ctrl+x ctrl+f", KeyAction::Open),
+    ("ctrl+x ctrl+c", KeyAction::Quit),
+    ("ctrl+x h", KeyAction::SelectAll),
+    ("ctrl+x u", KeyAction::Undo),
+    ("ctrl+x k", KeyAction::CloseBuffer),
+    ("ctrl+x right", KeyAction::NextBuffer),
+    ("ctrl+x left", KeyAction::PreviousBuffer),
+    ("ctrl+x 2", KeyAction::SplitHorizontal),
+    ("ctrl+x 3", KeyAction::SplitVertical),
+    ("ctrl+x 0", KeyAction::ClosePane),
+    ("ctrl+x o", KeyAction::FocusNextPane),
+    // Motion
+    ("ctrl+f", KeyAction::MoveCursor(Direction::Right)),
+    ("ctrl+b", KeyAction::MoveCursor(Direction::Left)),
+    ("ctrl+n", KeyAction::MoveCursor(Direction::Down)),
+    ("ctrl+p", KeyAction::MoveCursor(Direction::Up)),
+    ("ctrl+a", KeyAction::GoToLineStart),
+    ("ctrl+e", KeyAction::GoToLineEnd),
+    ("alt+f", KeyAction::ForwardWord),
+    ("alt+b", KeyAction::BackwardWord),
+    ("alt+<", KeyAction::GoToDocumentStart),
+    ("alt+>", KeyAction::GoToDocumentEnd),
+    ("alt+g g", KeyAction::GoToLine),
+    // Mark, region and kill ring
+    ("ctrl+space", KeyAction::SetMark),
+    ("ctrl+g", KeyAction::Cancel),
+    ("ctrl+w", KeyAction::KillRegion),
+    ("alt+w", KeyAction::CopyRegion),
+    ("ctrl+y", KeyAction::Yank),
+    ("alt+y", KeyAction::YankPop),
+    // Editing and search
+    ("ctrl+d", KeyAction::DeleteForward),
+    ("ctrl+/", KeyAction::Undo),
+    ("ctrl+_", KeyAction::Undo),
+    // Legacy terminals report Ctrl+/ as Ctrl+7
+    ("ctrl+7", KeyAction::Undo),
+    ("ctrl+s", KeyAction::Find),
+    ("ctrl+r", KeyAction::FindBackward),
+    ("alt+%", KeyAction::Replace),
+];
+
+/// Editing style layered on top of the key bindings
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum KeymapProfile {
+    /// Modeless editing with the shortcuts from the binding table
+    #[default]
+    Standard,
+    /// Vim emulation (modes, motions and operators) in front of the bindings
+    Vim,
+    /// Emacs-style control and meta bindings with a mark and kill ring
+    Emacs,
+}
+
+impl KeymapProfile {
+    /// Look up a profile by its keymap file name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "standard" => Some(KeymapProfile::Standard),
+            "vim" => Some(KeymapProfile::Vim),
+            "emacs" => Some(KeymapProfile::Emacs),
+            _ => None,
+        }
+    }
+
+    /// Built-in bindings the profile adds on top of the defaults
+    fn bindings(self) -> &'static [(&'static str, KeyAction)] {
+        match self {
+            KeymapProfile::Emacs => EMACS_BINDINGS,
+            KeymapProfile::Standard | KeymapProfile::Vim => &[],
+        }
+    }
+}
+
+/// A single key press with its modifiers, e.g. `ctrl+shift+left`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let modifiers = modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        match code {
+            // Terminals report Shift+Tab as a separate key
+            KeyCode::BackTab => Self { code: KeyCode::Tab, modifiers: modifiers | KeyModifiers::SHIFT },
+            // Shifted letters arrive uppercase; store them as lowercase plus Shift
+            KeyCode::Char(ch) if ch.is_uppercase() => Self {
+                code: KeyCode::Char(ch.to_lowercase().next().unwrap_or(ch)),
+                modifiers: modifiers | KeyModifiers::SHIFT,
+            },
+            // For symbols Shift is already part of the character (`?`, `!`)
+            KeyCode::Char(ch) if !ch.is_alphabetic() && ch != ' ' => Self {
+                code,
+                modifiers: modifiers - KeyModifiers::SHIFT,
+            },
+            _ => Self { code, modifiers },
+        }
+    }
+
+    /// Chord for a terminal key event
+    pub fn from_event(key_event: &KeyEvent) -> Self {
+        Self::new(key_event.code, key_event.modifiers)
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    /// Parse a chord such as `ctrl+k`, `alt+shift+f3` or `space`
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = text.trim().split('+').map(str::trim).collect();
+        let key = parts.pop().unwrap_or_default();
+        if key.is_empty() {
+            return Err("missing key (use `plus` for the + key)".to_string());
+        }
+
+        let mut modifiers = KeyModifiers::NONE;
+        for part in parts {
+            modifiers |= match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" | "option" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                other => return Err(format!("unknown modifier `{}`", other)),
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(ch), None) => KeyCode::Char(ch),
+            _ => match key.to_ascii_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "plus" => KeyCode::Char('+'),
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdn" => KeyCode::PageDown,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n) if (1..=24).contains(&n) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key `{}`", key)),
+                },
+            },
+        };
+
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            f.write_str("Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char('+') => f.write_str("Plus"),
+            KeyCode::Char(ch) => write!(f, "{}", ch.to_uppercase()),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::Esc => f.write_str("Esc"),
+            KeyCode::PageUp => f.write_str("PageUp"),
+            KeyCode::PageDown => f.write_str("PageDown"),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// Parse a space-separated chord sequence such as `ctrl+k ctrl+c`
+pub fn parse_sequence(text: &str) -> Result<Vec<KeyChord>, String> {
+    let sequence = text
+        .split_whitespace()
+        .map(KeyChord::from_str)
+        .collect::<Result<Vec<_>, _>>()?;
+    if sequence.is_empty() {
+        return Err("empty key sequence".to_string());
+    }
+    Ok(sequence)
+}
+
+/// Format a chord sequence for display, e.g. `Ctrl+K Ctrl+C`
+pub fn format_sequence(sequence: &[KeyChord]) -> String {
+    sequence.iter().map(KeyChord::to_string).collect::<Vec<_>>().join(" ")
+}
+
+/// Problem found while loading a keymap file
+#[derive(Debug)]
+pub enum KeymapError {
+    Io(PathBuf, io::Error),
+    Parse(String),
+    InvalidKey { keys: String, reason: String },
+    UnknownAction { keys: String, action: String },
+    Conflict { keys: String, other: String },
+    UnknownProfile(String),
+}
+
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeymapError::Io(path, error) => write!(f, "could not read {}: {}", path.display(), error),
+            KeymapError::Parse(message) => write!(f, "invalid keymap file: {}", message),
+            KeymapError::InvalidKey { keys, reason } => write!(f, "invalid key `{}`: {}", keys, reason),
+            KeymapError::UnknownAction { keys, action } => write!(f, "unknown action `{}` for `{}`", action, keys),
+            KeymapError::Conflict { keys, other } => write!(f, "`{}` conflicts with `{}`", keys, other),
+            KeymapError::UnknownProfile(name) => write!(f, "unknown profile `{}`", name),
+        }
+    }
+}
+
+impl std::error::Error for KeymapError {}
+
+/// On-disk keymap format: an optional `profile` plus `[bindings]` mapping key sequences to action names
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeymapFile {
+    profile: Option<String>,
+    #[serde(default)]
+    bindings: BTreeMap<String, String>,
+}
+
+/// Maps key chords and chord sequences to editor actions.
+///
+/// Multi-key sequences such as `ctrl+k ctrl+c` are resolved one key at a
+/// time: while the keys typed so far are a prefix of some binding, the
+/// keymap reports `KeyAction::None` and waits for the next key.
+///
+/// Bindings come in three layers: the defaults, the profile's own table
+/// and the user's bindings, each replacing what it collides with below.
+pub struct Keymap {
+    bindings: HashMap<Vec<KeyChord>, KeyAction>,
+    user: Vec<(Vec<KeyChord>, KeyAction)>,
+    pending: Vec<KeyChord>,
+    profile: KeymapProfile,
+}
+
+impl Keymap {
+    /// Create a keymap with the built-in bindings
+    pub fn new() -> Self {
+        Self::with_profile(KeymapProfile::Standard)
+    }
+
+    /// Create a keymap with the built-in bindings of a profile
+    pub fn with_profile(profile: KeymapProfile) -> Self {
+        let mut keymap = Self {
+            bindings: HashMap::new(),
+            user: Vec::new(),
+            pending: Vec::new(),
+            profile,
+        };
+        keymap.rebuild();
+        keymap
+    }
+
+    /// Load the default keymap plus the user's keymap file, if there is one
+    pub fn load() -> (Self, Vec<KeymapError>) {
+        let mut keymap = Self::new();
+        let path = KEYMAP_FILES
+            .iter()
+            .filter_map(|name| config::config_file(name))
+            .find(|path| path.exists());
+        let errors = match path {
+            Some(path) => keymap.load_file(&path),
+            None => Vec::new(),
+        };
+        (keymap, errors)
+    }
+
+    /// Apply a TOML or JSON keymap file (chosen by extension) on top of the current bindings
+    pub fn load_file(&mut self, path: &Path) -> Vec<KeymapError> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => return vec![KeymapError::Io(path.to_path_buf(), e)],
+        };
+        if path.extension().is_some_and(|ext| ext == "json") {
+            self.load_json(&text)
+        } else {
+            self.load_toml(&text)
+        }
+    }
+
+    /// Apply keymap bindings from TOML text
+    pub fn load_toml(&mut self, text: &str) -> Vec<KeymapError> {
+        match toml::from_str::<KeymapFile>(text) {
+            Ok(file) => self.apply_file(file),
+            Err(e) => vec![KeymapError::Parse(e.message().to_string())],
+        }
+    }
+
+    /// Apply keymap bindings from JSON text
+    pub fn load_json(&mut self, text: &str) -> Vec<KeymapError> {
+        match serde_json::from_str::<KeymapFile>(text) {
+            Ok(file) => self.apply_file(file),
+            Err(e) => vec![KeymapError::Parse(e.to_string())],
+        }
+    }
+
+    fn apply_file(&mut self, file: KeymapFile) -> Vec<KeymapError> {
+        let mut errors = Vec::new();
+        if let Some(name) = file.profile {
+            match KeymapProfile::from_name(name.trim()) {
+                Some(profile) => self.set_profile(profile),
+                None => errors.push(KeymapError::UnknownProfile(name)),
+            }
+        }
+        errors.extend(self.apply_bindings(&file.bindings));
+        errors
+    }
+
+    /// Editing style selected in the keymap file
+    pub fn profile(&self) -> KeymapProfile {
+        self.profile
+    }
+
+    /// Switch profile, keeping the user's bindings on top of the new built-in ones
+    pub fn set_profile(&mut self, profile: KeymapProfile) {
+        if profile != self.profile {
+            self.profile = profile;
+            self.rebuild();
+        }
+    }
+
+    /// Apply user bindings, replacing any existing binding they collide with.
+    ///
+    /// Invalid keys, unknown actions and bindings that clash with an earlier
+    /// entry (the same keys, or one being a prefix of the other) are skipped
+    /// and reported.
+    pub fn apply_bindings(&mut self, bindings: &BTreeMap<String, String>) -> Vec<KeymapError> {
+        let mut errors = Vec::new();
+        let mut applied: Vec<(&str, Vec<KeyChord>)> = Vec::new();
+
+        for (keys, action_name) in bindings {
+            let sequence = match parse_sequence(keys) {
+                Ok(sequence) => sequence,
+                Err(reason) => {
+                    errors.push(KeymapError::InvalidKey { keys: keys.clone(), reason });
+                    continue;
+                }
+            };
+            let Some(action) = KeyAction::from_name(action_name.trim()) else {
+                errors.push(KeymapError::UnknownAction { keys: keys.clone(), action: action_name.clone() });
+                continue;
+            };
+
+            if action == KeyAction::None {
+                self.add_user_binding(sequence, action);
+                continue;
+            }
+            if let Some((other, _)) = applied.iter().find(|(_, other)| overlaps(other, &sequence)) {
+                errors.push(KeymapError::Conflict { keys: keys.clone(), other: other.to_string() });
+                continue;
+            }
+
+            self.add_user_binding(sequence.clone(), action);
+            applied.push((keys, sequence));
+        }
+
+        errors
+    }
+
+    /// Bind a key sequence, replacing any binding it collides with
+    pub fn bind(&mut self, keys: &str, action: KeyAction) -> Result<(), KeymapError> {
+        let sequence = parse_sequence(keys)
+            .map_err(|reason| KeymapError::InvalidKey { keys: keys.to_string(), reason })?;
+        self.add_user_binding(sequence, action);
+        Ok(())
+    }
+
+    fn add_user_binding(&mut self, sequence: Vec<KeyChord>, action: KeyAction) {
+        self.insert(sequence.clone(), action.clone());
+        self.user.push((sequence, action));
+    }
+
+    /// Recompute the active bindings from the defaults, the profile and the user layer
+    fn rebuild(&mut self) {
+        self.bindings.clear();
+        for (keys, action) in DEFAULT_BINDINGS.iter().chain(self.profile.bindings()) {
+            let sequence = parse_sequence(keys).expect("built-in bindings are valid");
+            self.insert(sequence, action.clone());
+        }
+        let user = std::mem::take(&mut self.user);
+        for (sequence, action) in &user {
+            self.insert(sequence.clone(), action.clone());
+        }
+        self.user = user;
+    }
+
+    /// Bind a sequence, replacing any binding it collides with; `None` only removes the exact sequence
+    fn insert(&mut self, sequence: Vec<KeyChord>, action: KeyAction) {
+        if action == KeyAction::None {
+            self.bindings.remove(&sequence);
+            return;
+        }
+        self.bindings.retain(|existing, _| !overlaps(existing, &sequence));
+        self.bindings.insert(sequence, action);
+    }
+
+    /// Action bound to an exact key sequence
+    pub fn action_for(&self, keys: &str) -> Option<&KeyAction> {
+        parse_sequence(keys).ok().and_then(|sequence| self.bindings.get(&sequence))
+    }
+
+    /// Keys typed so far of an unfinished sequence, for display in the status line
+    pub fn pending_keys(&self) -> Option<String> {
+        if self.pending.is_empty() {
+            None
+        } else {
+            Some(format_sequence(&self.pending))
+        }
+    }
+
+    /// Forget any partially typed sequence
+    pub fn reset(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Process a key event and return the corresponding action.
+    ///
+    /// Plain characters that are not bound fall back to `InsertChar`.
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> KeyAction {
+        self.pending.push(KeyChord::from_event(&key_event));
+
+        if let Some(action) = self.bindings.get(&self.pending) {
+            let action = action.clone();
+            self.pending.clear();
+            return action;
+        }
+
+        let is_prefix = self
+            .bindings
+            .keys()
+            .any(|sequence| sequence.len() > self.pending.len() && sequence.starts_with(&self.pending));
+        if is_prefix {
+            return KeyAction::None;
+        }
+
+        // A broken sequence is dropped as a whole rather than typed
+        let was_pending = self.pending.len() > 1;
+        self.pending.clear();
+        if was_pending {
+            return KeyAction::None;
+        }
+
+        match key_event.code {
+            KeyCode::Char(ch) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => KeyAction::InsertChar(ch),
+            _ => KeyAction::None,
+        }
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Two sequences clash when they are equal or one is a prefix of the other
+fn overlaps(a: &[KeyChord], b: &[KeyChord]) -> bool {
+    a.starts_with(b) || b.starts_with(a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_chord_parsing_and_display() {
+        let chord: KeyChord = "Ctrl+Shift+Left".parse().unwrap();
+        assert_eq!(chord, KeyChord::new(KeyCode::Left, KeyModifiers::CONTROL | KeyModifiers::SHIFT));
+        assert_eq!(chord.to_string(), "Ctrl+Shift+Left");
+
+        // Uppercase letters and Shift+letter are the same chord, BackTab is Shift+Tab
+        assert_eq!("A".parse::<KeyChord>().unwrap(), "shift+a".parse().unwrap());
+        assert_eq!(KeyChord::new(KeyCode::BackTab, KeyModifiers::SHIFT), "shift+tab".parse().unwrap());
+        assert_eq!("shift+?".parse::<KeyChord>().unwrap(), KeyChord::new(KeyCode::Char('?'), KeyModifiers::NONE));
+        assert_eq!("f3".parse::<KeyChord>().unwrap().to_string(), "F3");
+
+        assert!("hyper+a".parse::<KeyChord>().is_err());
+        assert!("ctrl+".parse::<KeyChord>().is_err());
+        assert!(parse_sequence("  ").is_err());
+    }
+
+    #[test]
+    fn test_defaults_and_sequences() {
+        let mut keymap = Keymap::new();
+        assert_eq!(keymap.handle_key_event(key(KeyCode::Char('s'), KeyModifiers::CONTROL)), KeyAction::Save);
+        assert_eq!(keymap.handle_key_event(key(KeyCode::Char('X'), KeyModifiers::SHIFT)), KeyAction::InsertChar('X'));
+        assert_eq!(keymap.handle_key_event(key(KeyCode::F(3), KeyModifiers::SHIFT)), KeyAction::FindPrevious);
+        assert_eq!(keymap.handle_key_event(key(KeyCode::BackTab, KeyModifiers::CONTROL | KeyModifiers::SHIFT)), KeyAction::PreviousBuffer);
+
+        // A sequence replaces the default bound to its first key
+        keymap.bind("ctrl+k ctrl+c", KeyAction::Copy).unwrap();
+        assert_eq!(keymap.handle_key_event(key(KeyCode::Char('k'), KeyModifiers::CONTROL)), KeyAction::None);
+        assert_eq!(keymap.pending_keys().as_deref(), Some("Ctrl+K"));
+        assert_eq!(keymap.handle_key_event(key(KeyCode::Char('c'), KeyModifiers::CONTROL)), KeyAction::Copy);
+        assert_eq!(keymap.pending_keys(), None);
+
+        // A broken sequence is swallowed
+        keymap.handle_key_event(key(KeyCode::Char('k'), KeyModifiers::CONTROL));
+        assert_eq!(keymap.handle_key_event(key(KeyCode::Char('x'), KeyModifiers::NONE)), KeyAction::None);
+        assert_eq!(keymap.handle_key_event(key(KeyCode::Char('x'), KeyModifiers::NONE)), KeyAction::InsertChar('x'));
+    }
+
+    #[test]
+    fn test_user_bindings_and_errors() {
+        let mut keymap = Keymap::new();
+        let errors = keymap.load_toml(
+            r#"
+            [bindings]
+            "ctrl+b" = "find"
+            "ctrl+d" = "none"
+            "ctrl+g" = "go-to-line"
+            "ctrl+g ctrl+g" = "document-start"
+            "ctrl+j" = "jump-around"
+            "ctrl+shift" = "save"
+            "#,
+        );
+        assert_eq!(keymap.action_for("ctrl+b"), Some(&KeyAction::Find));
+        assert_eq!(keymap.action_for("ctrl+d"), None);
+        assert_eq!(keymap.action_for("ctrl+g"), Some(&KeyAction::GoToLine));
+        assert_eq!(keymap.action_for("ctrl+f"), Some(&KeyAction::Find));
+
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(&errors[0], KeymapError::Conflict { keys, other } if keys == "ctrl+g ctrl+g" && other == "ctrl+g"));
+        assert!(matches!(&errors[1], KeymapError::UnknownAction { action, .. } if action == "jump-around"));
+        assert!(matches!(&errors[2], KeymapError::InvalidKey { .. }));
+
+        let errors = keymap.load_json(r#"{ "profile": "vim", "bindings": { "alt+x": "cut" } }"#);
+        assert!(errors.is_empty());
+        assert_eq!(keymap.profile(), KeymapProfile::Vim);
+        assert_eq!(keymap.action_for("alt+x"), Some(&KeyAction::Cut));
+        assert!(matches!(keymap.load_toml("bindings = 3").as_slice(), [KeymapError::Parse(_)]));
+        assert!(matches!(keymap.load_toml("profile = \"nano\"").as_slice(), [KeymapError::UnknownProfile(_)]));
+    }
+
+    #[test]
+    fn test_emacs_profile_layers() {
+        let mut keymap = Keymap::new();
+        keymap.bind("alt+f", KeyAction::Find).unwrap();
+        keymap.set_profile(KeymapProfile::Emacs);
+
+        assert_eq!(keymap.action_for("ctrl+f"), Some(&KeyAction::MoveCursor(Direction::Right)));
+        assert_eq!(keymap.action_for("ctrl+space"), Some(&KeyAction::SetMark));
+        assert_eq!(keymap.action_for("alt+<"), Some(&KeyAction::GoToDocumentStart));
+        assert_eq!(keymap.action_for("ctrl+x 3"), Some(&KeyAction::SplitVertical));
+        // Defaults the profile does not touch survive, and user bindings stay on top
+        assert_eq!(keymap.action_for("f3"), Some(&KeyAction::FindNext));
+        assert_eq!(keymap.action_for("alt+f"), Some(&KeyAction::Find));
+
+        // `ctrl+x` turns into a prefix key
+        assert_eq!(keymap.handle_key_event(key(KeyCode::Char('x'), KeyModifiers::CONTROL)), KeyAction::None);
+        assert_eq!(keymap.handle_key_event(key(KeyCode::Char('s'), KeyModifiers::CONTROL)), KeyAction::Save);
+
+        keymap.set_profile(KeymapProfile::Standard);
+        assert_eq!(keymap.action_for("ctrl+f"), Some(&KeyAction::Find));
+        assert_eq!(keymap.action_for("ctrl+space"), None);
+    }
+}
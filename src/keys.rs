@@ -1,7 +1,5 @@
-use crossterm::event::{KeyEvent, KeyCode, KeyModifiers};
-
 /// Direction for cursor movement
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Direction {
     Up,
     Down,
@@ -10,7 +8,7 @@ pub enum Direction {
 }
 
 /// Key action that should be performed
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum KeyAction {
     // File operations
     Quit,
@@ -18,6 +16,19 @@ pub enum KeyAction {
     Open,
     NewFile,
     
+    // Buffers
+    NextBuffer,
+    PreviousBuffer,
+    CloseBuffer,
+    
+    // Panes
+    SplitHorizontal,
+    SplitVertical,
+    ClosePane,
+    FocusPane(Direction),
+    FocusNextPane,
+    ResizePane(Direction),
+    
     // Editing operations
     InsertChar(char),
     InsertNewline,
@@ -30,95 +41,168 @@ pub enum KeyAction {
     GoToLineEnd,
     GoToDocumentStart,
     GoToDocumentEnd,
+    MoveWord(Direction),
+    ForwardWord,
+    BackwardWord,
+    
+    // Selection
+    SelectMove(Direction),
+    SelectWord(Direction),
+    SelectToLineStart,
+    SelectToLineEnd,
+    SelectToDocumentStart,
+    SelectToDocumentEnd,
+    SelectAll,
+    
+    // Multiple cursors
+    AddCaretAbove,
+    AddCaretBelow,
+    AddNextOccurrence,
     
     // Line operations
     DuplicateLine,
     DeleteLine,
     
+    // History
+    Undo,
+    Redo,
+    
+    // Clipboard
+    Copy,
+    Cut,
+    Paste,
+    
+    // Emacs mark and kill ring
+    SetMark,
+    KillRegion,
+    CopyRegion,
+    Yank,
+    YankPop,
+    
+    // Search
+    Find,
+    FindBackward,
+    Replace,
+    FindNext,
+    FindPrevious,
+    
     // View operations
     ToggleSyntaxHighlighting,
     ToggleLinting,
+    ToggleSoftWrap,
     GoToLine,
+    ToggleLanguage,
+    ToggleVimMode,
+    PickTheme,
+    SetLanguage,
+    
+    // Lint fixes
+    QuickFix,
+    FixAll,
+    
+    // Dismiss extra carets, search highlights or the selection
+    Cancel,
     
     // No action
     None,
 }
 
-/// Key handler for processing keyboard events
-pub struct KeyHandler;
-
-impl KeyHandler {
-    pub fn new() -> Self {
-        Self
-    }
-    
-    /// Process a key event and return the corresponding action
-    pub fn handle_key_event(&self, key_event: KeyEvent) -> KeyAction {
-        match (key_event.modifiers.contains(KeyModifiers::CONTROL), key_event.code) {
-            // Ctrl+Q - Quit
-            (true, KeyCode::Char('q')) => KeyAction::Quit,
-            
-            // Ctrl+S - Save
-            (true, KeyCode::Char('s')) => KeyAction::Save,
-            
-            // Ctrl+O - Open file
-            (true, KeyCode::Char('o')) => KeyAction::Open,
-            
-            // Ctrl+H - Toggle syntax highlighting
-            (true, KeyCode::Char('h')) => KeyAction::ToggleSyntaxHighlighting,
-            
-            // Ctrl+E - Toggle linting
-            (true, KeyCode::Char('e')) => KeyAction::ToggleLinting,
-            
-            // Ctrl+N - New file
-            (true, KeyCode::Char('n')) => KeyAction::NewFile,
-            
-            // Ctrl+Home - Go to start of document
-            (true, KeyCode::Home) => KeyAction::GoToDocumentStart,
-            
-            // Ctrl+End - Go to end of document
-            (true, KeyCode::End) => KeyAction::GoToDocumentEnd,
-            
-            // Ctrl+L - Go to line
-            (true, KeyCode::Char('l')) => KeyAction::GoToLine,
-            
-            // Ctrl+D - Duplicate line
-            (true, KeyCode::Char('d')) => KeyAction::DuplicateLine,
-            
-            // Ctrl+K - Delete line
-            (true, KeyCode::Char('k')) => KeyAction::DeleteLine,
-
-            // Regular character input
-            (false, KeyCode::Char(ch)) => KeyAction::InsertChar(ch),
-            
-            // Enter key
-            (false, KeyCode::Enter) => KeyAction::InsertNewline,
-            
-            // Backspace
-            (false, KeyCode::Backspace) => KeyAction::DeleteBackward,
-            
-            // Delete key
-            (false, KeyCode::Delete) => KeyAction::DeleteForward,
-            
-            // Home - Go to start of line
-            (false, KeyCode::Home) => KeyAction::GoToLineStart,
-            
-            // End - Go to end of line
-            (false, KeyCode::End) => KeyAction::GoToLineEnd,
+/// Names used for actions in keymap files, e.g. `"ctrl+s" = "save"`
+const ACTION_NAMES: &[(&str, KeyAction)] = &[
+    ("quit", KeyAction::Quit),
+    ("save", KeyAction::Save),
+    ("open", KeyAction::Open),
+    ("new-file", KeyAction::NewFile),
+    ("next-buffer", KeyAction::NextBuffer),
+    ("previous-buffer", KeyAction::PreviousBuffer),
+    ("close-buffer", KeyAction::CloseBuffer),
+    ("split-horizontal", KeyAction::SplitHorizontal),
+    ("split-vertical", KeyAction::SplitVertical),
+    ("close-pane", KeyAction::ClosePane),
+    ("focus-pane-up", KeyAction::FocusPane(Direction::Up)),
+    ("focus-pane-down", KeyAction::FocusPane(Direction::Down)),
+    ("focus-pane-left", KeyAction::FocusPane(Direction::Left)),
+    ("focus-pane-right", KeyAction::FocusPane(Direction::Right)),
+    ("focus-next-pane", KeyAction::FocusNextPane),
+    ("resize-pane-up", KeyAction::ResizePane(Direction::Up)),
+    ("resize-pane-down", KeyAction::ResizePane(Direction::Down)),
+    ("resize-pane-left", KeyAction::ResizePane(Direction::Left)),
+    ("resize-pane-right", KeyAction::ResizePane(Direction::Right)),
+    ("insert-newline", KeyAction::InsertNewline),
+    ("delete-backward", KeyAction::DeleteBackward),
+    ("delete-forward", KeyAction::DeleteForward),
+    ("move-up", KeyAction::MoveCursor(Direction::Up)),
+    ("move-down", KeyAction::MoveCursor(Direction::Down)),
+    ("move-left", KeyAction::MoveCursor(Direction::Left)),
+    ("move-right", KeyAction::MoveCursor(Direction::Right)),
+    ("line-start", KeyAction::GoToLineStart),
+    ("line-end", KeyAction::GoToLineEnd),
+    ("document-start", KeyAction::GoToDocumentStart),
+    ("document-end", KeyAction::GoToDocumentEnd),
+    ("word-left", KeyAction::MoveWord(Direction::Left)),
+    ("word-right", KeyAction::MoveWord(Direction::Right)),
+    ("forward-word", KeyAction::ForwardWord),
+    ("backward-word", KeyAction::BackwardWord),
+    ("select-up", KeyAction::SelectMove(Direction::Up)),
+    ("select-down", KeyAction::SelectMove(Direction::Down)),
+    ("select-left", KeyAction::SelectMove(Direction::Left)),
+    ("select-right", KeyAction::SelectMove(Direction::Right)),
+    ("select-word-left", KeyAction::SelectWord(Direction::Left)),
+    ("select-word-right", KeyAction::SelectWord(Direction::Right)),
+    ("select-line-start", KeyAction::SelectToLineStart),
+    ("select-line-end", KeyAction::SelectToLineEnd),
+    ("select-document-start", KeyAction::SelectToDocumentStart),
+    ("select-document-end", KeyAction::SelectToDocumentEnd),
+    ("select-all", KeyAction::SelectAll),
+    ("add-caret-above", KeyAction::AddCaretAbove),
+    ("add-caret-below", KeyAction::AddCaretBelow),
+    ("add-next-occurrence", KeyAction::AddNextOccurrence),
+    ("duplicate-line", KeyAction::DuplicateLine),
+    ("delete-line", KeyAction::DeleteLine),
+    ("undo", KeyAction::Undo),
+    ("redo", KeyAction::Redo),
+    ("copy", KeyAction::Copy),
+    ("cut", KeyAction::Cut),
+    ("paste", KeyAction::Paste),
+    ("set-mark", KeyAction::SetMark),
+    ("kill-region", KeyAction::KillRegion),
+    ("copy-region", KeyAction::CopyRegion),
+    ("yank", KeyAction::Yank),
+    ("yank-pop", KeyAction::YankPop),
+    ("find", KeyAction::Find),
+    ("find-backward", KeyAction::FindBackward),
+    ("replace", KeyAction::Replace),
+    ("find-next", KeyAction::FindNext),
+    ("find-previous", KeyAction::FindPrevious),
+    ("toggle-syntax-highlighting", KeyAction::ToggleSyntaxHighlighting),
+    ("toggle-linting", KeyAction::ToggleLinting),
+    ("toggle-soft-wrap", KeyAction::ToggleSoftWrap),
+    ("go-to-line", KeyAction::GoToLine),
+    ("toggle-language", KeyAction::ToggleLanguage),
+    ("toggle-vim-mode", KeyAction::ToggleVimMode),
+    ("pick-theme", KeyAction::PickTheme),
+    ("set-language", KeyAction::SetLanguage),
+    ("quick-fix", KeyAction::QuickFix),
+    ("fix-all", KeyAction::FixAll),
+    ("cancel", KeyAction::Cancel),
+    // Binding a key to `none` removes its default binding
+    ("none", KeyAction::None),
+];
 
-            // Movement commands
-            (false, KeyCode::Up) => KeyAction::MoveCursor(Direction::Up),
-            (false, KeyCode::Down) => KeyAction::MoveCursor(Direction::Down),
-            (false, KeyCode::Left) => KeyAction::MoveCursor(Direction::Left),
-            (false, KeyCode::Right) => KeyAction::MoveCursor(Direction::Right),
-
-            _ => KeyAction::None,
-        }
+impl KeyAction {
+    /// Look up an action by its keymap name
+    pub fn from_name(name: &str) -> Option<KeyAction> {
+        ACTION_NAMES
+            .iter()
+            .find(|(action_name, _)| *action_name == name)
+            .map(|(_, action)| action.clone())
     }
-}
 
-impl Default for KeyHandler {
-    fn default() -> Self {
-        Self::new()
+    /// Keymap name of an action; character input has none
+    pub fn name(&self) -> Option<&'static str> {
+        ACTION_NAMES
+            .iter()
+            .find(|(_, action)| action == self)
+            .map(|(action_name, _)| *action_name)
     }
 }
@@ -0,0 +1,389 @@
+use std::collections::HashMap;
+use std::path::Path;
+use serde::Deserialize;
+use syntect::parsing::{SyntaxDefinition, SyntaxSet};
+use crate::buffer::TextBuffer;
+use crate::config;
+
+/// Language of files nothing else matches
+pub const PLAIN_TEXT: &str = "Plain Text";
+
+/// Lines at the start and end of a file searched for a modeline
+const MODELINE_LINES: usize = 5;
+
+/// Extensions whose language differs from what syntect would pick, or that it lacks
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("rs", "Rust"),
+    ("py", "Python"),
+    ("js", "JavaScript"),
+    ("jsx", "JavaScript"),
+    ("mjs", "JavaScript"),
+    ("cjs", "JavaScript"),
+    ("ts", "TypeScript"),
+    ("tsx", "TypeScript"),
+    ("mts", "TypeScript"),
+    ("cts", "TypeScript"),
+    ("html", "HTML"),
+    ("css", "CSS"),
+    ("json", "JSON"),
+    ("xml", "XML"),
+    ("yaml", "YAML"),
+    ("yml", "YAML"),
+    ("toml", "TOML"),
+    ("md", "Markdown"),
+    ("txt", PLAIN_TEXT),
+];
+
+/// Files known by their whole name
+const FILENAMES: &[(&str, &str)] = &[
+    ("Dockerfile", "Dockerfile"),
+    ("Containerfile", "Dockerfile"),
+    ("Makefile", "Makefile"),
+    ("GNUmakefile", "Makefile"),
+    ("makefile", "Makefile"),
+    ("Jenkinsfile", "Groovy"),
+    ("Cargo.lock", "TOML"),
+    ("Pipfile", "TOML"),
+];
+
+/// Interpreters named on a shebang line
+const INTERPRETERS: &[(&str, &str)] = &[
+    ("python", "Python"),
+    ("node", "JavaScript"),
+    ("nodejs", "JavaScript"),
+    ("deno", "JavaScript"),
+    ("bun", "JavaScript"),
+    ("ts-node", "TypeScript"),
+    ("sh", "Bourne Again Shell (bash)"),
+    ("bash", "Bourne Again Shell (bash)"),
+    ("zsh", "Bourne Again Shell (bash)"),
+    ("dash", "Bourne Again Shell (bash)"),
+    ("ksh", "Bourne Again Shell (bash)"),
+    ("ruby", "Ruby"),
+    ("perl", "Perl"),
+    ("php", "PHP"),
+    ("lua", "Lua"),
+    ("make", "Makefile"),
+];
+
+/// User mappings from `languages.toml`
+#[derive(Debug, Default, Deserialize)]
+struct LanguagesFile {
+    #[serde(default)]
+    extensions: HashMap<String, String>,
+    #[serde(default)]
+    filenames: HashMap<String, String>,
+}
+
+/// Works out which language a buffer is written in.
+///
+/// Highlighting and linting both ask this service, so they always agree.
+/// A file's language comes from, in order: a modeline (`vim: ft=python`),
+/// the user's filename and extension mappings, well-known file names, the
+/// extension, a shebang line, and finally the first-line patterns of the
+/// loaded syntaxes.
+pub struct LanguageDetector {
+    syntax_set: SyntaxSet,
+    user_filenames: HashMap<String, String>,
+    user_extensions: HashMap<String, String>,
+}
+
+impl Default for LanguageDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LanguageDetector {
+    /// A detector knowing syntect's bundled syntaxes
+    pub fn new() -> Self {
+        Self {
+            syntax_set: SyntaxSet::load_defaults_newlines(),
+            user_filenames: HashMap::new(),
+            user_extensions: HashMap::new(),
+        }
+    }
+
+    /// Syntaxes available for highlighting, including the user's
+    pub fn syntax_set(&self) -> &SyntaxSet {
+        &self.syntax_set
+    }
+
+    /// Names of every known language, sorted
+    pub fn languages(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.syntax_set.syntaxes().iter().map(|syntax| syntax.name.clone()).collect();
+        names.sort_by_key(|name| name.to_lowercase());
+        names.dedup();
+        names
+    }
+
+    /// Language of a buffer, judging by its file name and content
+    pub fn detect(&self, filename: Option<&str>, buffer: &TextBuffer) -> String {
+        self.detect_from_modeline(buffer)
+            .or_else(|| filename.and_then(|filename| self.detect_from_filename(filename)))
+            .or_else(|| self.detect_from_first_line(&buffer.line(0)))
+            .unwrap_or_else(|| PLAIN_TEXT.to_string())
+    }
+
+    /// Language implied by a file's name or extension
+    pub fn detect_from_filename(&self, filename: &str) -> Option<String> {
+        let path = Path::new(filename);
+        let name = path.file_name()?.to_str()?;
+        let extension = path.extension().and_then(|ext| ext.to_str());
+
+        if let Some(language) = self.user_filenames.get(name) {
+            return Some(language.clone());
+        }
+        if let Some(language) = extension.and_then(|ext| self.user_extensions.get(ext)) {
+            return Some(language.clone());
+        }
+        let builtin = |table: &[(&str, &str)], key: &str| {
+            table.iter().find(|(known, _)| *known == key).map(|(_, language)| language.to_string())
+        };
+        // `Dockerfile.dev` and the like are still Dockerfiles
+        let base_name = name.split('.').next().unwrap_or(name);
+        builtin(FILENAMES, name)
+            .or_else(|| builtin(FILENAMES, base_name).filter(|language| language == "Dockerfile"))
+            .or_else(|| extension.and_then(|ext| builtin(EXTENSIONS, ext)))
+            .or_else(|| {
+                self.syntax_set
+                    .find_syntax_by_extension(name)
+                    .or_else(|| extension.and_then(|ext| self.syntax_set.find_syntax_by_extension(ext)))
+                    .map(|syntax| syntax.name.clone())
+            })
+    }
+
+    /// Language from a shebang line or a syntax's first-line pattern
+    pub fn detect_from_first_line(&self, line: &str) -> Option<String> {
+        if let Some(interpreter) = shebang_interpreter(line) {
+            let known = INTERPRETERS.iter().find(|(name, _)| *name == interpreter);
+            if let Some((_, language)) = known {
+                return Some(language.to_string());
+            }
+            if let Some(syntax) = self.syntax_set.find_syntax_by_token(&interpreter) {
+                return Some(syntax.name.clone());
+            }
+        }
+        self.syntax_set
+            .find_syntax_by_first_line(line)
+            .map(|syntax| syntax.name.clone())
+    }
+
+    /// Language named by a vim modeline near the start or end of the buffer
+    fn detect_from_modeline(&self, buffer: &TextBuffer) -> Option<String> {
+        let count = buffer.len_lines();
+        let head = 0..MODELINE_LINES.min(count);
+        let tail = count.saturating_sub(MODELINE_LINES).max(head.end)..count;
+        head.chain(tail)
+            .find_map(|row| modeline_filetype(&buffer.line(row)))
+            .map(|filetype| {
+                self.syntax_set
+                    .find_syntax_by_token(&filetype)
+                    .map(|syntax| syntax.name.clone())
+                    .or_else(|| self.user_extensions.get(&filetype).cloned())
+                    .unwrap_or(filetype)
+            })
+    }
+
+    /// Map files with `extension` (without the dot) to `language`
+    pub fn add_extension(&mut self, extension: &str, language: &str) {
+        self.user_extensions.insert(extension.trim_start_matches('.').to_string(), language.to_string());
+    }
+
+    /// Map files named exactly `filename` to `language`
+    pub fn add_filename(&mut self, filename: &str, language: &str) {
+        self.user_filenames.insert(filename.to_string(), language.to_string());
+    }
+
+    /// Add the mappings of a `languages.toml` file
+    pub fn load_mappings(&mut self, text: &str) -> Result<(), String> {
+        let file: LanguagesFile = toml::from_str(text).map_err(|e| e.message().to_string())?;
+        for (extension, language) in &file.extensions {
+            self.add_extension(extension, language);
+        }
+        self.user_filenames.extend(file.filenames);
+        Ok(())
+    }
+
+    /// Add the `.sublime-syntax` files in `dir` to the known syntaxes.
+    ///
+    /// Returns a message for every file that could not be loaded.
+    pub fn load_syntaxes_from(&mut self, dir: &Path) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut errors = Vec::new();
+        let mut definitions = Vec::new();
+        let paths = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "sublime-syntax"));
+        for path in paths {
+            let name = path.file_stem().map(|stem| stem.to_string_lossy().into_owned());
+            let loaded = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| SyntaxDefinition::load_from_str(&text, true, name.as_deref()).map_err(|e| e.to_string()));
+            match loaded {
+                Ok(definition) => definitions.push(definition),
+                Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+
+        // Rebuilding the set is slow, so only do it when there is something to add
+        if !definitions.is_empty() {
+            let mut builder = std::mem::take(&mut self.syntax_set).into_builder();
+            for definition in definitions {
+                builder.add(definition);
+            }
+            self.syntax_set = builder.build();
+        }
+        errors
+    }
+
+    /// Load `languages.toml` and the `syntaxes` folder from the configuration directory
+    pub fn load_user_config(&mut self) -> Vec<String> {
+        let Some(dir) = config::config_dir() else {
+            return Vec::new();
+        };
+        let mut errors = Vec::new();
+        let mappings = dir.join("languages.toml");
+        if let Ok(text) = std::fs::read_to_string(&mappings)
+            && let Err(e) = self.load_mappings(&text)
+        {
+            errors.push(format!("{}: {}", mappings.display(), e));
+        }
+        errors.extend(self.load_syntaxes_from(&dir.join("syntaxes")));
+        errors
+    }
+}
+
+/// Entries of `names` containing the chars of `query` in order, best matches first.
+///
+/// Matching ignores case; names where the query is a prefix or whose matched
+/// chars sit close together rank higher.
+pub fn fuzzy_filter<'a>(query: &str, names: &'a [String]) -> Vec<&'a String> {
+    let query: Vec<char> = query.to_lowercase().chars().filter(|ch| !ch.is_whitespace()).collect();
+    let mut scored: Vec<(usize, &String)> = names
+        .iter()
+        .filter_map(|name| fuzzy_score(&query, name).map(|score| (score, name)))
+        .collect();
+    // Stable, so equal scores keep the order of `names`
+    scored.sort_by_key(|(score, _)| *score);
+    scored.into_iter().map(|(_, name)| name).collect()
+}
+
+/// Cost of matching `query` against `name`, lower being better; `None` if it does not match
+fn fuzzy_score(query: &[char], name: &str) -> Option<usize> {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let mut positions = Vec::with_capacity(query.len());
+    let mut next = 0;
+    for ch in query {
+        let found = next + name[next..].iter().position(|candidate| candidate == ch)?;
+        positions.push(found);
+        next = found + 1;
+    }
+    let (Some(first), Some(last)) = (positions.first(), positions.last()) else {
+        return Some(0);
+    };
+    // Spread of the match, then how late it starts
+    Some((last - first + 1 - query.len()) * 2 + first)
+}
+
+/// Program a `#!` line runs, without its directory or version number
+fn shebang_interpreter(line: &str) -> Option<String> {
+    let mut words = line.strip_prefix("#!")?.split_whitespace();
+    let mut program = Path::new(words.next()?).file_name()?.to_str()?;
+    // `#!/usr/bin/env -S node --flags` names the interpreter as an argument
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-'))?;
+    }
+    let name = program.trim_end_matches(|ch: char| ch.is_ascii_digit() || ch == '.');
+    Some(name.to_string())
+}
+
+/// File type set by a vim modeline such as `# vim: set ft=python:` or `// vim: filetype=rust`
+fn modeline_filetype(line: &str) -> Option<String> {
+    let (_, options) = line.split_once("vim:").or_else(|| line.split_once("vi:"))?;
+    options
+        .split(|ch: char| ch.is_whitespace() || ch == ':')
+        .find_map(|option| option.strip_prefix("ft=").or_else(|| option.strip_prefix("filetype=")))
+        .filter(|filetype| !filetype.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_from_filename() {
+        let detector = LanguageDetector::new();
+        let detect = |name: &str| detector.detect_from_filename(name);
+
+        assert_eq!(detect("test.rs").as_deref(), Some("Rust"));
+        assert_eq!(detect("test.py").as_deref(), Some("Python"));
+        assert_eq!(detect("test.js").as_deref(), Some("JavaScript"));
+        assert_eq!(detect("test.unknown"), None);
+        assert_eq!(detect("lib/index.mjs").as_deref(), Some("JavaScript"));
+        assert_eq!(detect("config.cjs").as_deref(), Some("JavaScript"));
+        assert_eq!(detect("Dockerfile").as_deref(), Some("Dockerfile"));
+        assert_eq!(detect("docker/Dockerfile.dev").as_deref(), Some("Dockerfile"));
+        assert_eq!(detect("Makefile").as_deref(), Some("Makefile"));
+        // Names syntect knows about still work
+        assert_eq!(detect("Gemfile").as_deref(), Some("Ruby"));
+        assert_eq!(detect("main.go").as_deref(), Some("Go"));
+    }
+
+    #[test]
+    fn test_detect_from_content() {
+        let detector = LanguageDetector::new();
+        let detect = |text: &str| detector.detect(Some("script"), &TextBuffer::from(text));
+
+        assert_eq!(detect("#!/usr/bin/env python3\nprint(1)"), "Python");
+        assert_eq!(detect("#!/usr/bin/env -S node --no-warnings\n"), "JavaScript");
+        assert_eq!(detect("#!/bin/bash\necho hi"), "Bourne Again Shell (bash)");
+        assert_eq!(detect("<?php echo 1;"), "PHP");
+        assert_eq!(detect("just words"), PLAIN_TEXT);
+        // A modeline wins over the file name, at either end of the file
+        let text = "x = 1\n\n# vim: set ts=4 ft=python:";
+        assert_eq!(detector.detect(Some("notes.txt"), &TextBuffer::from(text)), "Python");
+        assert_eq!(modeline_filetype("// vim: filetype=rust").as_deref(), Some("rust"));
+        assert_eq!(shebang_interpreter("#!/usr/bin/python3.11").as_deref(), Some("python"));
+    }
+
+    #[test]
+    fn test_user_mappings_and_syntaxes() {
+        let mut detector = LanguageDetector::new();
+        detector
+            .load_mappings("[extensions]\n\".tpl\" = \"Rust\"\n\n[filenames]\nJustfile = \"Makefile\"\n")
+            .unwrap();
+        assert!(detector.load_mappings("extensions = 3").is_err());
+        assert_eq!(detector.detect_from_filename("a.tpl").as_deref(), Some("Rust"));
+        assert_eq!(detector.detect_from_filename("Justfile").as_deref(), Some("Makefile"));
+
+        let dir = std::env::temp_dir().join(format!("hello_tui_syntaxes_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("Shout.sublime-syntax"), "%YAML 1.2\n---\nname: Shout\nfile_extensions: [shout]\nscope: source.shout\ncontexts:\n  main:\n    - match: '!'\n      scope: keyword.shout\n").unwrap();
+        std::fs::write(dir.join("broken.sublime-syntax"), "name: [").unwrap();
+        let errors = detector.load_syntaxes_from(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("broken.sublime-syntax"));
+        assert_eq!(detector.detect_from_filename("hello.shout").as_deref(), Some("Shout"));
+        assert!(detector.syntax_set().find_syntax_by_name("Rust").is_some());
+        assert!(detector.languages().contains(&"Shout".to_string()));
+    }
+
+    #[test]
+    fn test_fuzzy_filter() {
+        let names: Vec<String> = ["Java", "JavaScript", "JSON", "Plain Text", "Rust"].map(String::from).to_vec();
+        let filter = |query: &str| fuzzy_filter(query, &names).into_iter().map(String::as_str).collect::<Vec<_>>();
+
+        assert_eq!(filter(""), ["Java", "JavaScript", "JSON", "Plain Text", "Rust"]);
+        assert_eq!(filter("json"), ["JSON"]);
+        assert_eq!(filter("js"), ["JSON", "JavaScript"]);
+        assert_eq!(filter("text"), ["Plain Text"]);
+        assert_eq!(filter("plain t"), ["Plain Text"]);
+        assert!(filter("xyz").is_empty());
+    }
+}
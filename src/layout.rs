@@ -0,0 +1,336 @@
+use crate::keys::Direction;
+
+/// A rectangle of terminal cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self { x, y, width, height }
+    }
+
+    /// Column just right of the rectangle
+    pub fn right(&self) -> usize {
+        self.x + self.width
+    }
+
+    /// Row just below the rectangle
+    pub fn bottom(&self) -> usize {
+        self.y + self.height
+    }
+
+    pub fn contains(&self, col: usize, row: usize) -> bool {
+        (self.x..self.right()).contains(&col) && (self.y..self.bottom()).contains(&row)
+    }
+}
+
+/// Identifies a pane for as long as it is open
+pub type PaneId = usize;
+
+/// How a split arranges its two halves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitDirection {
+    /// One pane above the other
+    Horizontal,
+    /// Side by side, with a one-column separator between them
+    Vertical,
+}
+
+/// Share of a split, in percent, that each side keeps at least
+const MIN_RATIO: usize = 10;
+/// Percent a resize command moves a divider by
+const RESIZE_STEP: usize = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Pane(PaneId),
+    Split {
+        direction: SplitDirection,
+        // Percent of the space given to `first`
+        ratio: usize,
+        first: Box<Node>,
+        second: Box<Node>,
+    },
+}
+
+impl Node {
+    fn contains(&self, pane: PaneId) -> bool {
+        match self {
+            Node::Pane(id) => *id == pane,
+            Node::Split { first, second, .. } => first.contains(pane) || second.contains(pane),
+        }
+    }
+
+    fn first_pane(&self) -> PaneId {
+        match self {
+            Node::Pane(id) => *id,
+            Node::Split { first, .. } => first.first_pane(),
+        }
+    }
+
+    fn collect_panes(&self, panes: &mut Vec<PaneId>) {
+        match self {
+            Node::Pane(id) => panes.push(*id),
+            Node::Split { first, second, .. } => {
+                first.collect_panes(panes);
+                second.collect_panes(panes);
+            }
+        }
+    }
+
+    /// Replace the leaf `pane` with whatever `replace` makes of it
+    fn replace_pane(&mut self, pane: PaneId, replace: &mut dyn FnMut(Node) -> Node) -> bool {
+        match self {
+            Node::Pane(id) if *id == pane => {
+                let leaf = std::mem::replace(self, Node::Pane(pane));
+                *self = replace(leaf);
+                true
+            }
+            Node::Pane(_) => false,
+            Node::Split { first, second, .. } => first.replace_pane(pane, replace) || second.replace_pane(pane, replace),
+        }
+    }
+
+    /// Drop the leaf `pane`, letting its sibling take the split's place; returns the sibling
+    fn remove_pane(&mut self, pane: PaneId) -> Option<PaneId> {
+        let Node::Split { first, second, .. } = self else {
+            return None;
+        };
+        let sibling = if **first == Node::Pane(pane) {
+            std::mem::replace(second.as_mut(), Node::Pane(pane))
+        } else if **second == Node::Pane(pane) {
+            std::mem::replace(first.as_mut(), Node::Pane(pane))
+        } else {
+            return first.remove_pane(pane).or_else(|| second.remove_pane(pane));
+        };
+        let focus = sibling.first_pane();
+        *self = sibling;
+        Some(focus)
+    }
+
+    /// Move the divider of the innermost `direction` split holding `pane`
+    fn resize(&mut self, pane: PaneId, direction: SplitDirection, grow_first: bool) -> bool {
+        let Node::Split { direction: split, ratio, first, second } = self else {
+            return false;
+        };
+        if !first.contains(pane) && !second.contains(pane) {
+            return false;
+        }
+        if first.resize(pane, direction, grow_first) || second.resize(pane, direction, grow_first) {
+            return true;
+        }
+        if *split != direction {
+            return false;
+        }
+        let resized = if grow_first {
+            (*ratio + RESIZE_STEP).min(100 - MIN_RATIO)
+        } else {
+            ratio.saturating_sub(RESIZE_STEP).max(MIN_RATIO)
+        };
+        let changed = resized != *ratio;
+        *ratio = resized;
+        changed
+    }
+
+    fn arrange(&self, area: Rect, panes: &mut Vec<(PaneId, Rect)>, separators: &mut Vec<Rect>) {
+        match self {
+            Node::Pane(id) => panes.push((*id, area)),
+            Node::Split { direction: SplitDirection::Horizontal, ratio, first, second } => {
+                let top = area.height * ratio / 100;
+                first.arrange(Rect::new(area.x, area.y, area.width, top), panes, separators);
+                second.arrange(Rect::new(area.x, area.y + top, area.width, area.height - top), panes, separators);
+            }
+            Node::Split { direction: SplitDirection::Vertical, ratio, first, second } => {
+                let available = area.width.saturating_sub(1);
+                let left = available * ratio / 100;
+                first.arrange(Rect::new(area.x, area.y, left, area.height), panes, separators);
+                separators.push(Rect::new(area.x + left, area.y, area.width.min(1), area.height));
+                second.arrange(Rect::new(area.x + left + 1, area.y, available - left, area.height), panes, separators);
+            }
+        }
+    }
+}
+
+/// Tree of split panes filling the editor area
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    root: Node,
+    next_id: PaneId,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Layout {
+    /// A single pane taking the whole area
+    pub fn new() -> Self {
+        Self { root: Node::Pane(0), next_id: 1 }
+    }
+
+    /// Panes in reading order: top to bottom, left to right
+    pub fn panes(&self) -> Vec<PaneId> {
+        let mut panes = Vec::new();
+        self.root.collect_panes(&mut panes);
+        panes
+    }
+
+    pub fn pane_count(&self) -> usize {
+        self.panes().len()
+    }
+
+    /// Split `pane` in two; the new pane goes below or to the right of it
+    pub fn split(&mut self, pane: PaneId, direction: SplitDirection) -> Option<PaneId> {
+        let id = self.next_id;
+        let split = self.root.replace_pane(pane, &mut |leaf| Node::Split {
+            direction,
+            ratio: 50,
+            first: Box::new(leaf),
+            second: Box::new(Node::Pane(id)),
+        });
+        if split {
+            self.next_id += 1;
+        }
+        split.then_some(id)
+    }
+
+    /// Close `pane`, giving its space to its sibling.
+    ///
+    /// Returns the pane to focus next, or `None` if `pane` is the last one
+    /// or not in the layout.
+    pub fn remove(&mut self, pane: PaneId) -> Option<PaneId> {
+        self.root.remove_pane(pane)
+    }
+
+    /// Move the nearest divider beside `pane` one step towards `direction`.
+    ///
+    /// Returns `false` when there is no such divider or it is already at its limit.
+    pub fn resize(&mut self, pane: PaneId, direction: Direction) -> bool {
+        let (split, grow_first) = match direction {
+            Direction::Left => (SplitDirection::Vertical, false),
+            Direction::Right => (SplitDirection::Vertical, true),
+            Direction::Up => (SplitDirection::Horizontal, false),
+            Direction::Down => (SplitDirection::Horizontal, true),
+        };
+        self.root.resize(pane, split, grow_first)
+    }
+
+    /// Screen area of every pane within `area`
+    pub fn rects(&self, area: Rect) -> Vec<(PaneId, Rect)> {
+        let (mut panes, mut separators) = (Vec::new(), Vec::new());
+        self.root.arrange(area, &mut panes, &mut separators);
+        panes
+    }
+
+    /// Columns drawn between side-by-side panes
+    pub fn separators(&self, area: Rect) -> Vec<Rect> {
+        let (mut panes, mut separators) = (Vec::new(), Vec::new());
+        self.root.arrange(area, &mut panes, &mut separators);
+        separators
+    }
+
+    /// Pane after `pane` in reading order, wrapping around
+    pub fn next_pane(&self, pane: PaneId) -> PaneId {
+        let panes = self.panes();
+        let idx = panes.iter().position(|id| *id == pane).unwrap_or(0);
+        panes[(idx + 1) % panes.len()]
+    }
+
+    /// Closest pane on the `direction` side of `pane` that lines up with it
+    pub fn neighbor(&self, area: Rect, pane: PaneId, direction: Direction) -> Option<PaneId> {
+        let rects = self.rects(area);
+        let current = rects.iter().find(|(id, _)| *id == pane)?.1;
+        let overlaps_rows = |rect: &Rect| rect.y < current.bottom() && current.y < rect.bottom();
+        let overlaps_cols = |rect: &Rect| rect.x < current.right() && current.x < rect.right();
+
+        rects
+            .iter()
+            .filter_map(|(id, rect)| {
+                let distance = match direction {
+                    Direction::Left if overlaps_rows(rect) => current.x.checked_sub(rect.right())?,
+                    Direction::Right if overlaps_rows(rect) => rect.x.checked_sub(current.right())?,
+                    Direction::Up if overlaps_cols(rect) => current.y.checked_sub(rect.bottom())?,
+                    Direction::Down if overlaps_cols(rect) => rect.y.checked_sub(current.bottom())?,
+                    _ => return None,
+                };
+                // Prefer the pane lined up with the top-left corner of the current one
+                let offset = rect.x.abs_diff(current.x) + rect.y.abs_diff(current.y);
+                Some(((distance, offset), *id))
+            })
+            .min()
+            .map(|(_, id)| id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_and_arrange() {
+        let area = Rect::new(0, 0, 81, 20);
+        let mut layout = Layout::new();
+        let right = layout.split(0, SplitDirection::Vertical).unwrap();
+        let below = layout.split(right, SplitDirection::Horizontal).unwrap();
+        assert_eq!(layout.panes(), vec![0, right, below]);
+        assert_eq!(layout.split(42, SplitDirection::Vertical), None);
+
+        assert_eq!(
+            layout.rects(area),
+            vec![
+                (0, Rect::new(0, 0, 40, 20)),
+                (right, Rect::new(41, 0, 40, 10)),
+                (below, Rect::new(41, 10, 40, 10)),
+            ]
+        );
+        assert_eq!(layout.separators(area), vec![Rect::new(40, 0, 1, 20)]);
+        assert!(Rect::new(41, 10, 40, 10).contains(41, 19));
+        assert!(!Rect::new(41, 10, 40, 10).contains(40, 19));
+    }
+
+    #[test]
+    fn test_remove_and_focus_order() {
+        let mut layout = Layout::new();
+        let right = layout.split(0, SplitDirection::Vertical).unwrap();
+        let below = layout.split(right, SplitDirection::Horizontal).unwrap();
+        assert_eq!(layout.next_pane(below), 0);
+
+        // The sibling takes over the closed pane's space
+        assert_eq!(layout.remove(right), Some(below));
+        assert_eq!(layout.rects(Rect::new(0, 0, 81, 20))[1], (below, Rect::new(41, 0, 40, 20)));
+        assert_eq!(layout.remove(0), Some(below));
+        assert_eq!(layout.remove(below), None);
+        assert_eq!(layout.pane_count(), 1);
+    }
+
+    #[test]
+    fn test_neighbors_and_resize() {
+        let area = Rect::new(0, 0, 81, 20);
+        let mut layout = Layout::new();
+        let right = layout.split(0, SplitDirection::Vertical).unwrap();
+        let below = layout.split(right, SplitDirection::Horizontal).unwrap();
+
+        assert_eq!(layout.neighbor(area, 0, Direction::Right), Some(right));
+        assert_eq!(layout.neighbor(area, below, Direction::Left), Some(0));
+        assert_eq!(layout.neighbor(area, below, Direction::Up), Some(right));
+        assert_eq!(layout.neighbor(area, 0, Direction::Up), None);
+
+        // Resizing moves the nearest divider on the requested axis
+        assert!(layout.resize(below, Direction::Left));
+        assert_eq!(layout.rects(area)[0].1.width, 36);
+        assert!(!layout.resize(0, Direction::Down));
+        assert!(layout.resize(right, Direction::Down));
+        assert_eq!(layout.rects(area)[1].1.height, 11);
+        for _ in 0..20 {
+            layout.resize(0, Direction::Left);
+        }
+        assert_eq!(layout.rects(area)[0].1.width, 8);
+    }
+}
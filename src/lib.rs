@@ -1,10 +1,25 @@
 pub mod file_io;
 pub mod syntax;
+pub mod language;
 pub mod iocraft_file;
 pub mod iocraft_components;
 pub mod mouse;
 pub mod linter;
+pub mod lint_rules;
+pub mod lint_config;
+pub mod lint_suppressions;
 pub mod keys;
 pub mod render;
+pub mod wrap;
+pub mod text_metrics;
+pub mod layout;
 pub mod cursor;
 pub mod i18n;
+pub mod buffer;
+pub mod buffer_list;
+pub mod undo;
+pub mod clipboard;
+pub mod search;
+pub mod config;
+pub mod keymap;
+pub mod vim;
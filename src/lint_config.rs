@@ -0,0 +1,355 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use toml::{Table, Value};
+use crate::linter::{LintIssue, LintSeverity, RuleRegistry};
+use crate::lint_suppressions;
+
+/// Lint configuration file looked for next to a file and in every directory above it
+pub const CONFIG_FILE: &str = ".hello_tui_lint.toml";
+
+/// Project configuration whose `[lint]` section is used when there is no [`CONFIG_FILE`]
+pub const PROJECT_FILE: &str = ".hello_tui.toml";
+
+/// Rule name that problems with the configuration file itself are reported under
+pub const CONFIG_RULE: &str = "lint-config";
+
+/// Whether a rule runs, and at which severity
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleLevel {
+    Off,
+    On(LintSeverity),
+}
+
+/// How the configuration sets up one rule
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RuleSetting {
+    /// `None` keeps the registry's choice
+    pub level: Option<RuleLevel>,
+    /// Rule parameters, such as `max-length` for `long-line`
+    pub options: Table,
+}
+
+impl RuleSetting {
+    /// Apply `other` on top of this setting
+    fn merge(&mut self, other: &RuleSetting) {
+        if other.level.is_some() {
+            self.level = other.level.clone();
+        }
+        self.options.extend(other.options.clone());
+    }
+}
+
+/// Rule settings for the files matching some globs
+#[derive(Debug, Clone)]
+struct Override {
+    files: GlobSet,
+    rules: HashMap<String, RuleSetting>,
+}
+
+/// Per-project lint settings.
+///
+/// ```toml
+/// [rules]
+/// avoid-unwrap = "off"
+/// trailing-whitespace = "error"
+/// long-line = { severity = "info", max-length = 120 }
+///
+/// [[overrides]]
+/// files = ["tests/**", "*.test.js"]
+/// rules = { avoid-unwrap = "off" }
+/// ```
+///
+/// The same tables may sit under `[lint]` in a [`PROJECT_FILE`]. Globs are
+/// relative to the directory holding the configuration.
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    root: PathBuf,
+    text: String,
+    rules: HashMap<String, RuleSetting>,
+    overrides: Vec<Override>,
+    // Mistakes in the file, found while parsing
+    problems: Vec<LintIssue>,
+}
+
+impl LintConfig {
+    /// Read the configuration in `text`, which comes from the file at `path`.
+    ///
+    /// Parsing never fails: settings that make sense are kept and the rest
+    /// are recorded as [`problems`](Self::problems).
+    pub fn parse(text: &str, path: &Path) -> Self {
+        let mut config = Self {
+            root: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            text: text.to_string(),
+            ..Self::default()
+        };
+        let table = match text.parse::<Table>() {
+            Ok(table) => table,
+            Err(e) => {
+                config.problem(e.span().unwrap_or_default(), format!("Invalid lint configuration: {}", e.message()));
+                return config;
+            }
+        };
+
+        let is_project_file = path.file_name().is_some_and(|name| name == PROJECT_FILE);
+        let table = match table.get("lint") {
+            Some(Value::Table(lint)) if is_project_file => lint.clone(),
+            Some(_) if is_project_file => {
+                config.problem_at("lint", "`lint` must be a table".to_string());
+                return config;
+            }
+            _ if is_project_file => return config,
+            _ => table,
+        };
+
+        if let Some(rules) = table.get("rules") {
+            config.rules = config.parse_rules(rules, "rules");
+        }
+        match table.get("overrides") {
+            None => {}
+            Some(Value::Array(overrides)) => {
+                for entry in overrides {
+                    if let Some(entry) = config.parse_override(entry) {
+                        config.overrides.push(entry);
+                    }
+                }
+            }
+            Some(_) => config.problem_at("overrides", "`overrides` must be a list of tables".to_string()),
+        }
+        config
+    }
+
+    /// The configuration for files in `dir`: the nearest one in it or above it
+    pub fn discover(dir: &Path) -> Option<(PathBuf, Self)> {
+        let dir = std::path::absolute(dir).ok()?;
+        for dir in dir.ancestors() {
+            for name in [CONFIG_FILE, PROJECT_FILE] {
+                let path = dir.join(name);
+                let Ok(text) = std::fs::read_to_string(&path) else {
+                    continue;
+                };
+                // A project file without a `[lint]` section says nothing about linting
+                if name == PROJECT_FILE && !text.parse::<Table>().is_ok_and(|table| table.contains_key("lint")) {
+                    continue;
+                }
+                let config = Self::parse(&text, &path);
+                return Some((path, config));
+            }
+        }
+        None
+    }
+
+    /// Settings for every configured rule, with the overrides matching `file` applied
+    pub fn settings_for(&self, file: Option<&Path>) -> HashMap<String, RuleSetting> {
+        let mut settings = self.rules.clone();
+        let Some(file) = file.and_then(|file| std::path::absolute(file).ok()) else {
+            return settings;
+        };
+        let relative = file.strip_prefix(&self.root).unwrap_or(&file);
+        for entry in self.overrides.iter().filter(|entry| entry.files.is_match(relative)) {
+            for (id, setting) in &entry.rules {
+                settings.entry(id.clone()).or_default().merge(setting);
+            }
+        }
+        settings
+    }
+
+    /// Mistakes in the file, including rules `registry` does not know
+    pub fn problems(&self, registry: &RuleRegistry) -> Vec<LintIssue> {
+        let mut problems = self.problems.clone();
+        let mut ids: Vec<&String> = self.rules.keys().chain(self.overrides.iter().flat_map(|entry| entry.rules.keys())).collect();
+        ids.sort();
+        ids.dedup();
+        let known = |id: &str| registry.get(id).is_some() || id == lint_suppressions::UNUSED_RULE;
+        for id in ids.into_iter().filter(|id| !known(id)) {
+            let ((line, column), (end_line, end_column)) = span_position(&self.text, span_of(&self.text, id));
+            problems.push(LintIssue {
+                line,
+                column,
+                end_line,
+                end_column,
+                message: format!("Unknown lint rule '{}'", id),
+                severity: LintSeverity::Warning,
+                rule_name: CONFIG_RULE.to_string(),
+                fixes: Vec::new(),
+            });
+        }
+        problems.sort_by_key(|issue| (issue.line, issue.column));
+        problems
+    }
+
+    fn parse_rules(&mut self, rules: &Value, key: &str) -> HashMap<String, RuleSetting> {
+        let Value::Table(rules) = rules else {
+            self.problem_at(key, format!("`{}` must be a table of rule names", key));
+            return HashMap::new();
+        };
+        let mut settings = HashMap::new();
+        for (id, value) in rules {
+            let setting = match value {
+                Value::String(level) => self.parse_level(id, level).map(|level| RuleSetting { level: Some(level), ..RuleSetting::default() }),
+                Value::Table(table) => {
+                    let mut options = table.clone();
+                    match options.remove("severity") {
+                        None => Some(RuleSetting { level: None, options }),
+                        Some(Value::String(level)) => self.parse_level(id, &level).map(|level| RuleSetting { level: Some(level), options }),
+                        Some(_) => {
+                            self.problem_at(id, format!("Severity of '{}' must be a string", id));
+                            None
+                        }
+                    }
+                }
+                _ => {
+                    self.problem_at(id, format!("Rule '{}' must be set to a severity or a table", id));
+                    None
+                }
+            };
+            if let Some(setting) = setting {
+                settings.insert(id.clone(), setting);
+            }
+        }
+        settings
+    }
+
+    fn parse_level(&mut self, id: &str, level: &str) -> Option<RuleLevel> {
+        if level == "off" {
+            return Some(RuleLevel::Off);
+        }
+        let severity = LintSeverity::from_name(level).map(RuleLevel::On);
+        if severity.is_none() {
+            self.problem_at(id, format!("Unknown severity '{}' for '{}'; use off, error, warning, info or hint", level, id));
+        }
+        severity
+    }
+
+    fn parse_override(&mut self, entry: &Value) -> Option<Override> {
+        let Value::Table(entry) = entry else {
+            self.problem_at("overrides", "Each override must be a table".to_string());
+            return None;
+        };
+        let patterns: Option<Vec<&str>> = match entry.get("files") {
+            Some(Value::Array(files)) => files.iter().map(Value::as_str).collect(),
+            _ => None,
+        };
+        let Some(patterns) = patterns else {
+            self.problem_at("overrides", "Override needs `files`, a list of glob patterns".to_string());
+            return None;
+        };
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            match Glob::new(pattern) {
+                Ok(glob) => {
+                    builder.add(glob);
+                }
+                Err(e) => self.problem_at(pattern, format!("Invalid glob '{}': {}", pattern, e.kind())),
+            }
+        }
+        let files = builder.build().ok()?;
+        let rules = entry.get("rules").map(|rules| self.parse_rules(rules, "rules")).unwrap_or_default();
+        Some(Override { files, rules })
+    }
+
+    /// Report a problem with the text at byte range `span`
+    fn problem(&mut self, span: Range<usize>, message: String) {
+        let ((line, column), (end_line, end_column)) = span_position(&self.text, span);
+        self.problems.push(LintIssue {
+            line,
+            column,
+            end_line,
+            end_column,
+            message,
+            severity: LintSeverity::Error,
+            rule_name: CONFIG_RULE.to_string(),
+            fixes: Vec::new(),
+        });
+    }
+
+    /// Report a problem at the first mention of `needle`
+    fn problem_at(&mut self, needle: &str, message: String) {
+        self.problem(span_of(&self.text, needle), message);
+    }
+}
+
+/// Whether `path` names a file the linter reads its configuration from
+pub fn is_config_file(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == CONFIG_FILE || name == PROJECT_FILE)
+}
+
+/// 1-based line and column of a byte offset
+fn position_at(text: &str, offset: usize) -> (usize, usize) {
+    let mut end = offset.min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    let before = &text[..end];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+    (line, column)
+}
+
+/// 1-based positions where a byte range starts and ends
+fn span_position(text: &str, span: Range<usize>) -> ((usize, usize), (usize, usize)) {
+    (position_at(text, span.start), position_at(text, span.end))
+}
+
+/// Byte range where `needle` first appears, or the start of the text
+fn span_of(text: &str, needle: &str) -> Range<usize> {
+    text.find(needle).map_or(0..0, |offset| offset..offset + needle.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"[rules]
+avoid-unwrap = "off"
+trailing-whitespace = "error"
+long-line = { max-length = 120 }
+
+[[overrides]]
+files = ["tests/**"]
+rules = { avoid-unwrap = "warning", long-line = { severity = "hint" } }
+"#;
+
+    #[test]
+    fn test_settings_and_overrides() {
+        let root = std::env::temp_dir().join("project");
+        let config = LintConfig::parse(CONFIG, &root.join(CONFIG_FILE));
+        assert!(config.problems(&RuleRegistry::with_builtin_rules()).is_empty());
+
+        let settings = config.settings_for(Some(&root.join("src/main.rs")));
+        assert_eq!(settings["avoid-unwrap"].level, Some(RuleLevel::Off));
+        assert_eq!(settings["trailing-whitespace"].level, Some(RuleLevel::On(LintSeverity::Error)));
+        assert_eq!(settings["long-line"].level, None);
+        assert_eq!(settings["long-line"].options.get("max-length"), Some(&Value::Integer(120)));
+
+        let settings = config.settings_for(Some(&root.join("tests/parse.rs")));
+        assert_eq!(settings["avoid-unwrap"].level, Some(RuleLevel::On(LintSeverity::Warning)));
+        assert_eq!(settings["long-line"].level, Some(RuleLevel::On(LintSeverity::Hint)));
+        // Options set outside the override are kept
+        assert_eq!(settings["long-line"].options.get("max-length"), Some(&Value::Integer(120)));
+    }
+
+    #[test]
+    fn test_problems() {
+        let path = Path::new("/project").join(CONFIG_FILE);
+        let text = "[rules]\navoid-unwrap = \"loud\"\nno-such-rule = \"off\"\nlong-line = 3\n\n[[overrides]]\nfiles = [\"a/[\"]\n";
+        let problems = LintConfig::parse(text, &path).problems(&RuleRegistry::with_builtin_rules());
+        let found: Vec<(usize, &str)> = problems.iter().map(|issue| (issue.line, issue.message.as_str())).collect();
+        assert_eq!(found.len(), 4);
+        assert!(found[0].0 == 2 && found[0].1.contains("Unknown severity 'loud'"));
+        assert!(found[1].0 == 3 && found[1].1.contains("Unknown lint rule 'no-such-rule'"));
+        assert!(found[2].0 == 4 && found[2].1.contains("'long-line'"));
+        assert!(found[3].0 == 7 && found[3].1.contains("Invalid glob"));
+        assert!(problems.iter().all(|issue| issue.rule_name == CONFIG_RULE));
+
+        let broken = LintConfig::parse("[rules\n", &path).problems(&RuleRegistry::new());
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].severity, LintSeverity::Error);
+
+        // A project file only counts through its `[lint]` section
+        let project = Path::new("/project").join(PROJECT_FILE);
+        let config = LintConfig::parse("name = \"x\"\n[lint.rules]\navoid-unwrap = \"off\"\n", &project);
+        assert_eq!(config.settings_for(None)["avoid-unwrap"].level, Some(RuleLevel::Off));
+    }
+}
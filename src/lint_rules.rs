@@ -0,0 +1,331 @@
+use std::ops::Range;
+use std::sync::LazyLock;
+use regex::Regex;
+use crate::linter::{Finding, FnRule, LintContext, LintRule, LintSeverity, TextEdit};
+
+const RUST: &[&str] = &["Rust"];
+const JAVASCRIPT: &[&str] = &["JavaScript", "TypeScript"];
+const PYTHON: &[&str] = &["Python"];
+const JSON: &[&str] = &["JSON"];
+
+/// Compile one of the patterns below, which are known to be valid
+fn pattern(pattern: &str) -> Regex {
+    Regex::new(pattern).expect("built-in lint patterns are valid")
+}
+
+static UNWRAP_CALL: LazyLock<Regex> = LazyLock::new(|| pattern(r"\.unwrap\(\)"));
+static RUST_STATEMENT: LazyLock<Regex> =
+    LazyLock::new(|| pattern(r"^\s*(println!|print!|return\s+[^;]+|let\s+.*=\s*[^;]+)\s*$"));
+static RUST_LET: LazyLock<Regex> = LazyLock::new(|| pattern(r"let\s+_*([a-zA-Z_][a-zA-Z0-9_]*)\s*="));
+static PANIC_CALL: LazyLock<Regex> = LazyLock::new(|| pattern(r"panic!\s*\("));
+static CONSOLE_LOG: LazyLock<Regex> = LazyLock::new(|| pattern(r"console\.log\s*\("));
+static LOOSE_EQUALITY: LazyLock<Regex> = LazyLock::new(|| pattern(r"\s==\s"));
+static VAR_KEYWORD: LazyLock<Regex> = LazyLock::new(|| pattern(r"\bvar\s+"));
+static DEBUGGER: LazyLock<Regex> = LazyLock::new(|| pattern(r"\bdebugger\s*;?"));
+static JS_DECLARATION: LazyLock<Regex> = LazyLock::new(|| pattern(r"(let|const|var)\s+([a-zA-Z_$][a-zA-Z0-9_$]*)\s*="));
+static DOUBLE_NEGATION: LazyLock<Regex> = LazyLock::new(|| pattern(r"!!\s*\w"));
+// A double negation and the operand it applies to, e.g. `!!user.name` or `!!find(x)`
+static NEGATED_OPERAND: LazyLock<Regex> = LazyLock::new(|| pattern(r"^!!\s*([\w$.]+(?:\([^()]*\))?)"));
+static EMPTY_BLOCK: LazyLock<Regex> = LazyLock::new(|| pattern(r"\{\s*\}"));
+static ANONYMOUS_FUNCTION: LazyLock<Regex> = LazyLock::new(|| pattern(r"function\s*\(\s*\)\s*\{"));
+static JS_STATEMENT: LazyLock<Regex> = LazyLock::new(|| pattern(r"^\s*[a-zA-Z_$].*[^;{}\s]\s*$"));
+static COMMA_WITHOUT_SPACE: LazyLock<Regex> = LazyLock::new(|| pattern(r",[^\s]"));
+static PRINT_CALL: LazyLock<Regex> = LazyLock::new(|| pattern(r"\bprint\s*\("));
+
+/// Every rule that ships with the editor
+pub fn builtin_rules() -> Vec<Box<dyn LintRule>> {
+    let rules = [
+        // Any language
+        FnRule::new("trailing-whitespace", &[], LintSeverity::Info, trailing_whitespace),
+        FnRule::new("long-line", &[], LintSeverity::Warning, long_line),
+        FnRule::new("mixed-indentation", &[], LintSeverity::Warning, mixed_indentation),
+        // Rust
+        FnRule::new("avoid-unwrap", RUST, LintSeverity::Warning, avoid_unwrap),
+        FnRule::new("missing-semicolon", RUST, LintSeverity::Error, missing_semicolon),
+        FnRule::new("unused-variable", RUST, LintSeverity::Info, unused_variable),
+        FnRule::new("avoid-panic", RUST, LintSeverity::Warning, avoid_panic),
+        // JavaScript and TypeScript, after Biome's rules
+        FnRule::new("biome/no-console-log", JAVASCRIPT, LintSeverity::Warning, no_console_log),
+        FnRule::new("biome/use-strict-equality", JAVASCRIPT, LintSeverity::Error, use_strict_equality),
+        FnRule::new("biome/no-var", JAVASCRIPT, LintSeverity::Error, no_var),
+        FnRule::new("biome/no-debugger", JAVASCRIPT, LintSeverity::Error, no_debugger),
+        FnRule::new("biome/no-unused-variables", JAVASCRIPT, LintSeverity::Warning, no_unused_variables),
+        FnRule::new("biome/no-double-negation", JAVASCRIPT, LintSeverity::Info, no_double_negation),
+        FnRule::new("biome/no-empty-block", JAVASCRIPT, LintSeverity::Warning, no_empty_block),
+        FnRule::new("biome/function-spacing", JAVASCRIPT, LintSeverity::Info, function_spacing),
+        FnRule::new("biome/use-semicolons", JAVASCRIPT, LintSeverity::Warning, use_semicolons),
+        // Python
+        FnRule::new("pep8-line-length", PYTHON, LintSeverity::Info, pep8_line_length),
+        FnRule::new("pep8-indentation", PYTHON, LintSeverity::Warning, pep8_indentation),
+        FnRule::new("pep8-comma-spacing", PYTHON, LintSeverity::Info, pep8_comma_spacing),
+        FnRule::new("prefer-logging", PYTHON, LintSeverity::Hint, prefer_logging),
+        // JSON
+        FnRule::new("no-trailing-comma", JSON, LintSeverity::Error, no_trailing_comma),
+        FnRule::new("json-syntax", JSON, LintSeverity::Error, json_syntax),
+    ];
+    rules.into_iter().map(|rule| Box::new(rule) as Box<dyn LintRule>).collect()
+}
+
+/// Lines of the content with their 1-based numbers
+fn numbered_lines<'a>(context: &LintContext<'a>) -> impl Iterator<Item = (usize, &'a str)> {
+    (1..).zip(context.content.lines())
+}
+
+/// Char column of a byte offset within a line
+fn char_col(text: &str, byte: usize) -> usize {
+    text[..byte].chars().count()
+}
+
+/// A finding covering the byte range `bytes` of a line
+fn finding_at(line: usize, text: &str, bytes: Range<usize>, message: impl Into<String>) -> Finding {
+    Finding::new(line, char_col(text, bytes.start) + 1, message).until(line, char_col(text, bytes.end) + 1)
+}
+
+/// A finding covering a line's leading whitespace
+fn indentation(line: usize, text: &str, message: &str) -> Finding {
+    finding_at(line, text, 0..text.len() - text.trim_start().len(), message)
+}
+
+/// A finding on the last char of a statement that should end in a semicolon
+fn statement_end(line: usize, text: &str, message: &str) -> Finding {
+    let end = text.trim_end().len();
+    let last = text[..end].char_indices().next_back().map_or(0, |(idx, _)| idx);
+    finding_at(line, text, last..end, message)
+}
+
+/// The chars of a line past `max_length`
+fn overflow(line: usize, text: &str, max_length: usize, message: String) -> Finding {
+    Finding::new(line, max_length + 1, message).until(line, text.chars().count() + 1)
+}
+
+/// A finding at the first match of `regex` on every line
+fn first_match_per_line(context: &LintContext, regex: &Regex, message: &str) -> Vec<Finding> {
+    numbered_lines(context)
+        .filter_map(|(line, text)| regex.find(text).map(|mat| finding_at(line, text, mat.range(), message)))
+        .collect()
+}
+
+fn trailing_whitespace(context: &LintContext) -> Vec<Finding> {
+    numbered_lines(context)
+        .filter(|(_, text)| text.ends_with(' ') || text.ends_with('\t'))
+        .map(|(line, text)| {
+            let (row, trimmed) = (line - 1, text.trim_end());
+            finding_at(line, text, trimmed.len()..text.len(), "Trailing whitespace").with_fix(
+                "Remove trailing whitespace",
+                vec![TextEdit::new((row, trimmed.chars().count()), (row, text.chars().count()), "")],
+            )
+        })
+        .collect()
+}
+
+fn long_line(context: &LintContext) -> Vec<Finding> {
+    let max_length = context.usize_option("max-length").unwrap_or(100);
+    numbered_lines(context)
+        .filter(|(_, text)| text.chars().count() > max_length)
+        .map(|(line, text)| overflow(line, text, max_length, format!("Line too long (>{} characters)", max_length)))
+        .collect()
+}
+
+fn mixed_indentation(context: &LintContext) -> Vec<Finding> {
+    numbered_lines(context)
+        .filter(|(_, text)| text.starts_with(' ') && text.contains('\t'))
+        .map(|(line, text)| indentation(line, text, "Mixed indentation (tabs and spaces)"))
+        .collect()
+}
+
+fn avoid_unwrap(context: &LintContext) -> Vec<Finding> {
+    numbered_lines(context)
+        .flat_map(|(line, text)| {
+            UNWRAP_CALL.find_iter(text).map(move |mat| {
+                finding_at(line, text, mat.range(), "Avoid using .unwrap(), consider using .expect() with a descriptive message or proper error handling")
+            })
+        })
+        .collect()
+}
+
+fn missing_semicolon(context: &LintContext) -> Vec<Finding> {
+    numbered_lines(context)
+        .filter(|(_, text)| {
+            RUST_STATEMENT.is_match(text) && !text.trim().ends_with('{') && !text.trim().ends_with(',')
+        })
+        .map(|(line, text)| statement_end(line, text, "Missing semicolon"))
+        .collect()
+}
+
+fn unused_variable(context: &LintContext) -> Vec<Finding> {
+    numbered_lines(context)
+        .filter_map(|(line, text)| {
+            let var_name = RUST_LET.captures(text)?.get(1)?;
+            let var = var_name.as_str();
+            // Simple check: if variable name starts with underscore, it's intentionally unused
+            (!var.starts_with('_') && !context.content.contains(var)).then(|| {
+                finding_at(line, text, var_name.range(), format!("Variable '{}' may be unused. Consider prefixing with '_' if intentional", var))
+            })
+        })
+        .collect()
+}
+
+fn avoid_panic(context: &LintContext) -> Vec<Finding> {
+    first_match_per_line(context, &PANIC_CALL, "Consider using Result<T, E> or expect() instead of panic!()")
+}
+
+fn no_console_log(context: &LintContext) -> Vec<Finding> {
+    first_match_per_line(context, &CONSOLE_LOG, "Avoid console.log in production code (biome-style: no-console-log)")
+}
+
+fn use_strict_equality(context: &LintContext) -> Vec<Finding> {
+    numbered_lines(context)
+        .filter(|(_, text)| !text.contains("==="))
+        .filter_map(|(line, text)| {
+            let mat = LOOSE_EQUALITY.find(text)?;
+            let operator_start = mat.start() + mat.as_str().find("==")?;
+            let operator = char_col(text, operator_start);
+            let finding = finding_at(line, text, operator_start..operator_start + 2, "Use '===' instead of '==' for strict equality (biome-style: use-strict-equality)");
+            Some(finding.with_fix("Use '==='", vec![TextEdit::new((line - 1, operator), (line - 1, operator + 2), "===")]))
+        })
+        .collect()
+}
+
+fn no_var(context: &LintContext) -> Vec<Finding> {
+    numbered_lines(context)
+        .filter_map(|(line, text)| {
+            let mat = VAR_KEYWORD.find(text)?;
+            let (row, start) = (line - 1, char_col(text, mat.start()));
+            let replace = |keyword: &str| vec![TextEdit::new((row, start), (row, start + 3), keyword)];
+            let finding = finding_at(line, text, mat.start()..mat.start() + 3, "Use 'let' or 'const' instead of 'var' (biome-style: no-var)");
+            Some(finding
+                .with_fix("Replace 'var' with 'let'", replace("let"))
+                .with_fix("Replace 'var' with 'const'", replace("const")))
+        })
+        .collect()
+}
+
+fn no_debugger(context: &LintContext) -> Vec<Finding> {
+    let line_count = context.content.lines().count();
+    numbered_lines(context)
+        .filter_map(|(line, text)| {
+            let mat = DEBUGGER.find(text)?;
+            let row = line - 1;
+            // A line holding just the statement goes away entirely
+            let edit = if text.trim() == mat.as_str().trim() && (line < line_count || context.content.ends_with('\n')) {
+                TextEdit::new((row, 0), (row + 1, 0), "")
+            } else if text.trim() == mat.as_str().trim() {
+                TextEdit::new((row, 0), (row, text.chars().count()), "")
+            } else {
+                TextEdit::new((row, char_col(text, mat.start())), (row, char_col(text, mat.end())), "")
+            };
+            let statement = mat.start()..mat.start() + mat.as_str().trim_end().len();
+            let finding = finding_at(line, text, statement, "Remove debugger statements (biome-style: no-debugger)");
+            Some(finding.with_fix("Remove the debugger statement", vec![edit]))
+        })
+        .collect()
+}
+
+fn no_unused_variables(context: &LintContext) -> Vec<Finding> {
+    numbered_lines(context)
+        .filter_map(|(line, text)| {
+            let var_name = JS_DECLARATION.captures(text)?.get(2)?;
+            let var = var_name.as_str();
+            let rest = context.content.get(line - 1..).unwrap_or_default();
+            (!var.starts_with('_') && !rest.contains(var)).then(|| {
+                finding_at(line, text, var_name.range(), format!("Variable '{}' is declared but never used (biome-style: no-unused-variables)", var))
+            })
+        })
+        .collect()
+}
+
+fn no_double_negation(context: &LintContext) -> Vec<Finding> {
+    numbered_lines(context)
+        .filter_map(|(line, text)| {
+            let mat = DOUBLE_NEGATION.find(text)?;
+            const MESSAGE: &str = "Use Boolean() instead of double negation (!!) (biome-style: no-double-negation)";
+            // Only operands whose extent is clear get a fix
+            let Some(negation) = NEGATED_OPERAND.captures(&text[mat.start()..]) else {
+                return Some(finding_at(line, text, mat.start()..mat.start() + 2, MESSAGE));
+            };
+            let (whole, operand) = (negation.get(0)?, negation.get(1)?);
+            let finding = finding_at(line, text, mat.start()..mat.start() + whole.end(), MESSAGE);
+            let row = line - 1;
+            let start = char_col(text, mat.start());
+            let end = char_col(text, mat.start() + whole.end());
+            let edit = TextEdit::new((row, start), (row, end), format!("Boolean({})", operand.as_str()));
+            Some(finding.with_fix("Use Boolean()", vec![edit]))
+        })
+        .collect()
+}
+
+fn no_empty_block(context: &LintContext) -> Vec<Finding> {
+    first_match_per_line(context, &EMPTY_BLOCK, "Empty block statement (biome-style: no-empty-block)")
+}
+
+fn function_spacing(context: &LintContext) -> Vec<Finding> {
+    numbered_lines(context)
+        .filter(|(_, text)| !text.contains("function ()"))
+        .filter_map(|(line, text)| {
+            let mat = ANONYMOUS_FUNCTION.find(text)?;
+            Some(finding_at(line, text, mat.range(), "Consider proper spacing around function declarations (biome-style: formatting)"))
+        })
+        .collect()
+}
+
+fn use_semicolons(context: &LintContext) -> Vec<Finding> {
+    numbered_lines(context)
+        .filter(|(_, text)| {
+            let trimmed = text.trim();
+            JS_STATEMENT.is_match(text) && !trimmed.ends_with(',') && !trimmed.starts_with("//") && !trimmed.starts_with("/*")
+        })
+        .map(|(line, text)| statement_end(line, text, "Missing semicolon (biome-style: use-semicolons)"))
+        .collect()
+}
+
+fn pep8_line_length(context: &LintContext) -> Vec<Finding> {
+    let max_length = context.usize_option("max-length").unwrap_or(79);
+    numbered_lines(context)
+        .filter(|(_, text)| text.chars().count() > max_length)
+        .map(|(line, text)| overflow(line, text, max_length, format!("Line too long (PEP 8 recommends ≤{} characters)", max_length)))
+        .collect()
+}
+
+fn pep8_indentation(context: &LintContext) -> Vec<Finding> {
+    numbered_lines(context)
+        .filter(|(_, text)| {
+            let leading_spaces = text.len() - text.trim_start().len();
+            text.starts_with(' ') && !text.starts_with("    ") && leading_spaces % 4 != 0
+        })
+        .map(|(line, text)| indentation(line, text, "PEP 8: Use 4 spaces per indentation level"))
+        .collect()
+}
+
+fn pep8_comma_spacing(context: &LintContext) -> Vec<Finding> {
+    numbered_lines(context)
+        .filter_map(|(line, text)| {
+            let mat = COMMA_WITHOUT_SPACE.find(text)?;
+            Some(finding_at(line, text, mat.start()..mat.start() + 1, "PEP 8: Missing whitespace after ','"))
+        })
+        .collect()
+}
+
+fn prefer_logging(context: &LintContext) -> Vec<Finding> {
+    first_match_per_line(context, &PRINT_CALL, "Consider using logging instead of print for production code")
+}
+
+fn no_trailing_comma(context: &LintContext) -> Vec<Finding> {
+    // Style checks only make sense once the JSON parses
+    if serde_json::from_str::<serde_json::Value>(context.content).is_err() {
+        return Vec::new();
+    }
+    numbered_lines(context)
+        .filter(|(_, text)| text.trim().ends_with(',') && (text.contains('}') || text.contains(']')))
+        .filter_map(|(line, text)| {
+            let comma = text.rfind(',')?;
+            Some(finding_at(line, text, comma..comma + 1, "Trailing comma not allowed in JSON"))
+        })
+        .collect()
+}
+
+fn json_syntax(context: &LintContext) -> Vec<Finding> {
+    match serde_json::from_str::<serde_json::Value>(context.content) {
+        Ok(_) => Vec::new(),
+        Err(e) => vec![Finding::new(e.line().max(1), e.column().max(1), format!("JSON syntax error: {}", e))],
+    }
+}
@@ -0,0 +1,235 @@
+use std::ops::Range;
+use crate::linter::{LintIssue, LintSeverity};
+
+/// Rule name unused suppression comments are reported under
+pub const UNUSED_RULE: &str = "unused-suppression";
+
+/// Comment directives, longest first so prefixes do not shadow them
+const DIRECTIVES: &[(&str, Kind)] = &[
+    ("hello-lint-disable-next-line", Kind::NextLine),
+    ("hello-lint-disable-line", Kind::Line),
+    ("hello-lint-disable-file", Kind::File),
+    ("hello-lint-disable", Kind::Disable),
+    ("hello-lint-enable", Kind::Enable),
+];
+
+/// Text a directive has to follow on its line, so it only counts inside a comment
+const COMMENT_STARTS: &[&str] = &["//", "/*", "#", "--", "<!--"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    NextLine,
+    Line,
+    File,
+    Disable,
+    Enable,
+}
+
+/// A directive found in a comment; lines and columns are 1-based
+#[derive(Debug)]
+struct Directive {
+    kind: Kind,
+    line: usize,
+    column: usize,
+    // Just past the directive and its rule names
+    end_column: usize,
+    // Empty means every rule
+    rules: Vec<String>,
+}
+
+/// One rule (or every rule, for `None`) silenced over some lines by a directive
+#[derive(Debug)]
+struct Suppression {
+    rule: Option<String>,
+    lines: Range<usize>,
+    line: usize,
+    column: usize,
+    end_column: usize,
+    used: bool,
+}
+
+/// Suppression comments in a file.
+///
+/// ```text
+/// x.unwrap(); // hello-lint-disable-line avoid-unwrap
+/// // hello-lint-disable-next-line avoid-unwrap, avoid-panic
+/// # hello-lint-disable prefer-logging
+/// # hello-lint-enable prefer-logging
+/// // hello-lint-disable-file
+/// ```
+///
+/// Without rule names a directive silences every rule. Text after `--`
+/// is a free-form reason.
+#[derive(Debug, Default)]
+pub struct Suppressions {
+    suppressions: Vec<Suppression>,
+}
+
+impl Suppressions {
+    pub fn parse(content: &str) -> Self {
+        let directives: Vec<Directive> = (1..).zip(content.lines()).filter_map(|(line, text)| parse_directive(line, text)).collect();
+        let mut suppressions = Vec::new();
+        // Open `hello-lint-disable` blocks, by index into `suppressions`
+        let mut open: Vec<usize> = Vec::new();
+
+        for directive in &directives {
+            let lines = match directive.kind {
+                Kind::NextLine => directive.line + 1..directive.line + 2,
+                Kind::Line => directive.line..directive.line + 1,
+                Kind::File => 1..usize::MAX,
+                Kind::Disable => directive.line + 1..usize::MAX,
+                Kind::Enable => {
+                    open.retain(|&idx| {
+                        let suppression: &mut Suppression = &mut suppressions[idx];
+                        let closes = directive.rules.is_empty()
+                            || suppression.rule.as_ref().is_some_and(|rule| directive.rules.contains(rule));
+                        if closes {
+                            suppression.lines.end = directive.line;
+                        }
+                        !closes
+                    });
+                    continue;
+                }
+            };
+            let rules: Vec<Option<String>> = match directive.rules.is_empty() {
+                true => vec![None],
+                false => directive.rules.iter().cloned().map(Some).collect(),
+            };
+            for rule in rules {
+                if directive.kind == Kind::Disable {
+                    open.push(suppressions.len());
+                }
+                suppressions.push(Suppression {
+                    rule,
+                    lines: lines.clone(),
+                    line: directive.line,
+                    column: directive.column,
+                    end_column: directive.end_column,
+                    used: false,
+                });
+            }
+        }
+        Self { suppressions }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.suppressions.is_empty()
+    }
+
+    /// Drop the issues a directive silences, then report the directives that silenced nothing
+    pub fn apply(mut self, issues: Vec<LintIssue>) -> Vec<LintIssue> {
+        let mut kept: Vec<LintIssue> = issues
+            .into_iter()
+            .filter(|issue| {
+                let mut suppressed = false;
+                for suppression in &mut self.suppressions {
+                    let rule_matches = suppression.rule.as_ref().is_none_or(|rule| *rule == issue.rule_name);
+                    if rule_matches && suppression.lines.contains(&issue.line) {
+                        suppression.used = true;
+                        suppressed = true;
+                    }
+                }
+                !suppressed
+            })
+            .collect();
+
+        kept.extend(self.suppressions.into_iter().filter(|suppression| !suppression.used).map(|suppression| LintIssue {
+            line: suppression.line,
+            column: suppression.column,
+            end_line: suppression.line,
+            end_column: suppression.end_column,
+            message: match &suppression.rule {
+                Some(rule) => format!("Unused suppression of '{}'", rule),
+                None => "Unused suppression; nothing to silence here".to_string(),
+            },
+            severity: LintSeverity::Hint,
+            rule_name: UNUSED_RULE.to_string(),
+            fixes: Vec::new(),
+        }));
+        kept.sort_by_key(|issue| issue.line);
+        kept
+    }
+}
+
+/// The directive in a comment on `text`, if there is one
+fn parse_directive(line: usize, text: &str) -> Option<Directive> {
+    let start = text.find("hello-lint-")?;
+    if !COMMENT_STARTS.iter().any(|comment| text[..start].contains(comment)) {
+        return None;
+    }
+    let rest = &text[start..];
+    let (name, kind) = DIRECTIVES.iter().find(|(name, _)| {
+        rest.strip_prefix(name).is_some_and(|after| after.is_empty() || after.starts_with(char::is_whitespace))
+    })?;
+
+    // Rule names run up to a `--` reason or the end of a block comment
+    let arguments = &rest[name.len()..];
+    let arguments = ["--", "*/", "-->"]
+        .iter()
+        .filter_map(|end| arguments.find(end))
+        .min()
+        .map_or(arguments, |end| &arguments[..end]);
+    let rules = arguments
+        .split(|ch: char| ch == ',' || ch.is_whitespace())
+        .filter(|rule| !rule.is_empty())
+        .map(str::to_string)
+        .collect();
+    let column = text[..start].chars().count() + 1;
+    let end_column = column + rest[..name.len() + arguments.len()].trim_end().chars().count();
+    Some(Directive { kind: *kind, line, column, end_column, rules })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(line: usize, rule: &str) -> LintIssue {
+        LintIssue { line, column: 1, end_line: line, end_column: 2, message: String::new(), severity: LintSeverity::Warning, rule_name: rule.to_string(), fixes: Vec::new() }
+    }
+
+    fn remaining(content: &str, issues: Vec<LintIssue>) -> Vec<(usize, String)> {
+        Suppressions::parse(content)
+            .apply(issues)
+            .into_iter()
+            .map(|issue| (issue.line, issue.rule_name))
+            .collect()
+    }
+
+    #[test]
+    fn test_line_directives() {
+        let content = "// hello-lint-disable-next-line avoid-unwrap -- checked above\nx.unwrap();\ny.unwrap(); // hello-lint-disable-line\nz.unwrap();\n";
+        let issues = vec![issue(2, "avoid-unwrap"), issue(2, "long-line"), issue(3, "avoid-unwrap"), issue(4, "avoid-unwrap")];
+        assert_eq!(remaining(content, issues), [(2, "long-line".to_string()), (4, "avoid-unwrap".to_string())]);
+
+        // Only inside comments
+        assert!(Suppressions::parse("let s = \"hello-lint-disable-file\";").is_empty());
+        let directive = parse_directive(1, "  /* hello-lint-disable biome/no-var, no-debugger */").unwrap();
+        assert_eq!((directive.kind, directive.column, directive.end_column), (Kind::Disable, 6, 50));
+        assert_eq!(directive.rules, ["biome/no-var", "no-debugger"]);
+    }
+
+    #[test]
+    fn test_blocks_and_file() {
+        let content = "# hello-lint-disable prefer-logging\nprint(1)\n# hello-lint-enable prefer-logging\nprint(2)\n";
+        let issues = vec![issue(2, "prefer-logging"), issue(4, "prefer-logging")];
+        assert_eq!(remaining(content, issues), [(4, "prefer-logging".to_string())]);
+
+        // An unclosed block runs to the end of the file
+        let content = "a\n# hello-lint-disable\nb\nc\n";
+        assert!(remaining(content, vec![issue(3, "long-line"), issue(4, "pep8-indentation")]).is_empty());
+
+        let content = "x\n// hello-lint-disable-file avoid-unwrap\n";
+        assert!(remaining(content, vec![issue(1, "avoid-unwrap")]).is_empty());
+    }
+
+    #[test]
+    fn test_unused_suppressions() {
+        let content = "// hello-lint-disable-next-line avoid-unwrap, avoid-panic\nx.unwrap();\n";
+        let left = Suppressions::parse(content).apply(vec![issue(2, "avoid-unwrap")]);
+        assert_eq!(left.len(), 1);
+        assert_eq!((left[0].line, left[0].column), (1, 4));
+        assert_eq!(left[0].severity, LintSeverity::Hint);
+        assert_eq!(left[0].rule_name, UNUSED_RULE);
+        assert!(left[0].message.contains("avoid-panic"));
+    }
+}
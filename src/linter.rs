@@ -1,8 +1,13 @@
-use regex::Regex;
-use serde_json;
-use std::collections::HashMap;
-
-#[derive(Debug, Clone, PartialEq)]
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use crate::lint_config::{self, LintConfig, RuleLevel};
+use crate::lint_rules;
+use crate::lint_suppressions::{self, Suppressions};
+
+/// How bad an issue is, the most severe first
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum LintSeverity {
     Error,
     Warning,
@@ -10,433 +15,426 @@ pub enum LintSeverity {
     Hint,
 }
 
+impl LintSeverity {
+    /// Severity named in a configuration file: `error`, `warning`, `info` or `hint`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "error" => Some(Self::Error),
+            "warning" => Some(Self::Warning),
+            "info" => Some(Self::Info),
+            "hint" => Some(Self::Hint),
+            _ => None,
+        }
+    }
+}
+
+/// A problem found in the text.
+///
+/// It covers the text from `line`, `column` up to `end_line`, `end_column`;
+/// all are 1-based, columns count chars and the end is exclusive.
 #[derive(Debug, Clone)]
 pub struct LintIssue {
     pub line: usize,
     pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
     pub message: String,
     pub severity: LintSeverity,
     pub rule_name: String,
+    /// Ways to resolve the issue automatically, the preferred one first
+    pub fixes: Vec<LintFix>,
 }
 
-pub struct Linter {
-    enabled: bool,
-    regex_cache: HashMap<String, Regex>,
-}
-
-impl Linter {
-    pub fn new() -> Self {
-        Self {
-            enabled: true,
-            regex_cache: HashMap::new(),
+impl LintIssue {
+    /// Char columns (0-based) the issue covers on buffer row `row`, whose line is `line_len` chars long.
+    ///
+    /// An issue past the end of its line, like a missing semicolon, covers the last char.
+    pub fn columns_on(&self, row: usize, line_len: usize) -> Option<Range<usize>> {
+        let line = row + 1;
+        // A span ending at the start of a line leaves that line alone
+        if line < self.line || line > self.end_line || (line > self.line && line == self.end_line && self.end_column <= 1) {
+            return None;
         }
+        let start = if line == self.line { self.column.saturating_sub(1) } else { 0 };
+        let end = if line == self.end_line { self.end_column.saturating_sub(1) } else { line_len };
+        let start = start.min(line_len.checked_sub(1)?);
+        Some(start..end.clamp(start + 1, line_len))
     }
+}
 
-    pub fn is_enabled(&self) -> bool {
-        self.enabled
-    }
+/// The most severe issue at buffer position `(row, col)`, including one just before it
+pub fn issue_at(issues: &[LintIssue], row: usize, col: usize, line_len: usize) -> Option<&LintIssue> {
+    issues
+        .iter()
+        .filter(|issue| issue.columns_on(row, line_len).is_some_and(|columns| columns.contains(&col) || columns.end == col))
+        .min_by_key(|issue| &issue.severity)
+}
 
-    pub fn toggle(&mut self) {
-        self.enabled = !self.enabled;
+/// Replacement of the text between two buffer positions.
+///
+/// Positions are `(row, col)` in chars, 0-based, like [`crate::buffer::TextBuffer`]'s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+    pub text: String,
+}
+
+impl TextEdit {
+    pub fn new(start: (usize, usize), end: (usize, usize), text: impl Into<String>) -> Self {
+        Self { start, end, text: text.into() }
     }
+}
 
-    pub fn lint(&mut self, content: &str, filename: Option<&str>) -> Vec<LintIssue> {
-        if !self.enabled {
-            return Vec::new();
+/// A named set of edits that resolves an issue
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintFix {
+    pub title: String,
+    pub edits: Vec<TextEdit>,
+}
+
+/// The preferred fix of every issue that has one, as edits in document order.
+///
+/// Edits overlapping an earlier one are left out; linting again after
+/// applying the rest will offer them anew.
+pub fn fix_all_edits(issues: &[LintIssue]) -> Vec<TextEdit> {
+    let mut fixes: Vec<&LintFix> = issues.iter().filter_map(|issue| issue.fixes.first()).collect();
+    fixes.sort_by_key(|fix| fix.edits.iter().map(|edit| edit.start).min());
+    let mut edits: Vec<TextEdit> = Vec::new();
+    for fix in fixes {
+        let mut fix_edits = fix.edits.clone();
+        fix_edits.sort_by_key(|edit| edit.start);
+        let overlaps = |edit: &TextEdit| edits.iter().any(|taken| (edit.start < taken.end && taken.start < edit.end) || edit.start == taken.start);
+        // A fix is applied whole or not at all
+        if !fix_edits.iter().any(overlaps) {
+            edits.extend(fix_edits);
         }
+    }
+    edits.sort_by_key(|edit| edit.start);
+    edits
+}
 
-        let mut issues = Vec::new();
+/// Where a rule found a problem; the linter adds the rule's id and severity.
+///
+/// Positions are as in [`LintIssue`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub message: String,
+    pub fixes: Vec<LintFix>,
+}
 
-        // Add universal linting rules
-        issues.extend(self.lint_universal(content));
-
-        // Language-specific linting based on file extension
-        if let Some(filename) = filename {
-            if filename.ends_with(".rs") {
-                issues.extend(self.lint_rust(content));
-            } else if filename.ends_with(".js") || filename.ends_with(".ts") {
-                issues.extend(self.lint_javascript(content));
-            } else if filename.ends_with(".py") {
-                issues.extend(self.lint_python(content));
-            } else if filename.ends_with(".json") {
-                issues.extend(self.lint_json(content));
-            }
-        }
+impl Finding {
+    /// A finding covering the char at `line`, `column`
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self { line, column, end_line: line, end_column: column + 1, message: message.into(), fixes: Vec::new() }
+    }
 
-        issues
+    /// Extend the finding up to `end_line`, `end_column` (exclusive)
+    pub fn until(mut self, end_line: usize, end_column: usize) -> Self {
+        self.end_line = end_line;
+        self.end_column = end_column;
+        self
     }
 
-    fn get_regex(&mut self, key: &str, pattern: &str) -> Option<&Regex> {
-        if !self.regex_cache.contains_key(key) {
-            if let Ok(regex) = Regex::new(pattern) {
-                self.regex_cache.insert(key.to_string(), regex);
-            }
-        }
-        self.regex_cache.get(key)
+    /// Offer `edits` as a way to resolve the finding
+    pub fn with_fix(mut self, title: impl Into<String>, edits: Vec<TextEdit>) -> Self {
+        self.fixes.push(LintFix { title: title.into(), edits });
+        self
     }
+}
 
-    fn lint_universal(&mut self, content: &str) -> Vec<LintIssue> {
-        let mut issues = Vec::new();
+/// What a rule gets to look at
+pub struct LintContext<'a> {
+    pub content: &'a str,
+    /// Language as named by [`crate::language::LanguageDetector`]
+    pub language: &'a str,
+    /// Parameters the project's lint configuration sets for the rule
+    pub options: &'a toml::Table,
+}
 
-        for (line_idx, line) in content.lines().enumerate() {
-            let line_number = line_idx + 1;
-
-            // Check for trailing whitespace
-            if line.ends_with(' ') || line.ends_with('\t') {
-                issues.push(LintIssue {
-                    line: line_number,
-                    column: line.trim_end().len() + 1,
-                    message: "Trailing whitespace".to_string(),
-                    severity: LintSeverity::Info,
-                    rule_name: "trailing-whitespace".to_string(),
-                });
-            }
+impl LintContext<'_> {
+    /// A whole-number parameter, e.g. `max-length = 120`
+    pub fn usize_option(&self, key: &str) -> Option<usize> {
+        self.options.get(key)?.as_integer()?.try_into().ok()
+    }
+}
 
-            // Check for long lines (>100 characters)
-            if line.len() > 100 {
-                issues.push(LintIssue {
-                    line: line_number,
-                    column: 101,
-                    message: "Line too long (>100 characters)".to_string(),
-                    severity: LintSeverity::Warning,
-                    rule_name: "long-line".to_string(),
-                });
-            }
+/// A check the linter can run.
+///
+/// Implement this to add a rule of your own and hand it to
+/// [`RuleRegistry::register`].
+pub trait LintRule: Send + Sync {
+    /// Name the rule is reported, configured and disabled by, e.g. `avoid-unwrap`
+    fn id(&self) -> &str;
 
-            // Check for mixed indentation (tabs and spaces)
-            if line.starts_with(' ') && line.contains('\t') {
-                issues.push(LintIssue {
-                    line: line_number,
-                    column: 1,
-                    message: "Mixed indentation (tabs and spaces)".to_string(),
-                    severity: LintSeverity::Warning,
-                    rule_name: "mixed-indentation".to_string(),
-                });
-            }
-        }
+    /// Languages the rule applies to; empty means every language
+    fn languages(&self) -> &[&str];
 
-        issues
+    /// Severity of the rule's issues unless configured otherwise
+    fn default_severity(&self) -> LintSeverity;
+
+    /// Problems found in the content
+    fn check(&self, context: &LintContext) -> Vec<Finding>;
+}
+
+/// A rule made from a plain function, which is how the built-in rules are written
+pub struct FnRule {
+    id: &'static str,
+    languages: &'static [&'static str],
+    severity: LintSeverity,
+    check: fn(&LintContext) -> Vec<Finding>,
+}
+
+impl FnRule {
+    pub const fn new(
+        id: &'static str,
+        languages: &'static [&'static str],
+        severity: LintSeverity,
+        check: fn(&LintContext) -> Vec<Finding>,
+    ) -> Self {
+        Self { id, languages, severity, check }
     }
+}
 
-    fn lint_rust(&mut self, content: &str) -> Vec<LintIssue> {
-        let mut issues = Vec::new();
+impl LintRule for FnRule {
+    fn id(&self) -> &str {
+        self.id
+    }
 
-        for (line_idx, line) in content.lines().enumerate() {
-            let line_number = line_idx + 1;
-
-            // Check for .unwrap() usage - comprehensive pattern
-            if let Some(regex) = self.get_regex("unwrap_call", r"\.unwrap\(\)") {
-                for mat in regex.find_iter(line) {
-                    issues.push(LintIssue {
-                        line: line_number,
-                        column: mat.start() + 1,
-                        message: "Avoid using .unwrap(), consider using .expect() with a descriptive message or proper error handling".to_string(),
-                        severity: LintSeverity::Warning,
-                        rule_name: "avoid-unwrap".to_string(),
-                    });
-                }
-            }
+    fn languages(&self) -> &[&str] {
+        self.languages
+    }
 
-            // Check for missing semicolons in statements
-            if let Some(regex) = self.get_regex("missing_semicolon", r"^\s*(println!|print!|return\s+[^;]+|let\s+.*=\s*[^;]+)\s*$") {
-                if regex.is_match(line) && !line.trim().ends_with('{') && !line.trim().ends_with(',') {
-                    issues.push(LintIssue {
-                        line: line_number,
-                        column: line.len() + 1,
-                        message: "Missing semicolon".to_string(),
-                        severity: LintSeverity::Error,
-                        rule_name: "missing-semicolon".to_string(),
-                    });
-                }
-            }
+    fn default_severity(&self) -> LintSeverity {
+        self.severity.clone()
+    }
 
-            // Check for unused variables
-            if let Some(regex) = self.get_regex("unused_variable", r"let\s+_*([a-zA-Z_][a-zA-Z0-9_]*)\s*=") {
-                if let Some(captures) = regex.captures(line) {
-                    if let Some(var_name) = captures.get(1) {
-                        let var = var_name.as_str();
-                        // Simple check: if variable name starts with underscore, it's intentionally unused
-                        if !var.starts_with('_') && !content.contains(&format!("{}", var)) {
-                            issues.push(LintIssue {
-                                line: line_number,
-                                column: var_name.start() + 1,
-                                message: format!("Variable '{}' may be unused. Consider prefixing with '_' if intentional", var),
-                                severity: LintSeverity::Info,
-                                rule_name: "unused-variable".to_string(),
-                            });
-                        }
-                    }
-                }
-            }
+    fn check(&self, context: &LintContext) -> Vec<Finding> {
+        (self.check)(context)
+    }
+}
 
-            // Check for panic!() usage
-            if let Some(regex) = self.get_regex("panic_call", r"panic!\s*\(") {
-                if let Some(mat) = regex.find(line) {
-                    issues.push(LintIssue {
-                        line: line_number,
-                        column: mat.start() + 1,
-                        message: "Consider using Result<T, E> or expect() instead of panic!()".to_string(),
-                        severity: LintSeverity::Warning,
-                        rule_name: "avoid-panic".to_string(),
-                    });
-                }
-            }
-        }
+/// The rules the linter runs, with which are turned off and which report at another severity
+#[derive(Default)]
+pub struct RuleRegistry {
+    rules: Vec<Box<dyn LintRule>>,
+    disabled: HashSet<String>,
+    severities: HashMap<String, LintSeverity>,
+}
 
-        issues
+impl RuleRegistry {
+    /// A registry without any rules
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    fn lint_javascript(&mut self, content: &str) -> Vec<LintIssue> {
-        let mut issues = Vec::new();
+    /// A registry holding the rules that ship with the editor
+    pub fn with_builtin_rules() -> Self {
+        let mut registry = Self::new();
+        for rule in lint_rules::builtin_rules() {
+            registry.register_boxed(rule);
+        }
+        registry
+    }
 
-        // Enhanced JavaScript linting with Biome-style rules
-        issues.extend(self.lint_javascript_enhanced(content));
+    /// Add a rule, replacing any rule with the same id
+    pub fn register(&mut self, rule: impl LintRule + 'static) {
+        self.register_boxed(Box::new(rule));
+    }
 
-        issues
+    pub fn register_boxed(&mut self, rule: Box<dyn LintRule>) {
+        match self.rules.iter().position(|known| known.id() == rule.id()) {
+            Some(idx) => self.rules[idx] = rule,
+            None => self.rules.push(rule),
+        }
     }
 
-    fn lint_javascript_enhanced(&mut self, content: &str) -> Vec<LintIssue> {
-        let mut issues = Vec::new();
+    pub fn get(&self, id: &str) -> Option<&dyn LintRule> {
+        self.rules.iter().find(|rule| rule.id() == id).map(|rule| rule.as_ref())
+    }
 
-        for (line_idx, line) in content.lines().enumerate() {
-            let line_number = line_idx + 1;
-
-            // Check for console.log usage
-            if let Some(regex) = self.get_regex("console_log", r"console\.log\s*\(") {
-                if let Some(mat) = regex.find(line) {
-                    issues.push(LintIssue {
-                        line: line_number,
-                        column: mat.start() + 1,
-                        message: "Avoid console.log in production code (biome-style: no-console-log)".to_string(),
-                        severity: LintSeverity::Warning,
-                        rule_name: "biome/no-console-log".to_string(),
-                    });
-                }
-            }
+    /// Every registered rule, enabled or not, in registration order
+    pub fn rules(&self) -> impl Iterator<Item = &dyn LintRule> + '_ {
+        self.rules.iter().map(|rule| rule.as_ref())
+    }
 
-            // Check for == usage (suggest ===)
-            if let Some(regex) = self.get_regex("loose_equality", r"\s==\s") {
-                if let Some(mat) = regex.find(line) {
-                    if !line.contains("===") {
-                        issues.push(LintIssue {
-                            line: line_number,
-                            column: mat.start() + 1,
-                            message: "Use '===' instead of '==' for strict equality (biome-style: use-strict-equality)".to_string(),
-                            severity: LintSeverity::Error,
-                            rule_name: "biome/use-strict-equality".to_string(),
-                        });
-                    }
-                }
-            }
+    pub fn set_enabled(&mut self, id: &str, enabled: bool) {
+        if enabled {
+            self.disabled.remove(id);
+        } else {
+            self.disabled.insert(id.to_string());
+        }
+    }
 
-            // Check for var usage
-            if let Some(regex) = self.get_regex("var_usage", r"\bvar\s+") {
-                if let Some(mat) = regex.find(line) {
-                    issues.push(LintIssue {
-                        line: line_number,
-                        column: mat.start() + 1,
-                        message: "Use 'let' or 'const' instead of 'var' (biome-style: no-var)".to_string(),
-                        severity: LintSeverity::Error,
-                        rule_name: "biome/no-var".to_string(),
-                    });
-                }
-            }
+    pub fn is_enabled(&self, id: &str) -> bool {
+        !self.disabled.contains(id)
+    }
 
-            // Check for debugger statements
-            if let Some(regex) = self.get_regex("debugger_usage", r"\bdebugger\s*;?") {
-                if let Some(mat) = regex.find(line) {
-                    issues.push(LintIssue {
-                        line: line_number,
-                        column: mat.start() + 1,
-                        message: "Remove debugger statements (biome-style: no-debugger)".to_string(),
-                        severity: LintSeverity::Error,
-                        rule_name: "biome/no-debugger".to_string(),
-                    });
-                }
-            }
+    /// Report a rule's issues at `severity` instead of its default
+    pub fn set_severity(&mut self, id: &str, severity: LintSeverity) {
+        self.severities.insert(id.to_string(), severity);
+    }
 
-            // Check for unused variables (simplified)
-            if let Some(regex) = self.get_regex("js_unused_var", r"(let|const|var)\s+([a-zA-Z_$][a-zA-Z0-9_$]*)\s*=") {
-                if let Some(captures) = regex.captures(line) {
-                    if let Some(var_name) = captures.get(2) {
-                        let var = var_name.as_str();
-                        if !var.starts_with('_') && !content[line_idx..].contains(var) {
-                            issues.push(LintIssue {
-                                line: line_number,
-                                column: var_name.start() + 1,
-                                message: format!("Variable '{}' is declared but never used (biome-style: no-unused-variables)", var),
-                                severity: LintSeverity::Warning,
-                                rule_name: "biome/no-unused-variables".to_string(),
-                            });
-                        }
-                    }
-                }
-            }
+    /// Severity a rule's issues are reported at
+    pub fn severity(&self, rule: &dyn LintRule) -> LintSeverity {
+        self.severities.get(rule.id()).cloned().unwrap_or_else(|| rule.default_severity())
+    }
 
-            // Check for double negation
-            if let Some(regex) = self.get_regex("double_negation", r"!!\s*\w") {
-                if let Some(mat) = regex.find(line) {
-                    issues.push(LintIssue {
-                        line: line_number,
-                        column: mat.start() + 1,
-                        message: "Use Boolean() instead of double negation (!!) (biome-style: no-double-negation)".to_string(),
-                        severity: LintSeverity::Info,
-                        rule_name: "biome/no-double-negation".to_string(),
-                    });
-                }
-            }
+    /// Rules to run on `language`, each with the severity its issues are reported at.
+    ///
+    /// `level` gives a rule's level from a lint configuration, if it sets one; that
+    /// takes precedence over the registry's own on/off state and severity.
+    pub fn rules_for<'a>(
+        &'a self,
+        language: &'a str,
+        level: impl Fn(&str) -> Option<RuleLevel> + 'a,
+    ) -> impl Iterator<Item = (&'a dyn LintRule, LintSeverity)> + 'a {
+        self.rules()
+            .filter(move |rule| rule.languages().is_empty() || rule.languages().contains(&language))
+            .filter_map(move |rule| match level(rule.id()) {
+                Some(RuleLevel::Off) => None,
+                Some(RuleLevel::On(severity)) => Some((rule, severity)),
+                None if !self.is_enabled(rule.id()) => None,
+                None => Some((rule, self.severity(rule))),
+            })
+    }
+}
 
-            // Check for empty blocks
-            if let Some(regex) = self.get_regex("empty_block", r"\{\s*\}") {
-                if let Some(mat) = regex.find(line) {
-                    issues.push(LintIssue {
-                        line: line_number,
-                        column: mat.start() + 1,
-                        message: "Empty block statement (biome-style: no-empty-block)".to_string(),
-                        severity: LintSeverity::Warning,
-                        rule_name: "biome/no-empty-block".to_string(),
-                    });
-                }
-            }
+pub struct Linter {
+    enabled: bool,
+    registry: RuleRegistry,
+    // Lint configuration found for each directory, with the file it came from
+    configs: HashMap<PathBuf, Option<(PathBuf, Rc<LintConfig>)>>,
+}
 
-            // Check for function spacing
-            if let Some(regex) = self.get_regex("function_spacing", r"function\s*\(\s*\)\s*\{") {
-                if regex.is_match(line) && !line.contains("function ()") {
-                    issues.push(LintIssue {
-                        line: line_number,
-                        column: 1,
-                        message: "Consider proper spacing around function declarations (biome-style: formatting)".to_string(),
-                        severity: LintSeverity::Info,
-                        rule_name: "biome/function-spacing".to_string(),
-                    });
-                }
-            }
+impl Default for Linter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-            // Check for semicolon consistency
-            if let Some(regex) = self.get_regex("statement_without_semicolon", r"^\s*[a-zA-Z_$].*[^;{}\s]\s*$") {
-                if regex.is_match(line) && !line.trim().ends_with(',') && !line.trim().starts_with("//") && !line.trim().starts_with("/*") {
-                    issues.push(LintIssue {
-                        line: line_number,
-                        column: line.len() + 1,
-                        message: "Missing semicolon (biome-style: use-semicolons)".to_string(),
-                        severity: LintSeverity::Warning,
-                        rule_name: "biome/use-semicolons".to_string(),
-                    });
-                }
-            }
+impl Linter {
+    /// A linter running the built-in rules
+    pub fn new() -> Self {
+        Self::with_registry(RuleRegistry::with_builtin_rules())
+    }
+
+    /// A linter running the rules of `registry`
+    pub fn with_registry(registry: RuleRegistry) -> Self {
+        Self {
+            enabled: true,
+            registry,
+            configs: HashMap::new(),
         }
+    }
 
-        issues
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
     }
 
-    fn lint_python(&mut self, content: &str) -> Vec<LintIssue> {
-        let mut issues = Vec::new();
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
 
-        for (line_idx, line) in content.lines().enumerate() {
-            let line_number = line_idx + 1;
-
-            // PEP 8: Line length should be <= 79 characters
-            if line.len() > 79 {
-                issues.push(LintIssue {
-                    line: line_number,
-                    column: 80,
-                    message: "Line too long (PEP 8 recommends ≤79 characters)".to_string(),
-                    severity: LintSeverity::Info,
-                    rule_name: "pep8-line-length".to_string(),
-                });
-            }
+    pub fn registry(&self) -> &RuleRegistry {
+        &self.registry
+    }
 
-            // Check for improper indentation (should be 4 spaces)
-            if line.starts_with(' ') && !line.starts_with("    ") {
-                let leading_spaces = line.len() - line.trim_start().len();
-                if leading_spaces % 4 != 0 && leading_spaces > 0 {
-                    issues.push(LintIssue {
-                        line: line_number,
-                        column: 1,
-                        message: "PEP 8: Use 4 spaces per indentation level".to_string(),
-                        severity: LintSeverity::Warning,
-                        rule_name: "pep8-indentation".to_string(),
-                    });
-                }
-            }
+    /// The rules to run, for registering, disabling or re-severitying them
+    pub fn registry_mut(&mut self) -> &mut RuleRegistry {
+        &mut self.registry
+    }
 
-            // Check for missing space after comma
-            if let Some(regex) = self.get_regex("comma_spacing", r",[^\s]") {
-                if let Some(mat) = regex.find(line) {
-                    issues.push(LintIssue {
-                        line: line_number,
-                        column: mat.start() + 2,
-                        message: "PEP 8: Missing whitespace after ','".to_string(),
-                        severity: LintSeverity::Info,
-                        rule_name: "pep8-comma-spacing".to_string(),
-                    });
-                }
-            }
+    /// Forget the lint configuration files read so far, e.g. after one was saved
+    pub fn reload_config(&mut self) {
+        self.configs.clear();
+    }
 
-            // Check for print statements (suggest logging)
-            if let Some(regex) = self.get_regex("print_statement", r"\bprint\s*\(") {
-                if let Some(mat) = regex.find(line) {
-                    issues.push(LintIssue {
-                        line: line_number,
-                        column: mat.start() + 1,
-                        message: "Consider using logging instead of print for production code".to_string(),
-                        severity: LintSeverity::Hint,
-                        rule_name: "prefer-logging".to_string(),
-                    });
-                }
-            }
+    /// Check `content`, written in `language` as named by [`crate::language::LanguageDetector`].
+    ///
+    /// Rules are set up by the project's lint configuration for `filename`.
+    /// Linting a configuration file also reports the mistakes in it.
+    /// Issues silenced by `hello-lint-disable` comments are left out, and
+    /// comments that silence nothing are reported instead.
+    pub fn lint(&mut self, content: &str, language: &str, filename: Option<&str>) -> Vec<LintIssue> {
+        if !self.enabled {
+            return Vec::new();
         }
 
+        let path = filename.map(Path::new);
+        let mut issues = Vec::new();
+        let config = match path {
+            // The text being edited is newer than what is on disk
+            Some(path) if lint_config::is_config_file(path) => {
+                let config = Rc::new(LintConfig::parse(content, path));
+                issues.extend(config.problems(&self.registry));
+                self.update_config(path, &config);
+                Some(config)
+            }
+            Some(path) => self.config_for(path),
+            None => None,
+        };
+        let settings = config.map(|config| config.settings_for(path)).unwrap_or_default();
+
+        let no_options = toml::Table::new();
+        let configured = |id: &str| settings.get(id).and_then(|setting| setting.level.clone());
+        for (rule, severity) in self.registry.rules_for(language, configured) {
+            let options = settings.get(rule.id()).map_or(&no_options, |setting| &setting.options);
+            let context = LintContext { content, language, options };
+            issues.extend(rule.check(&context).into_iter().map(|finding| LintIssue {
+                line: finding.line,
+                column: finding.column,
+                end_line: finding.end_line,
+                end_column: finding.end_column,
+                message: finding.message,
+                severity: severity.clone(),
+                rule_name: rule.id().to_string(),
+                fixes: finding.fixes,
+            }));
+        }
+        // Line by line, each line's issues in rule order
+        issues.sort_by_key(|issue| issue.line);
+        let suppressions = Suppressions::parse(content);
+        if suppressions.is_empty() {
+            return issues;
+        }
+        let mut issues = suppressions.apply(issues);
+        // Unused suppressions can be configured like a rule
+        let unused_level = settings.get(lint_suppressions::UNUSED_RULE).and_then(|setting| setting.level.clone());
+        match unused_level {
+            Some(RuleLevel::Off) => issues.retain(|issue| issue.rule_name != lint_suppressions::UNUSED_RULE),
+            Some(RuleLevel::On(severity)) => issues
+                .iter_mut()
+                .filter(|issue| issue.rule_name == lint_suppressions::UNUSED_RULE)
+                .for_each(|issue| issue.severity = severity.clone()),
+            None => {}
+        }
         issues
     }
 
-    fn lint_json(&self, content: &str) -> Vec<LintIssue> {
-        let mut issues = Vec::new();
+    /// Lint configuration for the file at `path`, read once per directory
+    fn config_for(&mut self, path: &Path) -> Option<Rc<LintConfig>> {
+        let dir = std::path::absolute(path).ok()?.parent()?.to_path_buf();
+        let found = self.configs.entry(dir).or_insert_with_key(|dir| {
+            LintConfig::discover(dir).map(|(path, config)| (path, Rc::new(config)))
+        });
+        found.as_ref().map(|(_, config)| config.clone())
+    }
 
-        match serde_json::from_str::<serde_json::Value>(content) {
-            Ok(_) => {
-                // Valid JSON, check for common style issues
-                for (line_idx, line) in content.lines().enumerate() {
-                    let line_number = line_idx + 1;
-                    
-                    // Check for trailing commas (not allowed in JSON)
-                    if line.trim().ends_with(',') && (line.contains('}') || line.contains(']')) {
-                        issues.push(LintIssue {
-                            line: line_number,
-                            column: line.rfind(',').unwrap_or(0) + 1,
-                            message: "Trailing comma not allowed in JSON".to_string(),
-                            severity: LintSeverity::Error,
-                            rule_name: "no-trailing-comma".to_string(),
-                        });
-                    }
-                }
-            }
-            Err(e) => {
-                // Parse the error to get line information if possible
-                let error_msg = e.to_string();
-                let line = if error_msg.contains("line") {
-                    // Try to extract line number from error message
-                    error_msg
-                        .split("line ")
-                        .nth(1)
-                        .and_then(|s| s.split(' ').next())
-                        .and_then(|s| s.parse::<usize>().ok())
-                        .unwrap_or(1)
-                } else {
-                    1
-                };
-
-                issues.push(LintIssue {
-                    line,
-                    column: 1,
-                    message: format!("JSON syntax error: {}", e),
-                    severity: LintSeverity::Error,
-                    rule_name: "json-syntax".to_string(),
-                });
+    /// Use `config` wherever the file at `path` was read before
+    fn update_config(&mut self, path: &Path, config: &Rc<LintConfig>) {
+        let Ok(path) = std::path::absolute(path) else {
+            return;
+        };
+        for (found, cached) in self.configs.values_mut().flatten() {
+            if *found == path {
+                *cached = config.clone();
             }
         }
-
-        issues
     }
 
     pub fn get_issue_counts(&self, issues: &[LintIssue]) -> (usize, usize, usize, usize) {
@@ -457,3 +455,158 @@ impl Linter {
         (errors, warnings, infos, hints)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule_names(issues: &[LintIssue]) -> Vec<&str> {
+        issues.iter().map(|issue| issue.rule_name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_rules_apply_by_language() {
+        let mut linter = Linter::new();
+        let code = "let x = y.unwrap();\nvar a = 1; \n";
+
+        let rust = linter.lint(code, "Rust", None);
+        assert_eq!(rule_names(&rust), ["avoid-unwrap", "trailing-whitespace"]);
+        assert_eq!((rust[0].line, rust[0].column), (1, 10));
+        assert_eq!(rust[0].severity, LintSeverity::Warning);
+
+        let javascript = linter.lint(code, "JavaScript", None);
+        assert!(rule_names(&javascript).contains(&"biome/no-var"));
+        assert!(!rule_names(&javascript).contains(&"avoid-unwrap"));
+
+        let json = linter.lint("{\"a\": }", "JSON", None);
+        assert_eq!(rule_names(&json), ["json-syntax"]);
+        assert!(linter.lint(code, "Plain Text", None).iter().all(|issue| issue.rule_name == "trailing-whitespace"));
+    }
+
+    #[test]
+    fn test_registry_configuration() {
+        let mut linter = Linter::new();
+        linter.registry_mut().set_enabled("avoid-unwrap", false);
+        linter.registry_mut().set_severity("trailing-whitespace", LintSeverity::Error);
+        let issues = linter.lint("x.unwrap() ", "Rust", None);
+        assert_eq!(rule_names(&issues), ["trailing-whitespace"]);
+        assert_eq!(issues[0].severity, LintSeverity::Error);
+
+        // Third-party rules sit alongside the built-in ones
+        fn no_todo(context: &LintContext) -> Vec<Finding> {
+            (1..)
+                .zip(context.content.lines())
+                .filter_map(|(line, text)| text.find("TODO").map(|col| Finding::new(line, col + 1, "Unresolved TODO")))
+                .collect()
+        }
+        linter.registry_mut().register(FnRule::new("no-todo", &[], LintSeverity::Hint, no_todo));
+        let issues = linter.lint("// TODO: later", "Rust", None);
+        assert_eq!(rule_names(&issues), ["no-todo"]);
+        assert_eq!(issues[0].column, 4);
+        assert!(linter.registry().get("no-todo").is_some());
+
+        // A configured level wins over the registry's own
+        let registry = linter.registry();
+        let ids = |level: fn(&str) -> Option<RuleLevel>| -> Vec<&str> {
+            registry.rules_for("Rust", level).map(|(rule, _)| rule.id()).collect()
+        };
+        assert!(!ids(|_| None).contains(&"avoid-unwrap"));
+        assert!(ids(|_| Some(RuleLevel::On(LintSeverity::Hint))).contains(&"avoid-unwrap"));
+        assert!(ids(|_| Some(RuleLevel::Off)).is_empty());
+    }
+
+    #[test]
+    fn test_project_config() {
+        let root = std::env::temp_dir().join(format!("hello_tui_lint_{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        let config_path = root.join(lint_config::CONFIG_FILE);
+        std::fs::write(&config_path, "[rules]\navoid-unwrap = \"off\"\nlong-line = { severity = \"error\", max-length = 10 }\n").unwrap();
+        let file = root.join("src/main.rs");
+        let file = file.to_str().unwrap();
+
+        let mut linter = Linter::new();
+        let issues = linter.lint("let value = x.unwrap();", "Rust", Some(file));
+        assert_eq!(rule_names(&issues), ["long-line"]);
+        assert_eq!((issues[0].column, issues[0].severity.clone()), (11, LintSeverity::Error));
+        // Without a file there is no project to configure it
+        assert!(rule_names(&linter.lint("x.unwrap()", "Rust", None)).contains(&"avoid-unwrap"));
+
+        // Editing the configuration applies right away and reports its mistakes
+        let edited = "[rules]\nlong-line = \"loud\"\n";
+        let issues = linter.lint(edited, "TOML", config_path.to_str());
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(rule_names(&issues), [lint_config::CONFIG_RULE]);
+        assert_eq!(issues[0].line, 2);
+        assert_eq!(rule_names(&linter.lint("let value = x.unwrap();", "Rust", Some(file))), ["avoid-unwrap"]);
+    }
+
+    #[test]
+    fn test_suppression_comments() {
+        let mut linter = Linter::new();
+        let code = "// hello-lint-disable-next-line avoid-unwrap\nlet a = x.unwrap();\n# hello-lint-disable-line avoid-panic\n";
+        let issues = linter.lint(code, "Rust", None);
+        assert_eq!(rule_names(&issues), ["unused-suppression"]);
+        assert_eq!((issues[0].line, issues[0].severity.clone()), (3, LintSeverity::Hint));
+    }
+
+    #[test]
+    fn test_issue_ranges() {
+        let mut linter = Linter::new();
+        let code = "let é = x.unwrap()\n  print(a,b)\n";
+        let issues = linter.lint(code, "Rust", None);
+        let unwrap = issues.iter().find(|issue| issue.rule_name == "avoid-unwrap").unwrap();
+        // Columns count chars, not bytes
+        assert_eq!((unwrap.line, unwrap.column, unwrap.end_line, unwrap.end_column), (1, 10, 1, 19));
+        assert_eq!(unwrap.columns_on(0, 18), Some(9..18));
+        assert_eq!(unwrap.columns_on(1, 12), None);
+
+        // A missing semicolon sits on the statement's last char
+        let semicolon = issues.iter().find(|issue| issue.rule_name == "missing-semicolon").unwrap();
+        assert_eq!(semicolon.columns_on(0, 18), Some(17..18));
+        assert_eq!(issue_at(&issues, 0, 18, 18).map(|issue| issue.rule_name.as_str()), Some("missing-semicolon"));
+        assert_eq!(issue_at(&issues, 0, 12, 18).map(|issue| issue.rule_name.as_str()), Some("avoid-unwrap"));
+        assert!(issue_at(&issues, 0, 2, 18).is_none());
+
+        let python = linter.lint(code, "Python", None);
+        let spacing = python.iter().find(|issue| issue.rule_name == "pep8-comma-spacing").unwrap();
+        assert_eq!(spacing.columns_on(1, 12), Some(9..10));
+        let indentation = python.iter().find(|issue| issue.rule_name == "pep8-indentation").unwrap();
+        assert_eq!(indentation.columns_on(1, 12), Some(0..2));
+
+        // Spans over several lines cover whole lines in between
+        let issue = LintIssue { end_line: 3, end_column: 1, ..unwrap.clone() };
+        assert_eq!(issue.columns_on(1, 12), Some(0..12));
+        assert_eq!(issue.columns_on(2, 5), None);
+    }
+
+    /// Apply edits the way the editor does: from the end, by char position
+    fn apply_edits(content: &str, edits: &[TextEdit]) -> String {
+        let mut chars: Vec<char> = content.chars().collect();
+        let line_starts: Vec<usize> = std::iter::once(0)
+            .chain(chars.iter().enumerate().filter(|(_, ch)| **ch == '\n').map(|(idx, _)| idx + 1))
+            .collect();
+        for edit in edits.iter().rev() {
+            let start = line_starts[edit.start.0] + edit.start.1;
+            let end = line_starts[edit.end.0] + edit.end.1;
+            chars.splice(start..end, edit.text.chars());
+        }
+        chars.into_iter().collect()
+    }
+
+    #[test]
+    fn test_fix_all() {
+        let mut linter = Linter::new();
+        let code = "var a = 1;  \ndebugger;\nif (a == !!b) {}\n";
+        let issues = linter.lint(code, "JavaScript", None);
+        let var_fixes: Vec<&str> = issues
+            .iter()
+            .find(|issue| issue.rule_name == "biome/no-var")
+            .map(|issue| issue.fixes.iter().map(|fix| fix.title.as_str()).collect())
+            .unwrap();
+        assert_eq!(var_fixes.len(), 2);
+
+        let fixed = apply_edits(code, &fix_all_edits(&issues));
+        assert_eq!(fixed, "let a = 1;\nif (a === Boolean(b)) {}\n");
+        assert!(linter.lint(&fixed, "JavaScript", None).iter().all(|issue| issue.fixes.is_empty()));
+    }
+}
@@ -1,36 +1,36 @@
 use std::io;
-use hello_tui::{file_io, syntax, iocraft_components};
-
-mod editor_demo;
+use hello_tui::{file_io, syntax, iocraft_components, text_metrics, cursor::cursor_utils, buffer::TextBuffer};
 
 #[derive(Default)]
 struct TextEditor {
-    lines: Vec<String>,
+    buffer: TextBuffer,
     cursor_row: usize,
     cursor_col: usize,
     filename: Option<String>,
     syntax_highlighter: syntax::SyntaxHighlighter,
 }
 
+// The editing operations are only exercised by the demo binaries
+#[allow(dead_code)]
 impl TextEditor {
     fn new() -> Self {
         Self {
-            lines: vec![
-                "Welcome to IOCraft Text Editor!".to_string(),
-                "".to_string(),
-                "This is a simple text editor built with:".to_string(),
-                "  • iocraft - for beautiful terminal UI components".to_string(),
-                "  • crossterm - for terminal interaction".to_string(),
-                "".to_string(),
-                "Features:".to_string(),
-                "  ✓ File loading and saving".to_string(),
-                "  ✓ Multi-line text editing".to_string(),
-                "  ✓ Cursor positioning".to_string(),
-                "  ✓ Interactive editing mode".to_string(),
-                "  ✓ Visual cursor display".to_string(),
-                "".to_string(),
-                "Start editing here...".to_string(),
-            ],
+            buffer: TextBuffer::from_lines(&[
+                "Welcome to IOCraft Text Editor!",
+                "",
+                "This is a simple text editor built with:",
+                "  • iocraft - for beautiful terminal UI components",
+                "  • crossterm - for terminal interaction",
+                "",
+                "Features:",
+                "  ✓ File loading and saving",
+                "  ✓ Multi-line text editing",
+                "  ✓ Cursor positioning",
+                "  ✓ Interactive editing mode",
+                "  ✓ Visual cursor display",
+                "",
+                "Start editing here...",
+            ]),
             cursor_row: 12,
             cursor_col: 22,
             filename: None,
@@ -39,7 +39,7 @@ impl TextEditor {
     }
 
     fn load_file(&mut self, filename: &str) -> io::Result<()> {
-        self.lines = file_io::FileIO::load_file(filename)?;
+        self.buffer = file_io::FileIO::load_buffer(filename)?;
         self.filename = Some(filename.to_string());
         self.cursor_row = 0;
         self.cursor_col = 0;
@@ -47,42 +47,35 @@ impl TextEditor {
     }
 
     fn save_file(&mut self, filename: &str) -> io::Result<()> {
-        file_io::FileIO::save_file(filename, &self.lines)?;
+        file_io::FileIO::save_buffer(filename, &self.buffer)?;
         self.filename = Some(filename.to_string());
         Ok(())
     }
 
     fn insert_char(&mut self, ch: char) {
-        if self.cursor_row < self.lines.len() {
-            let line = &mut self.lines[self.cursor_row];
-            let mut chars: Vec<char> = line.chars().collect();
-            chars.insert(self.cursor_col, ch);
-            *line = chars.into_iter().collect();
+        if self.cursor_row < self.buffer.len_lines() {
+            self.buffer.insert_char_at(self.cursor_row, self.cursor_col, ch);
             self.cursor_col += 1;
         }
     }
 
     fn delete_char(&mut self) {
-        if self.cursor_col > 0 && self.cursor_row < self.lines.len() {
-            let line = &mut self.lines[self.cursor_row];
-            let mut chars: Vec<char> = line.chars().collect();
-            if self.cursor_col <= chars.len() && self.cursor_col > 0 {
-                chars.remove(self.cursor_col - 1);
-                *line = chars.into_iter().collect();
-                self.cursor_col -= 1;
-            }
+        if self.cursor_col > 0
+            && self.cursor_row < self.buffer.len_lines()
+            && self.cursor_col <= self.buffer.line_len(self.cursor_row)
+        {
+            // Remove the whole grapheme cluster before the cursor
+            let start = text_metrics::prev_boundary(&self.buffer.line(self.cursor_row), self.cursor_col);
+            self.buffer.remove_range(self.cursor_row, start, self.cursor_row, self.cursor_col);
+            self.cursor_col = start;
         }
     }
 
     fn insert_newline(&mut self) {
-        if self.cursor_row < self.lines.len() {
-            let cursor_col = self.cursor_col;
-            let line = self.lines[self.cursor_row].clone();
-            let (left, right) = line.split_at(cursor_col);
-            self.lines[self.cursor_row] = left.to_string();
-            self.lines.insert(self.cursor_row + 1, right.to_string());
+        if self.cursor_row < self.buffer.len_lines() {
+            self.buffer.split_line(self.cursor_row, self.cursor_col);
         } else {
-            self.lines.push(String::new());
+            self.buffer.insert_line(self.buffer.len_lines(), "");
         }
         self.cursor_row += 1;
         self.cursor_col = 0;
@@ -92,31 +85,31 @@ impl TextEditor {
         match direction {
             Direction::Up => {
                 if self.cursor_row > 0 {
+                    let current_line = self.buffer.line(self.cursor_row);
                     self.cursor_row -= 1;
-                    let line_len = self.lines[self.cursor_row].len();
-                    self.cursor_col = self.cursor_col.min(line_len);
+                    self.cursor_col = cursor_utils::calculate_preferred_column(&current_line, self.cursor_col, &self.buffer.line(self.cursor_row));
                 }
             }
             Direction::Down => {
-                if self.cursor_row < self.lines.len() - 1 {
+                if self.cursor_row < self.buffer.len_lines() - 1 {
+                    let current_line = self.buffer.line(self.cursor_row);
                     self.cursor_row += 1;
-                    let line_len = self.lines[self.cursor_row].len();
-                    self.cursor_col = self.cursor_col.min(line_len);
+                    self.cursor_col = cursor_utils::calculate_preferred_column(&current_line, self.cursor_col, &self.buffer.line(self.cursor_row));
                 }
             }
             Direction::Left => {
                 if self.cursor_col > 0 {
-                    self.cursor_col -= 1;
+                    self.cursor_col = text_metrics::prev_boundary(&self.buffer.line(self.cursor_row), self.cursor_col);
                 } else if self.cursor_row > 0 {
                     self.cursor_row -= 1;
-                    self.cursor_col = self.lines[self.cursor_row].len();
+                    self.cursor_col = self.buffer.line_len(self.cursor_row);
                 }
             }
             Direction::Right => {
-                let line_len = self.lines[self.cursor_row].len();
+                let line_len = self.buffer.line_len(self.cursor_row);
                 if self.cursor_col < line_len {
-                    self.cursor_col += 1;
-                } else if self.cursor_row < self.lines.len() - 1 {
+                    self.cursor_col = text_metrics::next_boundary(&self.buffer.line(self.cursor_row), self.cursor_col);
+                } else if self.cursor_row < self.buffer.len_lines() - 1 {
                     self.cursor_row += 1;
                     self.cursor_col = 0;
                 }
@@ -125,6 +118,7 @@ impl TextEditor {
     }
 }
 
+#[allow(dead_code)]
 #[derive(Debug)]
 enum Direction {
     Up,
@@ -204,9 +198,9 @@ fn main() -> io::Result<()> {
 fn display_editor_simple(editor: &TextEditor) {
     println!("┌─────────────────────────────────────────────┐");
     
-    let syntax_name = editor.syntax_highlighter.get_syntax_name(editor.filename.as_deref());
+    let syntax_name = editor.syntax_highlighter.get_syntax_name(editor.filename.as_deref(), &editor.buffer);
     
-    for (i, line) in editor.lines.iter().enumerate() {
+    for (i, line) in editor.buffer.lines().enumerate() {
         let display = if i == editor.cursor_row {
             let mut chars: Vec<char> = line.chars().collect();
             if editor.cursor_col <= chars.len() {
@@ -214,18 +208,18 @@ fn display_editor_simple(editor: &TextEditor) {
             }
             chars.into_iter().collect()
         } else {
-            line.clone()
+            line
         };
         
-        let truncated = if display.len() > 43 {
-            format!("{}...", &display[0..40])
+        let truncated = if text_metrics::str_width(&display) > 43 {
+            format!("{}...", text_metrics::truncate_to_width(&display, 40))
         } else {
             display
         };
         
         // Apply syntax highlighting
         let highlighted = if editor.filename.is_some() {
-            editor.syntax_highlighter.highlight_line(&truncated, &syntax_name)
+            syntax::to_ansi(&truncated, 0, &editor.syntax_highlighter.highlight_line(&truncated, &syntax_name))
         } else {
             truncated
         };
@@ -243,7 +237,7 @@ fn display_editor_simple(editor: &TextEditor) {
     println!("│ Row: {}, Col: {} | {} lines{}{} │", 
              editor.cursor_row + 1, 
              editor.cursor_col + 1, 
-             editor.lines.len(),
+             editor.buffer.len_lines(),
              syntax_info,
              " ".repeat(15 - syntax_info.len().min(15)));
     println!("└─────────────────────────────────────────────┘");
@@ -1,5 +1,6 @@
-use std::io;
-use crossterm::event::{MouseEvent, MouseEventKind, MouseButton};
+use crossterm::event::{KeyModifiers, MouseEvent, MouseEventKind, MouseButton};
+use crate::buffer::TextBuffer;
+use crate::layout::{PaneId, Rect};
 
 /// Mouse control handler for the text editor
 pub struct MouseController {
@@ -7,9 +8,14 @@ pub struct MouseController {
     last_click_col: usize,
     drag_start_row: Option<usize>,
     drag_start_col: Option<usize>,
+    block_drag: bool,
     selection_active: bool,
     double_click_threshold_ms: u64,
     last_click_time: std::time::Instant,
+    // Screen area of each pane, for routing events to them
+    panes: Vec<(PaneId, Rect)>,
+    // Pane the current gesture belongs to
+    target_pane: Option<PaneId>,
 }
 
 impl Default for MouseController {
@@ -26,14 +32,57 @@ impl MouseController {
             last_click_col: 0,
             drag_start_row: None,
             drag_start_col: None,
+            block_drag: false,
             selection_active: false,
             double_click_threshold_ms: 500,
             last_click_time: std::time::Instant::now(),
+            panes: Vec::new(),
+            target_pane: None,
         }
     }
 
+    /// Tell the controller where the panes are on screen.
+    ///
+    /// Once panes are set, every event is routed to one of them and its
+    /// position is reported relative to that pane's top-left corner.
+    pub fn set_panes(&mut self, panes: Vec<(PaneId, Rect)>) {
+        self.panes = panes;
+    }
+
+    /// Pane the last event was routed to; a click there should focus it
+    pub fn target_pane(&self) -> Option<PaneId> {
+        self.target_pane
+    }
+
+    /// Route an event to a pane and make its position relative to that pane.
+    ///
+    /// Presses and the wheel go to the pane under the pointer; drags and
+    /// releases stay with the pane the press started in, clamped to its edges.
+    fn route(&mut self, mouse_event: MouseEvent) -> Option<MouseEvent> {
+        if self.panes.is_empty() {
+            return Some(mouse_event);
+        }
+        let (col, row) = (mouse_event.column as usize, mouse_event.row as usize);
+        if matches!(
+            mouse_event.kind,
+            MouseEventKind::Down(_) | MouseEventKind::ScrollUp | MouseEventKind::ScrollDown | MouseEventKind::ScrollLeft | MouseEventKind::ScrollRight
+        ) {
+            self.target_pane = self.panes.iter().find(|(_, rect)| rect.contains(col, row)).map(|(id, _)| *id);
+        }
+
+        let rect = self.panes.iter().find(|(id, _)| Some(*id) == self.target_pane)?.1;
+        let mut local = mouse_event;
+        local.column = col.saturating_sub(rect.x).min(rect.width.saturating_sub(1)) as u16;
+        local.row = row.saturating_sub(rect.y).min(rect.height.saturating_sub(1)) as u16;
+        Some(local)
+    }
+
     /// Handle mouse events and return the appropriate action
     pub fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> MouseAction {
+        // Events between panes (on a separator) do nothing
+        let Some(mouse_event) = self.route(mouse_event) else {
+            return MouseAction::None;
+        };
         match mouse_event.kind {
             MouseEventKind::Down(MouseButton::Left) => {
                 let row = mouse_event.row as usize;
@@ -54,6 +103,8 @@ impl MouseController {
                 } else {
                     self.drag_start_row = Some(row);
                     self.drag_start_col = Some(col);
+                    // Alt+drag selects a rectangular block instead of a text range
+                    self.block_drag = mouse_event.modifiers.contains(KeyModifiers::ALT);
                     self.selection_active = false;
                     MouseAction::Click { row, col }
                 }
@@ -67,7 +118,15 @@ impl MouseController {
                     self.drag_start_row = None;
                     self.drag_start_col = None;
                     
-                    if start_row != end_row || start_col != end_col {
+                    if self.block_drag && start_row != end_row {
+                        self.selection_active = true;
+                        MouseAction::BlockSelect {
+                            start_row,
+                            start_col,
+                            end_row,
+                            end_col
+                        }
+                    } else if start_row != end_row || start_col != end_col {
                         self.selection_active = true;
                         MouseAction::DragEnd { 
                             start_row, 
@@ -106,6 +165,15 @@ impl MouseController {
                 }
             }
             
+            // Shift+wheel scrolls sideways, as do tilt wheels and trackpads
+            MouseEventKind::ScrollUp if mouse_event.modifiers.contains(KeyModifiers::SHIFT) => {
+                MouseAction::ScrollLeft
+            }
+            
+            MouseEventKind::ScrollDown if mouse_event.modifiers.contains(KeyModifiers::SHIFT) => {
+                MouseAction::ScrollRight
+            }
+            
             MouseEventKind::ScrollUp => {
                 MouseAction::ScrollUp
             }
@@ -114,6 +182,14 @@ impl MouseController {
                 MouseAction::ScrollDown
             }
             
+            MouseEventKind::ScrollLeft => {
+                MouseAction::ScrollLeft
+            }
+            
+            MouseEventKind::ScrollRight => {
+                MouseAction::ScrollRight
+            }
+            
             _ => MouseAction::None,
         }
     }
@@ -128,6 +204,7 @@ impl MouseController {
         self.selection_active = false;
         self.drag_start_row = None;
         self.drag_start_col = None;
+        self.block_drag = false;
     }
 
     /// Get the last click position
@@ -164,12 +241,26 @@ pub enum MouseAction {
         end_col: usize 
     },
     
+    /// Alt+drag finished (rectangular block selection, one caret per row)
+    BlockSelect {
+        start_row: usize,
+        start_col: usize,
+        end_row: usize,
+        end_col: usize
+    },
+    
     /// Scroll wheel up
     ScrollUp,
     
     /// Scroll wheel down
     ScrollDown,
     
+    /// Shift+wheel up or horizontal wheel left
+    ScrollLeft,
+    
+    /// Shift+wheel down or horizontal wheel right
+    ScrollRight,
+    
     /// No action needed
     None,
 }
@@ -216,56 +307,24 @@ impl TextSelection {
         }
     }
 
-    /// Get the selected text from the given lines
-    pub fn get_selected_text(&self, lines: &[String]) -> String {
-        if self.start_row >= lines.len() {
+    /// Get the selected text from the given buffer
+    pub fn get_selected_text(&self, buffer: &TextBuffer) -> String {
+        if self.start_row >= buffer.len_lines() {
             return String::new();
         }
 
-        if self.start_row == self.end_row {
-            // Single line selection
-            let line = &lines[self.start_row];
-            let start = self.start_col.min(line.len());
-            let end = self.end_col.min(line.len());
-            if start < end {
-                line[start..end].to_string()
-            } else {
-                String::new()
-            }
-        } else {
-            // Multi-line selection
-            let mut result = String::new();
-            
-            for row in self.start_row..=self.end_row.min(lines.len() - 1) {
-                let line = &lines[row];
-                
-                if row == self.start_row {
-                    let start = self.start_col.min(line.len());
-                    result.push_str(&line[start..]);
-                } else if row == self.end_row {
-                    let end = self.end_col.min(line.len());
-                    result.push_str(&line[..end]);
-                } else {
-                    result.push_str(line);
-                }
-                
-                if row < self.end_row {
-                    result.push('\n');
-                }
-            }
-            
-            result
-        }
+        buffer.text_range(self.start_row, self.start_col, self.end_row, self.end_col)
     }
 }
 
 /// Word boundary detection for double-click word selection
 pub fn find_word_boundaries(line: &str, col: usize) -> (usize, usize) {
-    if line.is_empty() || col >= line.len() {
+    let chars: Vec<char> = line.chars().collect();
+    // `col` is a char index, so compare against the char count rather than bytes
+    if col >= chars.len() {
         return (col, col);
     }
 
-    let chars: Vec<char> = line.chars().collect();
     let mut start = col;
     let mut end = col;
 
@@ -312,6 +371,26 @@ mod tests {
         assert!(!selection.contains(3, 10));
     }
 
+    #[test]
+    fn test_pane_routing() {
+        let event = |kind, column, row| MouseEvent { kind, column, row, modifiers: KeyModifiers::NONE };
+        let mut controller = MouseController::new();
+        controller.set_panes(vec![(0, Rect::new(0, 0, 40, 20)), (1, Rect::new(41, 0, 40, 20))]);
+
+        assert_eq!(controller.handle_mouse_event(event(MouseEventKind::Down(MouseButton::Left), 45, 3)), MouseAction::Click { row: 3, col: 4 });
+        assert_eq!(controller.target_pane(), Some(1));
+        // A drag stays with the pane it started in
+        assert_eq!(
+            controller.handle_mouse_event(event(MouseEventKind::Up(MouseButton::Left), 10, 5)),
+            MouseAction::DragEnd { start_row: 3, start_col: 4, end_row: 5, end_col: 0 }
+        );
+        assert_eq!(controller.target_pane(), Some(1));
+
+        assert_eq!(controller.handle_mouse_event(event(MouseEventKind::ScrollDown, 2, 2)), MouseAction::ScrollDown);
+        assert_eq!(controller.target_pane(), Some(0));
+        assert_eq!(controller.handle_mouse_event(event(MouseEventKind::Down(MouseButton::Right), 40, 2)), MouseAction::None);
+    }
+
     #[test]
     fn test_word_boundaries() {
         let line = "hello world test";
@@ -322,19 +401,19 @@ mod tests {
 
     #[test]
     fn test_text_selection_content() {
-        let lines = vec![
-            "first line".to_string(),
-            "second line".to_string(),
-            "third line".to_string(),
-        ];
+        let buffer = TextBuffer::from_lines(&[
+            "first line",
+            "second line",
+            "third line",
+        ]);
         
         // Single line selection
         let selection = TextSelection::new(1, 2, 1, 8);
-        assert_eq!(selection.get_selected_text(&lines), "cond l");
+        assert_eq!(selection.get_selected_text(&buffer), "cond l");
         
         // Multi-line selection
         let selection = TextSelection::new(0, 6, 2, 5);
-        assert_eq!(selection.get_selected_text(&lines), "line\nsecond line\nthird");
+        assert_eq!(selection.get_selected_text(&buffer), "line\nsecond line\nthird");
     }
 
     #[test]
@@ -1,4 +1,4 @@
-use hello_tui::{iocraft_file, syntax};
+use hello_tui::{iocraft_file, syntax, text_metrics, buffer::TextBuffer};
 use std::io;
 use std::env;
 
@@ -18,22 +18,22 @@ fn main() -> io::Result<()> {
         println!();
 
         // Try to load the file
-        match file_handler.load_file(filename) {
-            Ok(lines) => {
+        match file_handler.load_buffer(filename) {
+            Ok(buffer) => {
                 println!();
-                file_handler.display_file_info(filename, &lines);
+                file_handler.display_file_info(filename, &buffer);
                 println!();
                 
                 // Display syntax highlighting info
                 let highlighter = syntax::SyntaxHighlighter::new();
-                let syntax_name = highlighter.get_syntax_name(Some(filename));
+                let syntax_name = highlighter.get_syntax_name(Some(filename), &buffer);
                 println!("🎨 Syntax: {} (highlighting {})", 
                         syntax_name,
                         if highlighter.is_syntax_supported(&syntax_name) { "✅ supported" } else { "❌ not supported" });
                 
                 // Display file content preview
                 println!();
-                display_file_preview(&lines, filename, &highlighter, &syntax_name);
+                display_file_preview(&buffer, filename, &highlighter, &syntax_name);
                 
                 println!();
                 println!("💡 To edit this file interactively, run:");
@@ -82,37 +82,35 @@ fn main() -> io::Result<()> {
     Ok(())
 }
 
-fn display_file_preview(lines: &[String], filename: &str, highlighter: &syntax::SyntaxHighlighter, syntax_name: &str) {
+fn display_file_preview(buffer: &TextBuffer, filename: &str, highlighter: &syntax::SyntaxHighlighter, syntax_name: &str) {
     println!("┌─────────────────────────────────────────────────────────────┐");
     println!("│ 👀 File Preview: {:<45} │", filename);
     println!("├─────────────────────────────────────────────────────────────┤");
     
-    let preview_lines = lines.iter().take(15); // Show first 15 lines
-    let mut line_number = 1;
+    let preview_lines = buffer.lines().take(15); // Show first 15 lines
     
-    for line in preview_lines {
-        let display_line = if line.len() > 55 {
-            format!("{}...", &line[0..52])
+    for (line_number, line) in (1..).zip(preview_lines) {
+        let display_line = if text_metrics::str_width(&line) > 55 {
+            format!("{}...", text_metrics::truncate_to_width(&line, 52))
         } else {
-            line.clone()
+            line
         };
         
         // Apply syntax highlighting for supported languages
         let highlighted_line = if highlighter.is_syntax_supported(syntax_name) {
-            highlighter.highlight_line(&display_line, syntax_name)
+            syntax::to_ansi(&display_line, 0, &highlighter.highlight_line(&display_line, syntax_name))
         } else {
             display_line
         };
         
         println!("│ {:>2} │ {:<55} │", line_number, highlighted_line);
-        line_number += 1;
     }
     
-    if lines.len() > 15 {
-        println!("│    │ ... and {} more lines ...                       │", lines.len() - 15);
+    if buffer.len_lines() > 15 {
+        println!("│    │ ... and {} more lines ...                       │", buffer.len_lines() - 15);
     }
     
-    if lines.is_empty() {
+    if buffer.is_empty() {
         println!("│    │ (empty file)                                     │");
     }
     
@@ -1,110 +1,514 @@
 use std::io::{self, stdout, Write};
 use crossterm::{
     cursor::{MoveTo, Hide},
-    execute,
+    queue,
+    style::{Attribute, Color, Print, SetAttribute, SetBackgroundColor, SetForegroundColor, SetUnderlineColor},
+    terminal::{Clear, ClearType},
 };
-use crate::{syntax, linter, mouse, i18n};
+use unicode_segmentation::UnicodeSegmentation;
+use crate::{syntax, linter, mouse, i18n, search, wrap, text_metrics, buffer_list, buffer::TextBuffer, layout::Rect};
 use fluent_bundle::FluentValue;
 use std::collections::HashMap;
+use std::ops::Range;
+
+/// Rows above a pane's text area (the header)
+pub const HEADER_HEIGHT: usize = 1;
+/// Rows below a pane's text area (the status line)
+pub const STATUS_HEIGHT: usize = 1;
+/// Columns taken by the line-number gutter in front of the text
+pub const GUTTER_WIDTH: usize = 5;
+
+/// Columns available for text in a pane; one is kept free for the bar cursor
+pub fn content_width(pane_width: usize) -> usize {
+    pane_width.saturating_sub(GUTTER_WIDTH + 1)
+}
+
+/// Text rows between a pane's header and its status line
+pub fn visible_lines(pane_height: usize) -> usize {
+    pane_height.saturating_sub(HEADER_HEIGHT + STATUS_HEIGHT)
+}
+
+/// Area the editor's panes share: the whole terminal but its last row,
+/// which stays empty so printing the bottom status line never scrolls
+pub fn editor_area() -> io::Result<Rect> {
+    let (width, height) = crossterm::terminal::size()?;
+    Ok(Rect::new(0, 0, width as usize, (height as usize).saturating_sub(1)))
+}
+
+/// A lint issue's char columns on a line, for underlining
+type LintMark = (Range<usize>, linter::LintSeverity);
+
+/// Underlines for the lint issues on buffer row `row`, whose line is `line_len` chars long
+fn lint_marks(lint_issues: &[linter::LintIssue], row: usize, line_len: usize) -> Vec<LintMark> {
+    lint_issues
+        .iter()
+        .filter_map(|issue| Some((issue.columns_on(row, line_len)?, issue.severity.clone())))
+        .collect()
+}
+
+/// 256-color palette index issues of `severity` are underlined in
+fn underline_color(severity: &linter::LintSeverity) -> u8 {
+    match severity {
+        linter::LintSeverity::Error => 196,
+        linter::LintSeverity::Warning => 214,
+        linter::LintSeverity::Info => 39,
+        linter::LintSeverity::Hint => 244,
+    }
+}
+
+/// Like [`syntax::to_ansi`], with the text under lint issues underlined in the most severe one's color
+fn lint_ansi(text: &str, start_col: usize, spans: &[syntax::StyledSpan], marks: &[LintMark]) -> String {
+    if marks.is_empty() {
+        return syntax::to_ansi(text, start_col, spans);
+    }
+    let severity_at = |col: usize| marks.iter().filter(|(columns, _)| columns.contains(&col)).map(|(_, severity)| severity).min();
+    let chars: Vec<char> = text.chars().collect();
+    let clusters = text_metrics::clusters(text);
+    let mut escaped = String::new();
+    let mut idx = 0;
+    while idx < clusters.len() {
+        // Runs of whole clusters sharing an underline
+        let severity = severity_at(start_col + clusters[idx].col);
+        let mut end = idx + 1;
+        while end < clusters.len() && severity_at(start_col + clusters[end].col) == severity {
+            end += 1;
+        }
+        let (from, to) = (clusters[idx].col, clusters[end - 1].col + clusters[end - 1].len);
+        let run: String = chars[from..to].iter().collect();
+        let colored = syntax::to_ansi(&run, start_col + from, spans);
+        match severity {
+            Some(severity) => escaped.push_str(&format!("\x1b[4;58;5;{}m{}\x1b[24;59m", underline_color(severity), colored)),
+            None => escaped.push_str(&colored),
+        }
+        idx = end;
+    }
+    escaped
+}
+
+/// One entry of the header's tab bar
+pub struct Tab<'a> {
+    pub title: &'a str,
+    pub modified: bool,
+}
+
+/// Editor-wide state every pane is drawn with
+pub struct RenderContext<'a> {
+    pub tabs: &'a [Tab<'a>],
+    pub syntax_highlighter: &'a syntax::SyntaxHighlighter,
+    pub syntax_enabled: bool,
+    pub linter: &'a linter::Linter,
+    pub soft_wrap: bool,
+}
+
+/// What one pane shows: a buffer, the pane's view of it and what is known about it
+pub struct PaneContent<'a> {
+    pub buffer: &'a TextBuffer,
+    pub view: &'a buffer_list::ViewState,
+    pub filename: Option<&'a str>,
+    /// Language picked by hand, overriding detection
+    pub language: Option<&'a str>,
+    pub modified: bool,
+    pub lint_issues: &'a [linter::LintIssue],
+    pub highlights: &'a syntax::HighlightCache,
+    /// Position of the buffer among the tabs
+    pub tab: usize,
+    pub status_message: &'a str,
+    pub search: Option<&'a search::SearchState>,
+    pub focused: bool,
+}
+
+/// The part of a buffer line drawn on one screen row, with what styles it
+#[derive(Clone, Copy)]
+struct RowText<'a> {
+    text: &'a str,
+    /// Column of the row's first character within the buffer line
+    col_offset: usize,
+    content_width: usize,
+    spans: &'a [syntax::StyledSpan],
+    marks: &'a [LintMark],
+}
+
+/// Colors and attributes of a cell, as set by SGR escape codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CellStyle {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+    pub reverse: bool,
+    /// Drawn as a curly underline where the terminal supports one
+    pub underline: bool,
+    pub underline_color: Option<Color>,
+}
+
+impl CellStyle {
+    /// Update the style from the parameters of an `ESC [ ... m` sequence
+    fn apply_sgr(&mut self, params: &str) {
+        let codes: Vec<u8> = params.split(';').map(|code| code.parse().unwrap_or(0)).collect();
+        let mut codes = codes.into_iter();
+        while let Some(code) = codes.next() {
+            match code {
+                0 => *self = Self::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                4 => self.underline = true,
+                7 => self.reverse = true,
+                22 => (self.bold, self.dim) = (false, false),
+                24 => self.underline = false,
+                27 => self.reverse = false,
+                30..=37 => self.fg = Some(ansi_color(code - 30)),
+                39 => self.fg = None,
+                40..=47 => self.bg = Some(ansi_color(code - 40)),
+                49 => self.bg = None,
+                90..=97 => self.fg = Some(ansi_color(code - 90 + 8)),
+                100..=107 => self.bg = Some(ansi_color(code - 100 + 8)),
+                59 => self.underline_color = None,
+                38 | 48 | 58 => {
+                    let color = match codes.next() {
+                        Some(5) => codes.next().map(Color::AnsiValue),
+                        Some(2) => match (codes.next(), codes.next(), codes.next()) {
+                            (Some(r), Some(g), Some(b)) => Some(Color::Rgb { r, g, b }),
+                            _ => None,
+                        },
+                        _ => None,
+                    };
+                    match code {
+                        38 => self.fg = color,
+                        48 => self.bg = color,
+                        _ => self.underline_color = color,
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// One of the 16 basic terminal colors, by its SGR index
+fn ansi_color(idx: u8) -> Color {
+    const COLORS: [Color; 16] = [
+        Color::Black, Color::DarkRed, Color::DarkGreen, Color::DarkYellow,
+        Color::DarkBlue, Color::DarkMagenta, Color::DarkCyan, Color::Grey,
+        Color::DarkGrey, Color::Red, Color::Green, Color::Yellow,
+        Color::Blue, Color::Magenta, Color::Cyan, Color::White,
+    ];
+    COLORS[idx as usize % COLORS.len()]
+}
+
+/// One terminal cell: a grapheme cluster and its style.
+///
+/// The cell right of a wide character has an empty symbol; the terminal
+/// fills it when it prints the character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cell {
+    pub symbol: String,
+    pub style: CellStyle,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self { symbol: " ".to_string(), style: CellStyle::default() }
+    }
+}
+
+/// A screen's worth of cells that rendering draws into.
+///
+/// Text is written at a cursor like on a terminal, SGR escapes included,
+/// but only within the clip area; the renderer then compares the frame with
+/// the one on screen and sends just the cells that changed.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+    x: usize,
+    y: usize,
+    clip: Rect,
+    style: CellStyle,
+}
+
+impl Frame {
+    /// A blank frame of `width` by `height` cells
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Cell::default(); width * height],
+            x: 0,
+            y: 0,
+            clip: Rect::new(0, 0, width, height),
+            style: CellStyle::default(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn cell(&self, x: usize, y: usize) -> &Cell {
+        &self.cells[y * self.width + x]
+    }
+
+    /// Blank `area` and limit further writes to it, starting at its top-left corner
+    pub fn start_area(&mut self, area: Rect) {
+        let right = area.right().min(self.width);
+        for y in area.y..area.bottom().min(self.height) {
+            for x in area.x.min(right)..right {
+                self.set(x, y, Cell::default());
+            }
+        }
+        (self.x, self.y) = (area.x, area.y);
+        self.clip = area;
+        self.style = CellStyle::default();
+    }
+
+    /// Write text at the cursor, interpreting SGR escape sequences.
+    ///
+    /// Control characters show as spaces; whatever falls outside the clip
+    /// area is dropped.
+    pub fn print(&mut self, text: &str) {
+        let mut rest = text;
+        while !rest.is_empty() {
+            if let Some(sequence) = rest.strip_prefix("\x1b[") {
+                let end = sequence.find(|ch: char| ch.is_ascii_alphabetic()).unwrap_or(sequence.len());
+                if sequence[end..].starts_with('m') {
+                    self.style.apply_sgr(&sequence[..end]);
+                }
+                rest = sequence.get(end + 1..).unwrap_or("");
+                continue;
+            }
+            let end = match rest.find('\x1b') {
+                // A lone escape that starts no sequence is dropped
+                Some(0) => {
+                    rest = &rest[1..];
+                    continue;
+                }
+                Some(idx) => idx,
+                None => rest.len(),
+            };
+            for grapheme in rest[..end].graphemes(true) {
+                self.put(grapheme);
+            }
+            rest = &rest[end..];
+        }
+    }
+
+    /// Lets `write!` format straight into the frame
+    pub fn write_fmt(&mut self, args: std::fmt::Arguments) {
+        match args.as_str() {
+            Some(text) => self.print(text),
+            None => self.print(&args.to_string()),
+        }
+    }
+
+    fn put(&mut self, grapheme: &str) {
+        let in_clip = self.clip.contains(self.x, self.y) && self.x < self.width && self.y < self.height;
+        let width = text_metrics::grapheme_width(grapheme);
+        if !in_clip || width == 0 {
+            return;
+        }
+        let right = self.clip.right().min(self.width);
+        let symbol = if grapheme.chars().any(char::is_control) { " " } else { grapheme };
+        if self.x + width > right {
+            // A wide character that does not fit leaves a blank instead
+            self.set(self.x, self.y, Cell { symbol: " ".to_string(), style: self.style });
+            self.x = right;
+            return;
+        }
+        self.set(self.x, self.y, Cell { symbol: symbol.to_string(), style: self.style });
+        for x in self.x + 1..self.x + width {
+            self.set(x, self.y, Cell { symbol: String::new(), style: self.style });
+        }
+        self.x += width;
+    }
+
+    /// Store a cell, blanking any half of a wide character it breaks up
+    fn set(&mut self, x: usize, y: usize, cell: Cell) {
+        let idx = y * self.width + x;
+        if self.cells[idx].symbol.is_empty() && x > 0 && !cell.symbol.is_empty() {
+            let lead = (0..x).rev().find(|&x| !self.cells[y * self.width + x].symbol.is_empty()).unwrap_or(0);
+            for x in lead..x {
+                self.cells[y * self.width + x].symbol = " ".to_string();
+            }
+        }
+        let mut next = x + 1;
+        while next < self.width && self.cells[y * self.width + next].symbol.is_empty() && !self.cells[idx].symbol.is_empty() {
+            self.cells[y * self.width + next].symbol = " ".to_string();
+            next += 1;
+        }
+        self.cells[idx] = cell;
+    }
+
+    /// Queue the commands that turn `previous`, what the terminal shows, into this frame.
+    ///
+    /// Without a previous frame of the same size the screen is cleared and
+    /// everything but blank cells is drawn.
+    pub fn draw_diff(&self, previous: Option<&Frame>, out: &mut impl Write) -> io::Result<()> {
+        let previous = previous.filter(|frame| (frame.width, frame.height) == (self.width, self.height));
+        if previous.is_none() {
+            queue!(out, Clear(ClearType::All))?;
+        }
+        let blank = Cell::default();
+        let mut position = None;
+        let mut style = None;
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let cell = self.cell(x, y);
+                let shown = previous.map_or(&blank, |frame| frame.cell(x, y));
+                if cell.symbol.is_empty() || cell == shown {
+                    continue;
+                }
+                if position != Some((x, y)) {
+                    queue!(out, MoveTo(x as u16, y as u16))?;
+                }
+                if style != Some(cell.style) {
+                    queue_style(out, cell.style)?;
+                    style = Some(cell.style);
+                }
+                queue!(out, Print(&cell.symbol))?;
+                // Terminals disagree on the width of some wide characters, so
+                // only trust the cursor position after narrow ones
+                position = (text_metrics::grapheme_width(&cell.symbol) == 1).then_some((x + 1, y));
+            }
+        }
+        queue!(out, SetAttribute(Attribute::Reset))
+    }
+}
+
+fn queue_style(out: &mut impl Write, style: CellStyle) -> io::Result<()> {
+    queue!(out, SetAttribute(Attribute::Reset))?;
+    if let Some(fg) = style.fg {
+        queue!(out, SetForegroundColor(fg))?;
+    }
+    if let Some(bg) = style.bg {
+        queue!(out, SetBackgroundColor(bg))?;
+    }
+    if style.bold {
+        queue!(out, SetAttribute(Attribute::Bold))?;
+    }
+    if style.dim {
+        queue!(out, SetAttribute(Attribute::Dim))?;
+    }
+    if style.reverse {
+        queue!(out, SetAttribute(Attribute::Reverse))?;
+    }
+    if style.underline {
+        // Terminals without curly underlines keep the plain one
+        queue!(out, SetAttribute(Attribute::Underlined), SetAttribute(Attribute::Undercurled))?;
+    }
+    if let Some(color) = style.underline_color.filter(|_| style.underline) {
+        queue!(out, SetUnderlineColor(color))?;
+    }
+    Ok(())
+}
 
 /// Rendering module for the text editor
-pub struct EditorRenderer;
+pub struct EditorRenderer {
+    // What the terminal currently shows
+    previous: Option<Frame>,
+}
 
 impl EditorRenderer {
     pub fn new() -> Self {
-        Self
+        Self { previous: None }
     }
 
-    /// Main render function for the editor
-    pub fn render_editor(
-        &self,
-        lines: &[String],
-        cursor_row: usize,
-        cursor_col: usize,
-        filename: Option<&str>,
-        modified: bool,
-        status_message: &str,
-        syntax_highlighter: &syntax::SyntaxHighlighter,
-        syntax_enabled: bool,
-        scroll_offset: usize,
-        lint_issues: &[linter::LintIssue],
-        linter: &linter::Linter,
-        text_selection: Option<&mouse::TextSelection>,
-    ) -> io::Result<()> {
-        // Move to top and hide cursor, but don't clear entire screen to reduce flicker
-        execute!(stdout(), MoveTo(0, 0), Hide)?;
+    /// Show `frame`, sending only the cells that differ from the last one
+    pub fn present(&mut self, frame: Frame) -> io::Result<()> {
+        let mut out = stdout().lock();
+        queue!(out, Hide)?;
+        frame.draw_diff(self.previous.as_ref(), &mut out)?;
+        out.flush()?;
+        self.previous = Some(frame);
+        Ok(())
+    }
+
+    /// Forget what is on screen, so the next frame is drawn in full
+    pub fn invalidate(&mut self) {
+        self.previous = None;
+    }
 
-        let syntax_name = if syntax_enabled {
-            syntax_highlighter.get_syntax_name(filename)
+    /// Render one pane: its header, text and status line, within `area`
+    pub fn render_editor(&self, frame: &mut Frame, context: &RenderContext, pane: &PaneContent, area: Rect) -> io::Result<()> {
+        let syntax_name = if context.syntax_enabled {
+            context.syntax_highlighter.syntax_name_for(pane.language, pane.filename, pane.buffer)
         } else {
             i18n::t("ui-plain-text")
         };
+        let palette = context.syntax_highlighter.ui_palette();
+
+        self.render_header(frame, context, pane, &syntax_name, &palette, area)?;
+        self.render_content_lines(frame, context, pane, area)?;
+        self.render_status_line(frame, context.linter, pane, &syntax_name, &palette, area)?;
+
+        Ok(())
+    }
 
-        let terminal_size = crossterm::terminal::size()?;
-        let terminal_width = terminal_size.0 as usize;
-        let terminal_height = terminal_size.1 as usize;
-        let visible_lines = terminal_height.saturating_sub(3); // Reserve space for header and status
-        let line_number_width = 4; // Fixed width for cleaner alignment
-        let content_width = terminal_width.saturating_sub(line_number_width + 2); // Account for line numbers and space
-
-        // Render header
-        self.render_header(filename, modified, &syntax_name, syntax_enabled, terminal_width)?;
-
-        // Render content lines
-        self.render_content_lines(
-            lines,
-            cursor_row,
-            cursor_col,
-            scroll_offset,
-            visible_lines,
-            content_width,
-            syntax_highlighter,
-            syntax_enabled,
-            &syntax_name,
-            lint_issues,
-            linter,
-            text_selection,
-        )?;
-
-        // Render status line
-        self.render_status_line(
-            lines,
-            cursor_row,
-            cursor_col,
-            status_message,
-            &syntax_name,
-            lint_issues,
-            linter,
-            text_selection,
-            terminal_width,
-        )?;
-
-        stdout().flush()?;
+    /// Draw the columns between side-by-side panes
+    pub fn render_separators(&self, frame: &mut Frame, separators: &[Rect]) -> io::Result<()> {
+        for separator in separators {
+            for row in 0..separator.height {
+                self.start_row(frame, *separator, row)?;
+                write!(frame, "\x1b[90m│\x1b[0m");
+            }
+        }
         Ok(())
     }
 
-    /// Render the header with file info
+    /// Blank `row` of `area` and start writing at its first cell; text never spills past the area
+    fn start_row(&self, frame: &mut Frame, area: Rect, row: usize) -> io::Result<()> {
+        frame.start_area(Rect::new(area.x, area.y + row, area.width, 1));
+        Ok(())
+    }
+
+    /// Render the header with file info, or a tab bar when several buffers are open.
+    ///
+    /// The focused pane's header stands out from the others in the theme's colors.
     fn render_header(
         &self,
-        filename: Option<&str>,
-        modified: bool,
+        frame: &mut Frame,
+        context: &RenderContext,
+        pane: &PaneContent,
         syntax_name: &str,
-        syntax_enabled: bool,
-        terminal_width: usize,
+        palette: &syntax::UiPalette,
+        area: Rect,
     ) -> io::Result<()> {
+        self.start_row(frame, area, 0)?;
+        let (tabs, syntax_enabled) = (context.tabs, context.syntax_enabled);
+        let colors = if pane.focused { &palette.header } else { &palette.header_inactive };
         let no_file_text = i18n::t("ui-no-file");
-        let filename = filename.unwrap_or(&no_file_text);
-        let modified_indicator = if modified { " ●" } else { "" };
-        let syntax_indicator = if syntax_enabled { 
+        let filename = pane.filename.unwrap_or(&no_file_text);
+        let modified_indicator = if pane.modified { " ●" } else { "" };
+        let syntax_indicator = if syntax_enabled && pane.language.is_some() {
+            let mut args = HashMap::new();
+            args.insert("name".to_string(), FluentValue::from(syntax_name));
+            format!(" [{}]", i18n::t_with_args("ui-language-picked", args))
+        } else if syntax_enabled { 
             format!(" [{}]", syntax_name) 
         } else { 
             format!(" [{}]", i18n::t("ui-plain-text"))
         };
         
-        let header = format!("📝 {}{}{}", filename, modified_indicator, syntax_indicator);
-        let padding = terminal_width.saturating_sub(header.chars().count()).min(terminal_width);
-        print!("{}{}{}\x1b[0m\r\n",
-            "\x1b[44;37m", // Blue background, white text
+        if tabs.len() > 1 && area.width > text_metrics::str_width(&syntax_indicator) + 3 {
+            let available = area.width - text_metrics::str_width(&syntax_indicator) - 3;
+            let (tab_bar, width) = self.tab_bar(tabs, pane.tab, available);
+            let padding = available.saturating_sub(width);
+            write!(frame, "{}📝 {}{}{}\x1b[0m", colors, tab_bar, " ".repeat(padding), syntax_indicator);
+            return Ok(());
+        }
+
+        let header = text_metrics::truncate_to_width(
+            &format!("📝 {}{}{}", filename, modified_indicator, syntax_indicator),
+            area.width,
+        );
+        let padding = area.width.saturating_sub(text_metrics::str_width(&header));
+        write!(frame, "{}{}{}\x1b[0m", // Header colors from the theme
+            colors,
             header,
             " ".repeat(padding)
         );
@@ -112,93 +516,175 @@ impl EditorRenderer {
         Ok(())
     }
 
-    /// Render the main content lines
-    fn render_content_lines(
-        &self,
-        lines: &[String],
-        cursor_row: usize,
-        cursor_col: usize,
-        scroll_offset: usize,
-        visible_lines: usize,
-        content_width: usize,
-        syntax_highlighter: &syntax::SyntaxHighlighter,
-        syntax_enabled: bool,
-        syntax_name: &str,
-        lint_issues: &[linter::LintIssue],
-        linter: &linter::Linter,
-        text_selection: Option<&mouse::TextSelection>,
-    ) -> io::Result<()> {
-        // Clear each line as we render to prevent artifacts
-        for (_, actual_row) in (scroll_offset..scroll_offset + visible_lines)
-            .enumerate()
-            .take_while(|(_, actual_row)| *actual_row < lines.len())
-        {
-            // Clear current line
-            print!("\x1b[2K");
-            
-            let line = &lines[actual_row];
-            
-            // Render line number with lint indicator
-            self.render_line_number(actual_row, cursor_row, lint_issues, linter)?;
-            
-            // Content area - handle text properly
-            let display_line = if line.len() > content_width {
-                format!("{}…", &line[..content_width.saturating_sub(1)])
+    /// Tabs that fit in `available` cells, scrolled to keep the active one visible.
+    ///
+    /// Returns the styled text and its width in cells.
+    fn tab_bar(&self, tabs: &[Tab], active_tab: usize, available: usize) -> (String, usize) {
+        let labels: Vec<String> = tabs
+            .iter()
+            .map(|tab| format!(" {}{} ", tab.title, if tab.modified { " ●" } else { "" }))
+            .collect();
+        // Each tab after the first is preceded by a one-cell separator
+        let widths: Vec<usize> = labels.iter().map(|label| text_metrics::str_width(label) + 1).collect();
+        let range = buffer_list::visible_tabs(&widths, active_tab, available + 1);
+
+        let mut bar = String::new();
+        let mut width = 0;
+        for idx in range {
+            if !bar.is_empty() {
+                bar.push('│');
+            }
+            let label = text_metrics::truncate_to_width(&labels[idx], available.saturating_sub(width));
+            width += text_metrics::str_width(&label) + usize::from(width > 0);
+            if idx == active_tab {
+                // Active tab in reverse video
+                bar.push_str(&format!("\x1b[7m{}\x1b[27m", label));
             } else {
-                line.clone()
-            };
-            
-            // Check if this line has selection
-            let line_has_selection = text_selection
-                .map(|sel| sel.start_row <= actual_row && actual_row <= sel.end_row)
-                .unwrap_or(false);
-            
-            if actual_row == cursor_row && !line_has_selection {
-                // Current line with cursor
-                self.render_current_line(
-                    &display_line,
-                    cursor_col,
-                    content_width,
-                    syntax_highlighter,
-                    syntax_enabled,
-                    syntax_name,
-                )?;
-            } else if line_has_selection {
-                // Render line with selection highlighting
-                self.render_line_with_selection(
-                    &display_line,
-                    actual_row,
-                    cursor_row,
-                    cursor_col,
-                    syntax_name,
-                    content_width,
-                    text_selection.unwrap(),
-                    syntax_highlighter,
-                    syntax_enabled,
-                )?;
+                bar.push_str(&label);
+            }
+        }
+        (bar, width)
+    }
+
+    /// Render the main content lines.
+    ///
+    /// Without soft wrap every buffer line takes one row, starts at screen
+    /// cell `scroll_offset_x` and is cut off with "…"; with it, long lines
+    /// continue on further rows marked in the gutter. Widths are measured in
+    /// terminal cells, so wide and combining characters line up.
+    fn render_content_lines(&self, frame: &mut Frame, context: &RenderContext, pane: &PaneContent, area: Rect) -> io::Result<()> {
+        let PaneContent { buffer, view, lint_issues, search, .. } = *pane;
+        let (linter, soft_wrap) = (context.linter, context.soft_wrap);
+        let (cursor_row, cursor_col) = (view.cursor.row(), view.cursor.col());
+        let (scroll_offset, scroll_offset_x) = (view.scroll_offset, view.scroll_offset_x);
+        let text_selection = view.text_selection.as_ref();
+        let extra_carets = view.cursor.extra_carets();
+        let highlights = context.syntax_enabled.then_some(pane.highlights);
+        let visible_lines = visible_lines(area.height); // Reserve space for header and status
+        let content_width = content_width(area.width);
+        let hidden_rows = if soft_wrap {
+            wrap::hidden_rows(buffer, scroll_offset, cursor_row, cursor_col, content_width, visible_lines)
+        } else {
+            0
+        };
+        let mut rows_rendered = 0;
+
+        for actual_row in scroll_offset..buffer.len_lines() {
+            if rows_rendered >= visible_lines {
+                break;
+            }
+            let line = buffer.line(actual_row);
+            let chars: Vec<char> = line.chars().collect();
+            let segments = if soft_wrap {
+                wrap::wrap_line(&line, content_width)
             } else {
-                // Regular line - simple and clean
-                if syntax_enabled {
-                    let highlighted = syntax_highlighter.highlight_line(&display_line, syntax_name);
-                    print!("{}", highlighted);
+                vec![(text_metrics::col_at_display(&line, scroll_offset_x), chars.len())]
+            };
+
+            // Selections and carets (primary and secondary) touching this line
+            let selections: Vec<&mouse::TextSelection> = text_selection
+                .into_iter()
+                .chain(extra_carets.iter().filter_map(|caret| caret.selection.as_ref()))
+                .filter(|sel| sel.start_row <= actual_row && actual_row <= sel.end_row)
+                .collect();
+            let extra_caret_cols: Vec<usize> = extra_carets
+                .iter()
+                .filter(|caret| caret.position.row == actual_row)
+                .map(|caret| caret.position.col)
+                .collect();
+
+            // Secondary carets are drawn by the per-character path as well
+            let line_has_selection = !selections.is_empty() || !extra_caret_cols.is_empty();
+
+            // Search matches on this line, if a search is active
+            let line_matches = search
+                .map(|search| search.line_matches(buffer, actual_row))
+                .unwrap_or_default();
+
+            let spans = highlights.map_or(&[][..], |highlights| highlights.line(actual_row));
+            let marks = if linter.is_enabled() { lint_marks(lint_issues, actual_row, chars.len()) } else { Vec::new() };
+
+            // Rows of a tall top line above the cursor may be scrolled past
+            let skipped = if actual_row == scroll_offset { hidden_rows } else { 0 };
+            for (idx, &(start, end)) in segments.iter().enumerate().skip(skipped) {
+                if rows_rendered >= visible_lines {
+                    break;
+                }
+                // Clear each line as we render to prevent artifacts
+                self.start_row(frame, area, HEADER_HEIGHT + rows_rendered)?;
+
+                if idx == 0 {
+                    // Render line number with lint indicator
+                    self.render_line_number(frame, actual_row, cursor_row, lint_issues, linter)?;
                 } else {
-                    print!("{}", display_line);
+                    self.render_continuation_marker(frame, actual_row == cursor_row);
                 }
+
+                // Carets on this row, relative to its first column
+                let on_segment = |col: &usize| *col >= start && wrap::locate(&segments, *col).0 == idx;
+                let mut caret_cols: Vec<usize> = extra_caret_cols
+                    .iter()
+                    .filter(|col| on_segment(col))
+                    .map(|col| col - start)
+                    .collect();
+                let segment_cursor = (actual_row == cursor_row && on_segment(&cursor_col)).then(|| cursor_col - start);
+                caret_cols.extend(segment_cursor);
+
+                let segment: String = chars[start..end].iter().collect();
+                let segment_matches: Vec<(usize, usize, bool)> = line_matches
+                    .iter()
+                    .filter(|(match_start, match_end, _)| *match_start < end && *match_end > start)
+                    .map(|&(match_start, match_end, current)| {
+                        (match_start.saturating_sub(start), match_end.min(end) - start, current)
+                    })
+                    .collect();
+
+                // Content area - cut off by screen width, never inside a grapheme cluster
+                let display_line = if text_metrics::str_width(&segment) > content_width {
+                    format!("{}…", text_metrics::truncate_to_width(&segment, content_width.saturating_sub(1)))
+                } else {
+                    segment.clone()
+                };
+
+                let row_text = RowText { text: &segment, col_offset: start, content_width, spans, marks: &marks };
+
+                if !segment_matches.is_empty() && !line_has_selection {
+                    // Render line with search match highlighting
+                    self.render_line_with_matches(frame, row_text, segment_cursor, &segment_matches, actual_row == cursor_row)?;
+                } else if actual_row == cursor_row && !line_has_selection {
+                    // Current line, with the cursor if it is on this row
+                    self.render_current_line(frame, RowText { text: &display_line, ..row_text }, segment_cursor)?;
+                } else if line_has_selection {
+                    // Render line with selection highlighting
+                    self.render_line_with_selection(frame, row_text, actual_row, &caret_cols, &selections)?;
+                } else {
+                    // Regular line - simple and clean
+                    write!(frame, "{}", lint_ansi(&display_line, start, spans, &marks));
+                }
+
+                rows_rendered += 1;
             }
-            
-            // Move to next line
-            print!("\r\n");
         }
 
         // Render empty lines
-        self.render_empty_lines(scroll_offset, visible_lines, lines.len())?;
+        self.render_empty_lines(frame, rows_rendered, visible_lines, area)?;
 
         Ok(())
     }
 
+    /// Gutter for the continuation rows of a soft-wrapped line
+    fn render_continuation_marker(&self, frame: &mut Frame, is_current_line: bool) {
+        if is_current_line {
+            write!(frame, "\x1b[43;30m   ↪\x1b[0m ");
+        } else {
+            write!(frame, "\x1b[100;37m   ↪\x1b[0m ");
+        }
+    }
+
     /// Render line number with lint indicator
     fn render_line_number(
         &self,
+        frame: &mut Frame,
         actual_row: usize,
         cursor_row: usize,
         lint_issues: &[linter::LintIssue],
@@ -208,110 +694,165 @@ impl EditorRenderer {
         let lint_indicator = self.get_lint_indicator_for_line(actual_row, lint_issues, linter);
         
         if is_current_line {
-            print!("\x1b[43;30m{:>3}{}\x1b[0m ", actual_row + 1, if lint_indicator.is_empty() { " " } else { &lint_indicator });
+            write!(frame, "\x1b[43;30m{:>3}{}\x1b[0m ", actual_row + 1, if lint_indicator.is_empty() { " " } else { &lint_indicator });
         } else {
-            print!("\x1b[100;37m{:>3}{}\x1b[0m ", actual_row + 1, if lint_indicator.is_empty() { " " } else { &lint_indicator });
+            write!(frame, "\x1b[100;37m{:>3}{}\x1b[0m ", actual_row + 1, if lint_indicator.is_empty() { " " } else { &lint_indicator });
         }
         
         Ok(())
     }
 
     /// Render current line with cursor
-    fn render_current_line(
-        &self,
-        display_line: &str,
-        cursor_col: usize,
-        content_width: usize,
-        syntax_highlighter: &syntax::SyntaxHighlighter,
-        syntax_enabled: bool,
-        syntax_name: &str,
-    ) -> io::Result<()> {
+    fn render_current_line(&self, frame: &mut Frame, row_text: RowText, cursor_col: Option<usize>) -> io::Result<()> {
+        let RowText { text: display_line, col_offset, content_width, spans, marks } = row_text;
         // Current line with cursor - add subtle background
-        print!("\x1b[48;5;235m"); // Dark gray background
+        write!(frame, "\x1b[48;5;235m"); // Dark gray background
         
-        let cursor_pos = cursor_col.min(display_line.len());
-        let (before_cursor, after_cursor) = display_line.split_at(cursor_pos);
+        // Split by char, not byte, so multi-byte text cannot panic
+        let char_count = display_line.chars().count();
+        let cursor_pos = cursor_col.unwrap_or(char_count).min(char_count);
+        let split = display_line.char_indices().nth(cursor_pos).map_or(display_line.len(), |(idx, _)| idx);
+        let (before_cursor, after_cursor) = display_line.split_at(split);
 
         // Apply syntax highlighting
-        if syntax_enabled {
-            let highlighted_before = syntax_highlighter.highlight_line(before_cursor, syntax_name);
-            print!("{}", highlighted_before);
-        } else {
-            print!("{}", before_cursor);
-        }
+        write!(frame, "{}", lint_ansi(before_cursor, col_offset, spans, marks));
         
         // Modern cursor - vertical line
-        print!("\x1b[48;5;220;30m│\x1b[48;5;235m");
+        if cursor_col.is_some() {
+            write!(frame, "\x1b[48;5;220;30m│\x1b[48;5;235m");
+        }
         
         if !after_cursor.is_empty() {
-            if syntax_enabled {
-                let highlighted_after = syntax_highlighter.highlight_line(after_cursor, syntax_name);
-                print!("{}", highlighted_after);
-            } else {
-                print!("{}", after_cursor);
-            }
+            write!(frame, "{}", lint_ansi(after_cursor, col_offset + cursor_pos, spans, marks));
         }
         
         // Fill rest of line with background
-        let used_width = before_cursor.chars().count() + 1 + after_cursor.chars().count();
+        let used_width = text_metrics::str_width(display_line) + usize::from(cursor_col.is_some());
         if used_width < content_width {
-            print!("{}", " ".repeat(content_width - used_width));
+            write!(frame, "{}", " ".repeat(content_width - used_width));
         }
-        print!("\x1b[0m"); // Reset background
+        write!(frame, "\x1b[0m"); // Reset background
         
         Ok(())
     }
 
-    /// Render line with selection highlighting
+    /// Render line with selection highlighting and one or more carets
     fn render_line_with_selection(
         &self,
-        line: &str,
+        frame: &mut Frame,
+        row_text: RowText,
         row: usize,
-        cursor_row: usize,
-        cursor_col: usize,
-        syntax_name: &str,
-        content_width: usize,
-        selection: &mouse::TextSelection,
-        syntax_highlighter: &syntax::SyntaxHighlighter,
-        syntax_enabled: bool,
+        caret_cols: &[usize],
+        selections: &[&mouse::TextSelection],
     ) -> io::Result<()> {
+        let RowText { text: line, col_offset, content_width, spans, marks } = row_text;
         let chars: Vec<char> = line.chars().collect();
-        let max_chars = chars.len().min(content_width);
+        let max_chars = text_metrics::fit_width(line, content_width);
         
-        for (col, ch) in chars.iter().take(max_chars).enumerate() {
-            let is_selected = selection.contains(row, col);
-            let is_cursor = row == cursor_row && col == cursor_col;
+        // Whole grapheme clusters, so combining marks stay with their base
+        for cluster in text_metrics::clusters(line).iter().take_while(|cluster| cluster.col < max_chars) {
+            let col = cluster.col;
+            let ch: String = chars[col..col + cluster.len].iter().collect();
+            let is_selected = selections.iter().any(|selection| selection.contains(row, col_offset + col));
+            let is_cursor = caret_cols.contains(&col);
             
             if is_cursor && is_selected {
                 // Cursor within selection - bright highlight
-                print!("\x1b[48;5;220;30m{}\x1b[0m", ch); // Yellow background, black text
+                write!(frame, "\x1b[48;5;220;30m{}\x1b[0m", ch); // Yellow background, black text
             } else if is_cursor {
                 // Cursor outside selection - vertical line cursor
-                print!("\x1b[48;5;220;30m│\x1b[0m"); // Yellow cursor line
-                print!("\x1b[48;5;220;30m{}\x1b[0m", ch); // Character with cursor background
+                write!(frame, "\x1b[48;5;220;30m│\x1b[0m"); // Yellow cursor line
+                write!(frame, "\x1b[48;5;220;30m{}\x1b[0m", ch); // Character with cursor background
             } else if is_selected {
                 // Selected text - blue highlight
-                print!("\x1b[48;5;68;37m{}\x1b[0m", ch); // Blue background, white text
+                write!(frame, "\x1b[48;5;68;37m{}\x1b[0m", ch); // Blue background, white text
             } else {
                 // Normal text
-                if syntax_enabled {
-                    let char_str = ch.to_string();
-                    let highlighted = syntax_highlighter.highlight_line(&char_str, syntax_name);
-                    print!("{}", highlighted);
-                } else {
-                    print!("{}", ch);
-                }
+                write!(frame, "{}", lint_ansi(&ch, col_offset + col, spans, marks));
             }
         }
         
         // Handle cursor at end of line
-        if row == cursor_row && cursor_col >= max_chars {
-            print!("\x1b[48;5;220;30m│\x1b[0m"); // Cursor at end of line
+        if caret_cols.iter().any(|col| *col >= max_chars) {
+            write!(frame, "\x1b[48;5;220;30m│\x1b[0m"); // Cursor at end of line
         }
         
         // Show truncation indicator if line was cut off
-        if chars.len() > content_width {
-            print!("\x1b[2m…\x1b[0m"); // Dim ellipsis
+        if chars.len() > max_chars {
+            write!(frame, "\x1b[2m…\x1b[0m"); // Dim ellipsis
+        }
+        
+        Ok(())
+    }
+
+    /// Render line with search matches highlighted over its syntax colors
+    fn render_line_with_matches(
+        &self,
+        frame: &mut Frame,
+        row_text: RowText,
+        cursor_col: Option<usize>,
+        line_matches: &[(usize, usize, bool)],
+        current_line: bool,
+    ) -> io::Result<()> {
+        let RowText { text: line, col_offset, content_width, spans, marks } = row_text;
+        let chars: Vec<char> = line.chars().collect();
+        let max_chars = text_metrics::fit_width(line, content_width);
+        let clusters = text_metrics::clusters(line);
+        let visible = &clusters[..clusters.iter().take_while(|cluster| cluster.col < max_chars).count()];
+        // The current line keeps its subtle background between matches
+        let base = if current_line { "\x1b[48;5;235m" } else { "" };
+        let match_at = |col: usize| {
+            line_matches
+                .iter()
+                .find(|(start, end, _)| *start <= col && col < *end)
+                .map(|(_, _, current)| *current)
+        };
+        write!(frame, "{}", base);
+
+        // Runs of whole clusters inside or outside one match, split at the cursor
+        let mut idx = 0;
+        while idx < visible.len() {
+            let from = visible[idx].col;
+            let found = match_at(from);
+            if cursor_col == Some(from) {
+                write!(frame, "\x1b[48;5;220;30m│\x1b[0m{}", base); // Yellow cursor line
+            }
+            let mut end = idx + 1;
+            while end < visible.len() && match_at(visible[end].col) == found && cursor_col != Some(visible[end].col) {
+                end += 1;
+            }
+            let to = visible[end - 1].col + visible[end - 1].len;
+            let run: String = chars[from..to].iter().collect();
+            let styled = lint_ansi(&run, col_offset + from, spans, marks);
+            match found {
+                // Current match - orange highlight
+                Some(true) => write!(frame, "\x1b[48;5;208;30m{}\x1b[0m{}", styled, base),
+                // Other matches - dim yellow highlight
+                Some(false) => write!(frame, "\x1b[48;5;58;37m{}\x1b[0m{}", styled, base),
+                None => write!(frame, "{}", styled),
+            }
+            idx = end;
+        }
+        
+        // Handle cursor at end of line
+        let cursor_at_end = cursor_col.is_some_and(|col| col >= max_chars);
+        if cursor_at_end {
+            write!(frame, "\x1b[48;5;220;30m│\x1b[0m{}", base);
+        }
+        
+        // Show truncation indicator if line was cut off
+        let truncated = chars.len() > max_chars;
+        if truncated {
+            write!(frame, "\x1b[2m…\x1b[0m{}", base); // Dim ellipsis
+        }
+
+        if current_line {
+            let text: String = chars[..max_chars.min(chars.len())].iter().collect();
+            let used_width = text_metrics::str_width(&text) + usize::from(cursor_col.is_some()) + usize::from(truncated);
+            if used_width < content_width {
+                write!(frame, "{}", " ".repeat(content_width - used_width));
+            }
+            write!(frame, "\x1b[0m");
         }
         
         Ok(())
@@ -320,19 +861,14 @@ impl EditorRenderer {
     /// Render empty lines (tilde indicators)
     fn render_empty_lines(
         &self,
-        scroll_offset: usize,
+        frame: &mut Frame,
+        rows_rendered: usize,
         visible_lines: usize,
-        total_lines: usize,
+        area: Rect,
     ) -> io::Result<()> {
-        let lines_rendered = (scroll_offset..scroll_offset + visible_lines)
-            .take_while(|actual_row| *actual_row < total_lines)
-            .count();
-        
-        let terminal_height = crossterm::terminal::size()?.1 as usize;
-        for _ in lines_rendered..visible_lines.min(terminal_height.saturating_sub(3)) {
-            print!("\x1b[2K");
-            print!("\x1b[100;37m   ~ \x1b[0m");
-            print!("\r\n");
+        for row in rows_rendered..visible_lines {
+            self.start_row(frame, area, HEADER_HEIGHT + row)?;
+            write!(frame, "\x1b[100;37m   ~ \x1b[0m");
         }
         
         Ok(())
@@ -341,18 +877,19 @@ impl EditorRenderer {
     /// Render status line
     fn render_status_line(
         &self,
-        lines: &[String],
-        cursor_row: usize,
-        cursor_col: usize,
-        status_message: &str,
-        syntax_name: &str,
-        lint_issues: &[linter::LintIssue],
+        frame: &mut Frame,
         linter: &linter::Linter,
-        text_selection: Option<&mouse::TextSelection>,
-        terminal_width: usize,
+        pane: &PaneContent,
+        syntax_name: &str,
+        palette: &syntax::UiPalette,
+        area: Rect,
     ) -> io::Result<()> {
+        let PaneContent { buffer, view, lint_issues, status_message, .. } = *pane;
+        let (cursor_row, cursor_col) = (view.cursor.row(), view.cursor.col());
+        let text_selection = view.text_selection.as_ref();
+
         // Enhanced status line with better formatting
-        print!("\x1b[2K"); // Clear status line
+        self.start_row(frame, area, area.height.saturating_sub(STATUS_HEIGHT))?;
         
         let mut args = HashMap::new();
         args.insert("row".to_string(), FluentValue::from(cursor_row + 1));
@@ -363,7 +900,7 @@ impl EditorRenderer {
         );
         
         let selection_info = if let Some(sel) = text_selection {
-            let selected_text = sel.get_selected_text(lines);
+            let selected_text = sel.get_selected_text(buffer);
             let char_count = selected_text.chars().count();
             
             let mut args = HashMap::new();
@@ -379,10 +916,10 @@ impl EditorRenderer {
         let lint_info = if linter.is_enabled() && !lint_issues.is_empty() {
             let (errors, warnings, info, hints) = linter.get_issue_counts(lint_issues);
             let mut parts = Vec::new();
-            if errors > 0 { parts.push(format!("{}❌{}", errors, if errors > 1 { "" } else { "" })); }
-            if warnings > 0 { parts.push(format!("{}⚠️{}", warnings, if warnings > 1 { "" } else { "" })); }
-            if info > 0 { parts.push(format!("{}ℹ️{}", info, if info > 1 { "" } else { "" })); }
-            if hints > 0 { parts.push(format!("{}💡{}", hints, if hints > 1 { "" } else { "" })); }
+            if errors > 0 { parts.push(format!("{}❌", errors)); }
+            if warnings > 0 { parts.push(format!("{}⚠️", warnings)); }
+            if info > 0 { parts.push(format!("{}ℹ️", info)); }
+            if hints > 0 { parts.push(format!("{}💡", hints)); }
             if parts.is_empty() { String::new() } else { format!(" | {}", parts.join(" ")) }
         } else if linter.is_enabled() {
             " | ✅".to_string()
@@ -390,7 +927,7 @@ impl EditorRenderer {
             String::new()
         };
         
-        let line_count = lines.len();
+        let line_count = buffer.len_lines();
         let mut args = HashMap::new();
         args.insert("count".to_string(), FluentValue::from(line_count));
         let line_count_text = i18n::t_with_args("ui-line-count", args);
@@ -398,32 +935,24 @@ impl EditorRenderer {
         let right_status = format!("{} | {}{}", syntax_name, line_count_text, lint_info);
         let center_message = status_message;
         
-        // Status line with dark background
-        print!("\x1b[48;5;235;37m{}", left_status);
-        
-        // Center the message
-        let left_width = left_status.chars().count();
-        let right_width = right_status.chars().count();
-        let center_width = center_message.chars().count();
-        let available_width = terminal_width.saturating_sub(left_width + right_width);
+        // Center the message, cutting the line off at the pane's edge
+        let left_width = text_metrics::str_width(&left_status);
+        let right_width = text_metrics::str_width(&right_status);
+        let center_width = text_metrics::str_width(center_message);
+        let available_width = area.width.saturating_sub(left_width + right_width);
         
-        if center_width < available_width {
+        let status = if center_width < available_width {
             let center_padding = (available_width - center_width) / 2;
-            print!("{}", " ".repeat(center_padding));
-            print!("{}", center_message);
-            print!("{}", " ".repeat(center_padding));
+            let right_padding = available_width - center_width - center_padding;
+            format!("{}{}{}{}{}", left_status, " ".repeat(center_padding), center_message, " ".repeat(right_padding), right_status)
         } else {
-            print!(" {} ", center_message);
-        }
-        
-        print!("{}", right_status);
+            format!("{} {} {}", left_status, center_message, right_status)
+        };
+        let status = text_metrics::truncate_to_width(&status, area.width);
         
-        // Fill remaining space
-        let used = left_width + center_width + right_width + if center_width < available_width { available_width - center_width } else { 2 };
-        if used < terminal_width {
-            print!("{}", " ".repeat(terminal_width - used));
-        }
-        print!("\x1b[0m\r"); // Reset formatting and return to start of line
+        // Status line in the theme's colors, filling the pane's width
+        let padding = area.width.saturating_sub(text_metrics::str_width(&status));
+        write!(frame, "{}{}{}\x1b[0m", palette.status, status, " ".repeat(padding));
 
         Ok(())
     }
@@ -439,30 +968,16 @@ impl EditorRenderer {
             return String::new();
         }
         
-        // Find issues for this line (1-based indexing)
+        // The most severe issue starting on this line (1-based indexing)
         let line_number = line + 1;
-        let issues_for_line: Vec<&linter::LintIssue> = lint_issues
+        let most_severe = lint_issues
             .iter()
             .filter(|issue| issue.line == line_number)
-            .collect();
-            
-        if issues_for_line.is_empty() {
+            .map(|issue| &issue.severity)
+            .min();
+        let Some(most_severe) = most_severe else {
             return String::new();
-        }
-        
-        // Find the most severe issue for this line
-        let mut most_severe = &linter::LintSeverity::Hint;
-        for issue in &issues_for_line {
-            most_severe = match (&most_severe, &issue.severity) {
-                (linter::LintSeverity::Error, _) => most_severe, // Error is already most severe
-                (_, linter::LintSeverity::Error) => &issue.severity,
-                (linter::LintSeverity::Warning, linter::LintSeverity::Warning) => most_severe,
-                (_, linter::LintSeverity::Warning) => &issue.severity,
-                (linter::LintSeverity::Info, linter::LintSeverity::Info) => most_severe,
-                (linter::LintSeverity::Hint, linter::LintSeverity::Info) => &issue.severity,
-                _ => most_severe,
-            };
-        }
+        };
         
         match most_severe {
             linter::LintSeverity::Error => "🔴".to_string(),
@@ -478,3 +993,120 @@ impl Default for EditorRenderer {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row_text(frame: &Frame, y: usize) -> String {
+        (0..frame.width()).map(|x| frame.cell(x, y).symbol.as_str()).collect()
+    }
+
+    #[test]
+    fn test_frame_print_styles_and_clip() {
+        let mut frame = Frame::new(10, 2);
+        frame.start_area(Rect::new(2, 0, 5, 1));
+        frame.print("\x1b[48;5;235;1ma\x1b[0mb\tcdefgh");
+        assert_eq!(row_text(&frame, 0), "  ab cd   ");
+        assert_eq!(frame.cell(2, 0).style, CellStyle { bg: Some(Color::AnsiValue(235)), bold: true, ..CellStyle::default() });
+        assert_eq!(frame.cell(3, 0).style, CellStyle::default());
+
+        // A wide character takes two cells, or leaves a blank where it does not fit
+        frame.start_area(Rect::new(0, 1, 4, 1));
+        let text = "a日本";
+        write!(frame, "\x1b[38;2;1;2;3m{}", text);
+        assert_eq!(row_text(&frame, 1), "a日       ");
+        assert_eq!(frame.cell(2, 1).symbol, "");
+        assert_eq!(frame.cell(1, 1).style.fg, Some(Color::Rgb { r: 1, g: 2, b: 3 }));
+    }
+
+    #[test]
+    fn test_selection_and_matches_keep_clusters() {
+        let renderer = EditorRenderer::new();
+        let line = "ae\u{301}\u{1F44D}\u{1F3FD}b";
+        let selection = mouse::TextSelection::new(0, 1, 0, 3);
+        let text = RowText { text: line, col_offset: 0, content_width: 8, spans: &[], marks: &[] };
+        let mut frame = Frame::new(8, 2);
+        renderer.render_line_with_selection(&mut frame, text, 0, &[], &[&selection]).unwrap();
+        assert_eq!(frame.cell(1, 0).symbol, "e\u{301}");
+        assert_eq!(frame.cell(1, 0).style.bg, Some(Color::AnsiValue(68)));
+        assert_eq!(frame.cell(2, 0).symbol, "\u{1F44D}\u{1F3FD}");
+        assert_eq!(frame.cell(4, 0).symbol, "b");
+
+        frame.start_area(Rect::new(0, 1, 8, 1));
+        renderer.render_line_with_matches(&mut frame, text, None, &[(1, 3, true)], false).unwrap();
+        assert_eq!(frame.cell(1, 1).symbol, "e\u{301}");
+        assert_eq!(frame.cell(1, 1).style.bg, Some(Color::AnsiValue(208)));
+        assert_eq!(frame.cell(2, 1).symbol, "\u{1F44D}\u{1F3FD}");
+    }
+
+    #[test]
+    fn test_matches_keep_syntax_colors() {
+        let renderer = EditorRenderer::new();
+        let color = syntect::highlighting::Color { r: 1, g: 2, b: 3, a: 255 };
+        let style = syntect::highlighting::Style { foreground: color, ..Default::default() };
+        let spans = [syntax::StyledSpan { start: 0, end: 6, style }];
+        let text = RowText { text: "let xy", col_offset: 0, content_width: 8, spans: &spans, marks: &[] };
+        let mut frame = Frame::new(8, 1);
+        renderer.render_line_with_matches(&mut frame, text, Some(0), &[(4, 6, false)], true).unwrap();
+
+        let fg = Some(Color::Rgb { r: 1, g: 2, b: 3 });
+        // The cursor bar takes the first cell
+        assert_eq!(frame.cell(1, 0).style.fg, fg);
+        assert_eq!(frame.cell(1, 0).style.bg, Some(Color::AnsiValue(235)));
+        assert_eq!(frame.cell(5, 0).symbol, "x");
+        assert_eq!(frame.cell(5, 0).style.fg, fg);
+        assert_eq!(frame.cell(5, 0).style.bg, Some(Color::AnsiValue(58)));
+        // The current line's background runs to the edge
+        assert_eq!(frame.cell(7, 0).style.bg, Some(Color::AnsiValue(235)));
+    }
+
+    #[test]
+    fn test_lint_underlines() {
+        let marks = [(1..3, linter::LintSeverity::Warning), (2..4, linter::LintSeverity::Error)];
+        let mut frame = Frame::new(6, 1);
+        frame.print(&lint_ansi("abcdef", 0, &[], &marks));
+        assert_eq!(row_text(&frame, 0), "abcdef");
+        let underline = |x: usize| frame.cell(x, 0).style.underline.then_some(frame.cell(x, 0).style.underline_color).flatten();
+        let colors: Vec<Option<Color>> = (0..6).map(underline).collect();
+        // The most severe issue wins where they overlap
+        assert_eq!(colors, [None, Some(Color::AnsiValue(214)), Some(Color::AnsiValue(196)), Some(Color::AnsiValue(196)), None, None]);
+        assert_eq!(lint_ansi("abc", 0, &[], &[]), "abc");
+
+        // A mark ending inside a cluster does not split it
+        let mut frame = Frame::new(3, 1);
+        frame.print(&lint_ansi("ae\u{301}b", 0, &[], &[(0..2, linter::LintSeverity::Hint)]));
+        assert_eq!(frame.cell(1, 0).symbol, "e\u{301}");
+    }
+
+    #[test]
+    fn test_overwriting_half_of_wide_character() {
+        let mut frame = Frame::new(4, 1);
+        frame.print("日本");
+        frame.start_area(Rect::new(1, 0, 2, 1));
+        frame.print("x");
+        assert_eq!(row_text(&frame, 0), " x  ");
+        assert_eq!(frame.cell(3, 0).symbol, " ");
+    }
+
+    #[test]
+    fn test_draw_diff_sends_only_changes() {
+        let mut previous = Frame::new(6, 2);
+        previous.print("hello");
+        let mut frame = previous.clone();
+        frame.start_area(Rect::new(0, 1, 6, 1));
+        frame.print("\x1b[7mX");
+
+        let mut out = Vec::new();
+        frame.draw_diff(Some(&previous), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains('X') && !out.contains("hello"));
+        assert!(!out.contains("\x1b[2J"));
+
+        // Without a matching previous frame the screen is redrawn from scratch
+        let mut out = Vec::new();
+        frame.draw_diff(Some(&Frame::new(3, 3)), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("\x1b[2J") && out.contains('X') && out.contains('o'));
+    }
+}
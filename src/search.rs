@@ -0,0 +1,340 @@
+use regex::{Regex, RegexBuilder};
+use crate::buffer::TextBuffer;
+
+/// Options that control how a search query is matched (all off by default)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchOptions {
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    pub use_regex: bool,
+}
+
+/// A match in the buffer, as a half-open char range
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchMatch {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Incremental search over a text buffer with regex and replace support
+pub struct SearchState {
+    query: String,
+    options: SearchOptions,
+    regex: Option<Regex>,
+    error: Option<String>,
+    matches: Vec<SearchMatch>,
+    current: Option<usize>,
+}
+
+impl SearchState {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            options: SearchOptions::default(),
+            regex: None,
+            error: None,
+            matches: Vec::new(),
+            current: None,
+        }
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn options(&self) -> &SearchOptions {
+        &self.options
+    }
+
+    /// Error message if the current query is not a valid regex
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn matches(&self) -> &[SearchMatch] {
+        &self.matches
+    }
+
+    pub fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// Index of the current match, if any
+    pub fn current_index(&self) -> Option<usize> {
+        self.current
+    }
+
+    pub fn current_match(&self) -> Option<SearchMatch> {
+        self.current.and_then(|idx| self.matches.get(idx).copied())
+    }
+
+    /// Replace the query and recompute matches
+    pub fn set_query(&mut self, query: &str, buffer: &TextBuffer) {
+        self.query = query.to_string();
+        self.update(buffer);
+    }
+
+    pub fn toggle_case_sensitive(&mut self, buffer: &TextBuffer) {
+        self.options.case_sensitive = !self.options.case_sensitive;
+        self.update(buffer);
+    }
+
+    pub fn toggle_whole_word(&mut self, buffer: &TextBuffer) {
+        self.options.whole_word = !self.options.whole_word;
+        self.update(buffer);
+    }
+
+    pub fn toggle_regex(&mut self, buffer: &TextBuffer) {
+        self.options.use_regex = !self.options.use_regex;
+        self.update(buffer);
+    }
+
+    /// Drop the query and all matches
+    pub fn clear(&mut self) {
+        self.query.clear();
+        self.regex = None;
+        self.error = None;
+        self.matches.clear();
+        self.current = None;
+    }
+
+    /// Recompute matches after the query, options or buffer changed
+    pub fn update(&mut self, buffer: &TextBuffer) {
+        let previous = self.current_match();
+        self.matches.clear();
+        self.current = None;
+        self.error = None;
+        self.regex = None;
+
+        if self.query.is_empty() {
+            return;
+        }
+
+        match self.build_regex() {
+            Ok(regex) => {
+                let text = buffer.to_string();
+                // Zero-width matches (e.g. `^` or `a*`) have nothing to highlight
+                self.matches = regex
+                    .find_iter(&text)
+                    .filter(|m| !m.is_empty())
+                    .map(|m| SearchMatch {
+                        start: buffer.byte_to_char(m.start()),
+                        end: buffer.byte_to_char(m.end()),
+                    })
+                    .collect();
+                self.regex = Some(regex);
+            }
+            Err(e) => {
+                self.error = Some(e.to_string());
+                return;
+            }
+        }
+
+        // Stay on the same match where possible
+        if let Some(previous) = previous {
+            self.select_from(previous.start, true);
+        }
+    }
+
+    fn build_regex(&self) -> Result<Regex, regex::Error> {
+        let pattern = if self.options.use_regex {
+            self.query.clone()
+        } else {
+            regex::escape(&self.query)
+        };
+        let pattern = if self.options.whole_word {
+            format!(r"\b(?:{})\b", pattern)
+        } else {
+            pattern
+        };
+
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.options.case_sensitive)
+            .multi_line(true)
+            .build()
+    }
+
+    /// Select the first match at or after `char_idx` (forward) or the last one before it (backward), wrapping around
+    pub fn select_from(&mut self, char_idx: usize, forward: bool) -> Option<SearchMatch> {
+        if self.matches.is_empty() {
+            self.current = None;
+            return None;
+        }
+
+        let after = self.matches.partition_point(|m| m.start < char_idx);
+        let idx = if forward {
+            if after < self.matches.len() { after } else { 0 }
+        } else if after > 0 {
+            after - 1
+        } else {
+            self.matches.len() - 1
+        };
+
+        self.current = Some(idx);
+        self.current_match()
+    }
+
+    /// Move to the next match, wrapping around at the end of the buffer
+    pub fn next_match(&mut self) -> Option<SearchMatch> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        self.current = Some(match self.current {
+            Some(idx) => (idx + 1) % self.matches.len(),
+            None => 0,
+        });
+        self.current_match()
+    }
+
+    /// Move to the previous match, wrapping around at the start of the buffer
+    pub fn prev_match(&mut self) -> Option<SearchMatch> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        let len = self.matches.len();
+        self.current = Some(match self.current {
+            Some(idx) => (idx + len - 1) % len,
+            None => len - 1,
+        });
+        self.current_match()
+    }
+
+    /// Column ranges of matches on a line, flagged when they are the current match
+    pub fn line_matches(&self, buffer: &TextBuffer, row: usize) -> Vec<(usize, usize, bool)> {
+        if self.matches.is_empty() || row >= buffer.len_lines() {
+            return Vec::new();
+        }
+
+        let line_start = buffer.line_to_char(row);
+        let line_end = line_start + buffer.line_len(row);
+        let first = self.matches.partition_point(|m| m.end <= line_start);
+
+        self.matches[first..]
+            .iter()
+            .enumerate()
+            .take_while(|(_, m)| m.start < line_end)
+            .map(|(offset, m)| {
+                let start = m.start.max(line_start) - line_start;
+                let end = m.end.min(line_end) - line_start;
+                (start, end, self.current == Some(first + offset))
+            })
+            .collect()
+    }
+
+    /// Expand the replacement template for a match (`$1`, `${name}` in regex mode)
+    pub fn replacement_for(&self, buffer: &TextBuffer, m: SearchMatch, template: &str) -> String {
+        let Some(regex) = &self.regex else {
+            return template.to_string();
+        };
+        if !self.options.use_regex {
+            return template.to_string();
+        }
+
+        // Match against the full text so anchors and word boundaries behave as in the search
+        let text = buffer.to_string();
+        let byte_start = buffer.char_to_byte(m.start);
+        match regex.captures_at(&text, byte_start) {
+            Some(captures) => {
+                let mut expanded = String::new();
+                captures.expand(template, &mut expanded);
+                expanded
+            }
+            None => template.to_string(),
+        }
+    }
+
+    /// Every match paired with its replacement text, in buffer order
+    pub fn replace_all_plan(&self, buffer: &TextBuffer, template: &str) -> Vec<(SearchMatch, String)> {
+        let Some(regex) = &self.regex else {
+            return Vec::new();
+        };
+        if !self.options.use_regex {
+            return self.matches.iter().map(|m| (*m, template.to_string())).collect();
+        }
+
+        let text = buffer.to_string();
+        regex
+            .captures_iter(&text)
+            .filter_map(|captures| {
+                let whole = captures.get(0)?;
+                if whole.is_empty() {
+                    return None;
+                }
+                let mut expanded = String::new();
+                captures.expand(template, &mut expanded);
+                let m = SearchMatch {
+                    start: buffer.byte_to_char(whole.start()),
+                    end: buffer.byte_to_char(whole.end()),
+                };
+                Some((m, expanded))
+            })
+            .collect()
+    }
+}
+
+impl Default for SearchState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_and_navigation() {
+        let buffer = TextBuffer::from("foo bar\nFoo food\nbar");
+        let mut search = SearchState::new();
+        search.set_query("foo", &buffer);
+        assert_eq!(search.match_count(), 3);
+
+        // Case-insensitive by default, whole word narrows it down
+        search.toggle_whole_word(&buffer);
+        assert_eq!(search.match_count(), 2);
+        search.toggle_case_sensitive(&buffer);
+        assert_eq!(search.matches(), &[SearchMatch { start: 0, end: 3 }]);
+
+        search.toggle_case_sensitive(&buffer);
+        assert_eq!(search.select_from(5, true), Some(SearchMatch { start: 8, end: 11 }));
+        assert_eq!(search.next_match(), Some(SearchMatch { start: 0, end: 3 }));
+        assert_eq!(search.prev_match(), Some(SearchMatch { start: 8, end: 11 }));
+        assert_eq!(search.select_from(5, false), Some(SearchMatch { start: 0, end: 3 }));
+    }
+
+    #[test]
+    fn test_line_matches_and_invalid_regex() {
+        let buffer = TextBuffer::from("héllo wörld\nwörld");
+        let mut search = SearchState::new();
+        search.toggle_regex(&buffer);
+        search.set_query("w.rld", &buffer);
+        search.select_from(0, true);
+        assert_eq!(search.line_matches(&buffer, 0), vec![(6, 11, true)]);
+        assert_eq!(search.line_matches(&buffer, 1), vec![(0, 5, false)]);
+
+        search.set_query("(", &buffer);
+        assert!(search.error().is_some());
+        assert_eq!(search.match_count(), 0);
+    }
+
+    #[test]
+    fn test_capture_group_replacement() {
+        let buffer = TextBuffer::from("let a = 1;\nlet b = 2;");
+        let mut search = SearchState::new();
+        search.toggle_regex(&buffer);
+        search.set_query(r"let (\w+) = (\d+)", &buffer);
+
+        let first = search.select_from(0, true).unwrap();
+        assert_eq!(search.replacement_for(&buffer, first, "const $1: i32 = $2"), "const a: i32 = 1");
+
+        let plan = search.replace_all_plan(&buffer, "$2 -> $1");
+        let replacements: Vec<&str> = plan.iter().map(|(_, text)| text.as_str()).collect();
+        assert_eq!(replacements, vec!["1 -> a", "2 -> b"]);
+
+        // Literal mode leaves `$` alone
+        search.toggle_regex(&buffer);
+        search.set_query("a", &buffer);
+        let first = search.select_from(0, true).unwrap();
+        assert_eq!(search.replacement_for(&buffer, first, "$1"), "$1");
+    }
+}
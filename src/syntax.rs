@@ -1,12 +1,159 @@
-use syntect::easy::HighlightLines;
-use syntect::highlighting::{ThemeSet, Style};
-use syntect::parsing::SyntaxSet;
-use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
+use syntect::highlighting::{Color, HighlightIterator, HighlightState, Highlighter, Theme, ThemeSet, Style};
+use syntect::parsing::{ParseState, ScopeStack, SyntaxReference};
+use syntect::util::LinesWithEndings;
+use std::fmt::Write;
 use std::path::Path;
+use crate::buffer::{LineChanges, TextBuffer};
+use crate::config;
+use crate::language::LanguageDetector;
+
+/// Style of a run of text within a line; `start` and `end` are char columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StyledSpan {
+    pub start: usize,
+    pub end: usize,
+    pub style: Style,
+}
+
+/// Terminal escapes coloring `text` by `spans`; the text starts at column `start_col` of its line
+pub fn to_ansi(text: &str, start_col: usize, spans: &[StyledSpan]) -> String {
+    let mut escaped = String::new();
+    let mut current = None;
+    for (col, ch) in (start_col..).zip(text.chars()) {
+        let color = spans
+            .iter()
+            .find(|span| span.start <= col && col < span.end)
+            .map(|span| span.style.foreground);
+        if let Some(color) = color.filter(|color| Some(*color) != current) {
+            let _ = write!(escaped, "\x1b[38;2;{};{};{}m", color.r, color.g, color.b);
+            current = Some(color);
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+/// Escape sequences coloring the editor's header and status line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UiPalette {
+    /// Header of the focused pane
+    pub header: String,
+    /// Header of the other panes
+    pub header_inactive: String,
+    pub status: String,
+}
+
+impl Default for UiPalette {
+    fn default() -> Self {
+        Self {
+            header: "\x1b[44;37m".to_string(),
+            header_inactive: "\x1b[100;37m".to_string(),
+            status: "\x1b[48;5;235;37m".to_string(),
+        }
+    }
+}
+
+impl UiPalette {
+    /// Colors derived from a theme's global settings, falling back to the defaults
+    pub fn from_theme(theme: &Theme) -> Self {
+        let settings = &theme.settings;
+        let colors = |background: Option<Color>, fallback: String| match (background, settings.foreground) {
+            (Some(bg), Some(fg)) => format!("\x1b[48;2;{};{};{};38;2;{};{};{}m", bg.r, bg.g, bg.b, fg.r, fg.g, fg.b),
+            _ => fallback,
+        };
+        let default = Self::default();
+        Self {
+            header: colors(settings.selection, default.header),
+            header_inactive: colors(settings.gutter.or(settings.line_highlight), default.header_inactive),
+            status: colors(settings.line_highlight.or(settings.background), default.status),
+        }
+    }
+}
+
+/// Parser and highlighter state between two lines
+type LineState = (ParseState, HighlightState);
+
+#[derive(Clone)]
+struct CachedLine {
+    spans: Vec<StyledSpan>,
+    // State at the end of the line, where the next one starts
+    end: LineState,
+}
+
+/// Highlighting of one buffer, kept between frames.
+///
+/// Lines are highlighted in order and the state at each line boundary is
+/// cached. After an edit, highlighting resumes at the first changed line and
+/// stops as soon as the state after a line matches the cached one, since
+/// everything below is then unaffected.
+#[derive(Clone, Default)]
+pub struct HighlightCache {
+    // Syntax and theme the cached lines were highlighted with
+    key: Option<(String, String)>,
+    lines: Vec<CachedLine>,
+    // Lines up to here are current
+    valid: usize,
+    // Cached lines from here on still line up with the buffer; between
+    // `valid` and here they belong to edited text
+    aligned_from: usize,
+    line_count: usize,
+}
+
+impl HighlightCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spans of a highlighted line; empty if it has not been highlighted yet
+    pub fn line(&self, row: usize) -> &[StyledSpan] {
+        if row < self.valid {
+            &self.lines[row].spans
+        } else {
+            &[]
+        }
+    }
+
+    /// Take note of the buffer's edits since the last sync
+    pub fn sync(&mut self, buffer: &mut TextBuffer) {
+        if let Some(changes) = buffer.take_changed_lines() {
+            self.edited(changes, buffer.len_lines());
+        }
+    }
+
+    fn edited(&mut self, changes: LineChanges, line_count: usize) {
+        let added = line_count as isize - self.line_count as isize;
+        self.line_count = line_count;
+        let LineChanges { first, last } = changes;
+
+        // Re-align the cached lines after the edit with their new line numbers
+        let old_last = (last as isize - added).max(first as isize) as usize;
+        let cached = self.lines.len();
+        if first + 1 < cached {
+            let dummy = self.lines[first].clone();
+            self.lines.splice(
+                first + 1..(old_last + 1).min(cached),
+                std::iter::repeat_n(dummy, last - first),
+            );
+        }
+
+        // Only lines that lined up before the edit can be trusted after it
+        let aligned_from = if self.valid < self.aligned_from {
+            (self.aligned_from as isize + added).max(0) as usize
+        } else {
+            0
+        };
+        self.aligned_from = aligned_from.max(last + 1);
+        self.valid = self.valid.min(first);
+    }
+
+    fn reset(&mut self, key: (String, String)) {
+        *self = Self { key: Some(key), line_count: self.line_count, ..Self::default() };
+    }
+}
 
 /// Syntax highlighting utilities for the text editor
 pub struct SyntaxHighlighter {
-    syntax_set: SyntaxSet,
+    languages: LanguageDetector,
     theme_set: ThemeSet,
     current_theme: String,
 }
@@ -20,21 +167,63 @@ impl Default for SyntaxHighlighter {
 impl SyntaxHighlighter {
     /// Create a new syntax highlighter with default settings
     pub fn new() -> Self {
-        let syntax_set = SyntaxSet::load_defaults_newlines();
         let theme_set = ThemeSet::load_defaults();
         
         Self {
-            syntax_set,
+            languages: LanguageDetector::new(),
             theme_set,
             current_theme: "base16-ocean.dark".to_string(),
         }
     }
 
-    /// Set the theme for syntax highlighting
-    pub fn set_theme(&mut self, theme_name: &str) {
-        if self.theme_set.themes.contains_key(theme_name) {
+    /// Set the theme for syntax highlighting; returns `false` for an unknown theme
+    pub fn set_theme(&mut self, theme_name: &str) -> bool {
+        let known = self.theme_set.themes.contains_key(theme_name);
+        if known {
             self.current_theme = theme_name.to_string();
         }
+        known
+    }
+
+    /// Name of the theme in use
+    pub fn current_theme(&self) -> &str {
+        &self.current_theme
+    }
+
+    /// Header and status line colors matching the current theme
+    pub fn ui_palette(&self) -> UiPalette {
+        UiPalette::from_theme(&self.theme_set.themes[&self.current_theme])
+    }
+
+    /// Add the `.tmTheme` files in `dir` to the available themes, named after their files.
+    ///
+    /// Returns a message for every file that could not be loaded.
+    pub fn load_themes_from(&mut self, dir: &Path) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut errors = Vec::new();
+        let paths = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("tmtheme")));
+        for path in paths {
+            let name = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+            match ThemeSet::get_theme(&path) {
+                Ok(theme) => {
+                    self.theme_set.themes.insert(name, theme);
+                }
+                Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+        errors
+    }
+
+    /// Load the user's themes from the `themes` folder of the configuration directory
+    pub fn load_user_themes(&mut self) -> Vec<String> {
+        match config::config_dir() {
+            Some(dir) => self.load_themes_from(&dir.join("themes")),
+            None => Vec::new(),
+        }
     }
 
     /// Get available themes
@@ -42,93 +231,134 @@ impl SyntaxHighlighter {
         self.theme_set.themes.keys().collect()
     }
 
-    /// Detect syntax from file extension
-    pub fn detect_syntax_from_filename(&self, filename: &str) -> Option<&str> {
-        let path = Path::new(filename);
-        if let Some(extension) = path.extension().and_then(|ext| ext.to_str()) {
-            match extension {
-                "rs" => Some("Rust"),
-                "py" => Some("Python"),
-                "js" | "jsx" => Some("JavaScript"),
-                "ts" | "tsx" => Some("TypeScript"),
-                "html" => Some("HTML"),
-                "css" => Some("CSS"),
-                "json" => Some("JSON"),
-                "xml" => Some("XML"),
-                "yaml" | "yml" => Some("YAML"),
-                "toml" => Some("TOML"),
-                "md" => Some("Markdown"),
-                "txt" => Some("Plain Text"),
-                _ => None,
+    /// Language detection, which also holds the syntaxes used for highlighting
+    pub fn languages(&self) -> &LanguageDetector {
+        &self.languages
+    }
+
+    /// Language detection, for adding user mappings and syntaxes
+    pub fn languages_mut(&mut self) -> &mut LanguageDetector {
+        &mut self.languages
+    }
+
+    /// Highlight a single line of text on its own, without any surrounding context
+    pub fn highlight_line(&self, line: &str, syntax_name: &str) -> Vec<StyledSpan> {
+        match self.languages.syntax_set().find_syntax_by_name(syntax_name) {
+            Some(syntax) => {
+                let mut state = self.initial_state(syntax);
+                self.highlight_next(&mut state, line)
             }
-        } else {
-            None
+            None => Vec::new(),
         }
     }
 
-    /// Highlight a single line of text
-    pub fn highlight_line(&self, line: &str, syntax_name: &str) -> String {
-        let theme = &self.theme_set.themes[&self.current_theme];
-        
-        if let Some(syntax) = self.syntax_set.find_syntax_by_name(syntax_name) {
-            let mut highlight_lines = HighlightLines::new(syntax, theme);
-            let ranges: Vec<(Style, &str)> = highlight_lines.highlight_line(line, &self.syntax_set).unwrap_or_default();
-            as_24_bit_terminal_escaped(&ranges[..], false)
-        } else {
-            line.to_string()
+    /// Highlight multiple lines of text, carrying state from one line to the next
+    pub fn highlight_text(&self, text: &str, syntax_name: &str) -> Vec<Vec<StyledSpan>> {
+        match self.languages.syntax_set().find_syntax_by_name(syntax_name) {
+            Some(syntax) => {
+                let mut state = self.initial_state(syntax);
+                LinesWithEndings::from(text)
+                    .map(|line| self.highlight_next(&mut state, line.trim_end_matches(['\r', '\n'])))
+                    .collect()
+            }
+            None => text.lines().map(|_| Vec::new()).collect(),
         }
     }
 
-    /// Highlight multiple lines of text
-    pub fn highlight_text(&self, text: &str, syntax_name: &str) -> Vec<String> {
-        let theme = &self.theme_set.themes[&self.current_theme];
-        
-        if let Some(syntax) = self.syntax_set.find_syntax_by_name(syntax_name) {
-            let mut highlight_lines = HighlightLines::new(syntax, theme);
-            let mut highlighted_lines = Vec::new();
-            
-            for line in LinesWithEndings::from(text) {
-                let ranges: Vec<(Style, &str)> = highlight_lines.highlight_line(line, &self.syntax_set).unwrap_or_default();
-                highlighted_lines.push(as_24_bit_terminal_escaped(&ranges[..], false));
+    /// Bring `cache` up to date for the first `rows` lines of `buffer`.
+    ///
+    /// Call [`HighlightCache::sync`] first so edits are taken into account.
+    pub fn highlight_buffer(&self, cache: &mut HighlightCache, buffer: &TextBuffer, syntax_name: &str, rows: usize) {
+        let key = (syntax_name.to_string(), self.current_theme.clone());
+        if cache.key.as_ref() != Some(&key) {
+            cache.reset(key);
+        }
+        let Some(syntax) = self.languages.syntax_set().find_syntax_by_name(syntax_name) else {
+            return;
+        };
+
+        while cache.valid < rows.min(buffer.len_lines()) {
+            let row = cache.valid;
+            let mut state = match row {
+                0 => self.initial_state(syntax),
+                _ => cache.lines[row - 1].end.clone(),
+            };
+            let spans = self.highlight_next(&mut state, &buffer.line(row));
+            // Once the state after an unedited line is unchanged, so is everything below
+            let converged = row >= cache.aligned_from && cache.lines.get(row).is_some_and(|line| line.end == state);
+            let line = CachedLine { spans, end: state };
+            if row < cache.lines.len() {
+                cache.lines[row] = line;
+            } else {
+                cache.lines.push(line);
+            }
+            cache.valid += 1;
+            if converged {
+                cache.lines.truncate(buffer.len_lines());
+                cache.valid = cache.lines.len();
+                cache.aligned_from = cache.valid;
             }
-            
-            highlighted_lines
-        } else {
-            text.lines().map(|s| s.to_string()).collect()
         }
     }
 
-    /// Get syntax name for highlighting
-    pub fn get_syntax_name(&self, filename: Option<&str>) -> String {
-        if let Some(filename) = filename {
-            if let Some(detected) = self.detect_syntax_from_filename(filename) {
-                detected.to_string()
-            } else {
-                "Plain Text".to_string()
+    fn initial_state(&self, syntax: &SyntaxReference) -> LineState {
+        let highlighter = Highlighter::new(&self.theme_set.themes[&self.current_theme]);
+        (ParseState::new(syntax), HighlightState::new(&highlighter, ScopeStack::new()))
+    }
+
+    /// Highlight the line following `state`, advancing the state past it
+    fn highlight_next(&self, state: &mut LineState, line: &str) -> Vec<StyledSpan> {
+        let highlighter = Highlighter::new(&self.theme_set.themes[&self.current_theme]);
+        let (parse_state, highlight_state) = state;
+        // The bundled syntaxes expect every line to end with a newline
+        let line = format!("{}\n", line);
+        let ops = parse_state.parse_line(&line, self.languages.syntax_set()).unwrap_or_default();
+
+        let mut spans = Vec::new();
+        let mut col = 0;
+        for (style, text) in HighlightIterator::new(highlight_state, &ops, &line, &highlighter) {
+            let text = text.strip_suffix('\n').unwrap_or(text);
+            let len = text.chars().count();
+            if len > 0 {
+                spans.push(StyledSpan { start: col, end: col + len, style });
             }
-        } else {
-            "Plain Text".to_string()
+            col += len;
         }
+        spans
+    }
+
+    /// Get syntax name for highlighting, judging by the file name and the buffer's content
+    pub fn get_syntax_name(&self, filename: Option<&str>, buffer: &TextBuffer) -> String {
+        self.languages.detect(filename, buffer)
+    }
+
+    /// Syntax name for a buffer: the language picked for it, or else the detected one
+    pub fn syntax_name_for(&self, language: Option<&str>, filename: Option<&str>, buffer: &TextBuffer) -> String {
+        language.map_or_else(|| self.get_syntax_name(filename, buffer), str::to_string)
     }
 
     /// Check if syntax highlighting is available for a language
     pub fn is_syntax_supported(&self, syntax_name: &str) -> bool {
-        self.syntax_set.find_syntax_by_name(syntax_name).is_some()
+        self.languages.syntax_set().find_syntax_by_name(syntax_name).is_some()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::language::PLAIN_TEXT;
 
     #[test]
     fn test_syntax_detection() {
         let highlighter = SyntaxHighlighter::new();
+        let detect = |filename: &str| highlighter.get_syntax_name(Some(filename), &TextBuffer::new());
         
-        assert_eq!(highlighter.detect_syntax_from_filename("test.rs"), Some("Rust"));
-        assert_eq!(highlighter.detect_syntax_from_filename("test.py"), Some("Python"));
-        assert_eq!(highlighter.detect_syntax_from_filename("test.js"), Some("JavaScript"));
-        assert_eq!(highlighter.detect_syntax_from_filename("test.unknown"), None);
+        assert_eq!(detect("test.rs"), "Rust");
+        assert_eq!(detect("test.py"), "Python");
+        assert_eq!(detect("test.js"), "JavaScript");
+        assert_eq!(detect("test.unknown"), PLAIN_TEXT);
+        assert_eq!(highlighter.get_syntax_name(None, &TextBuffer::from("#!/bin/sh
+")), "Bourne Again Shell (bash)");
     }
 
     #[test]
@@ -155,4 +385,82 @@ mod tests {
             assert_eq!(highlighter.current_theme, theme_name);
         }
     }
+
+    #[test]
+    fn test_multiline_comment() {
+        let highlighter = SyntaxHighlighter::new();
+        let lines = highlighter.highlight_text("/* start\nstill comment */\nfn main() {}", "Rust");
+        assert_eq!(lines.len(), 3);
+        // The second line keeps the comment style of the first
+        assert_eq!(lines[1][0].style, lines[0][0].style);
+        assert_ne!(highlighter.highlight_line("still comment */", "Rust")[0].style, lines[0][0].style);
+        assert_eq!(lines[2].last().map(|span| span.end), Some(12));
+        assert!(to_ansi("fn", 0, &lines[2]).starts_with("\x1b[38;2;"));
+    }
+
+    #[test]
+    fn test_incremental_highlighting() {
+        let highlighter = SyntaxHighlighter::new();
+        let text: Vec<String> = (0..40).map(|idx| format!("let x{} = \"{}\"; // note", idx, idx)).collect();
+        let mut buffer = TextBuffer::from_lines(&text);
+        let mut cache = HighlightCache::new();
+        let fresh = |buffer: &TextBuffer| highlighter.highlight_text(&buffer.to_string(), "Rust");
+        let cached = |cache: &HighlightCache| (0..cache.valid).map(|row| cache.line(row).to_vec()).collect::<Vec<_>>();
+
+        cache.sync(&mut buffer);
+        highlighter.highlight_buffer(&mut cache, &buffer, "Rust", 40);
+        assert_eq!(cached(&cache), fresh(&buffer));
+
+        // A local edit converges right away, reusing the lines below it
+        buffer.insert_str_at(10, 0, "x");
+        buffer.split_line(11, 3);
+        cache.sync(&mut buffer);
+        highlighter.highlight_buffer(&mut cache, &buffer, "Rust", 14);
+        assert_eq!(cache.valid, 41);
+        assert_eq!(cached(&cache), fresh(&buffer));
+
+        // Opening a block comment changes everything below it
+        buffer.insert_str_at(5, 0, "/*");
+        cache.sync(&mut buffer);
+        highlighter.highlight_buffer(&mut cache, &buffer, "Rust", 20);
+        assert_eq!(cache.valid, 20);
+        highlighter.highlight_buffer(&mut cache, &buffer, "Rust", 41);
+        assert_eq!(cached(&cache), fresh(&buffer));
+
+        buffer.remove_range(5, 0, 7, 0);
+        cache.sync(&mut buffer);
+        highlighter.highlight_buffer(&mut cache, &buffer, "Rust", 41);
+        assert_eq!(cached(&cache), fresh(&buffer));
+    }
+
+    #[test]
+    fn test_user_theme_and_palette() {
+        let dir = std::env::temp_dir().join(format!("hello_tui_themes_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("Custom.tmTheme"), r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0"><dict>
+<key>name</key><string>Custom</string>
+<key>settings</key><array><dict><key>settings</key><dict>
+<key>background</key><string>#101010</string>
+<key>foreground</key><string>#E0E0E0</string>
+<key>selection</key><string>#203040</string>
+</dict></dict></array>
+</dict></plist>"#).unwrap();
+        std::fs::write(dir.join("broken.tmTheme"), "not a theme").unwrap();
+
+        let mut highlighter = SyntaxHighlighter::new();
+        let errors = highlighter.load_themes_from(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("broken.tmTheme"));
+
+        assert!(highlighter.set_theme("Custom"));
+        assert!(!highlighter.set_theme("Missing"));
+        assert_eq!(highlighter.current_theme(), "Custom");
+        let palette = highlighter.ui_palette();
+        assert_eq!(palette.header, "\x1b[48;2;32;48;64;38;2;224;224;224m");
+        assert_eq!(palette.status, "\x1b[48;2;16;16;16;38;2;224;224;224m");
+        // Without a gutter or line highlight color the default is kept
+        assert_eq!(palette.header_inactive, UiPalette::default().header_inactive);
+    }
 }
@@ -0,0 +1,153 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// A grapheme cluster within a line: what the user sees as one character.
+///
+/// `col` and `len` are in chars, matching the buffer's `(row, col)`
+/// positions; `width` is the number of terminal cells it occupies.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cluster {
+    pub col: usize,
+    pub len: usize,
+    pub width: usize,
+}
+
+/// Terminal cells taken by a grapheme cluster.
+///
+/// Control characters such as tabs have no defined width; they count as one
+/// cell so the cursor never sits on an invisible column.
+pub fn grapheme_width(grapheme: &str) -> usize {
+    if grapheme.chars().any(char::is_control) {
+        return 1;
+    }
+    grapheme.width()
+}
+
+/// Grapheme clusters of a line, in order
+pub fn clusters(line: &str) -> Vec<Cluster> {
+    let mut col = 0;
+    line.graphemes(true)
+        .map(|grapheme| {
+            let len = grapheme.chars().count();
+            let cluster = Cluster { col, len, width: grapheme_width(grapheme) };
+            col += len;
+            cluster
+        })
+        .collect()
+}
+
+/// Terminal cells taken by a whole string
+pub fn str_width(text: &str) -> usize {
+    text.graphemes(true).map(grapheme_width).sum()
+}
+
+/// Column just after the cluster at `col`, or the line length at the end
+pub fn next_boundary(line: &str, col: usize) -> usize {
+    clusters(line)
+        .iter()
+        .map(|cluster| cluster.col + cluster.len)
+        .find(|&end| end > col)
+        .unwrap_or_else(|| line.chars().count())
+}
+
+/// Start of the cluster before `col`, or 0 at the start of the line
+pub fn prev_boundary(line: &str, col: usize) -> usize {
+    clusters(line)
+        .iter()
+        .map(|cluster| cluster.col)
+        .take_while(|&start| start < col)
+        .last()
+        .unwrap_or(0)
+}
+
+/// Start of the cluster containing `col`, so positions never land inside one
+pub fn snap_to_boundary(line: &str, col: usize) -> usize {
+    clusters(line)
+        .iter()
+        .map(|cluster| cluster.col)
+        .take_while(|&start| start <= col)
+        .last()
+        .unwrap_or(0)
+}
+
+/// Screen cells in front of char column `col`
+pub fn display_col(line: &str, col: usize) -> usize {
+    clusters(line)
+        .iter()
+        .take_while(|cluster| cluster.col < col)
+        .map(|cluster| cluster.width)
+        .sum()
+}
+
+/// Char column of the cluster drawn at screen cell `cells`; past the end this is the line length
+pub fn col_at_display(line: &str, cells: usize) -> usize {
+    let mut used = 0;
+    for cluster in clusters(line) {
+        if used + cluster.width > cells {
+            return cluster.col;
+        }
+        used += cluster.width;
+    }
+    line.chars().count()
+}
+
+/// Number of chars from the start of `text` whose clusters fit in `width` cells
+pub fn fit_width(text: &str, width: usize) -> usize {
+    let mut used = 0;
+    let mut chars = 0;
+    for cluster in clusters(text) {
+        if used + cluster.width > width {
+            break;
+        }
+        used += cluster.width;
+        chars += cluster.len;
+    }
+    chars
+}
+
+/// Longest prefix of `text` that fits in `width` cells, never splitting a cluster
+pub fn truncate_to_width(text: &str, width: usize) -> String {
+    text.chars().take(fit_width(text, width)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clusters_and_widths() {
+        // "e" + combining acute accent is one cluster of one cell
+        let line = "ae\u{301}日🙂";
+        let widths: Vec<(usize, usize, usize)> = clusters(line).iter().map(|c| (c.col, c.len, c.width)).collect();
+        assert_eq!(widths, vec![(0, 1, 1), (1, 2, 1), (3, 1, 2), (4, 1, 2)]);
+        assert_eq!(str_width(line), 6);
+        assert_eq!(str_width("a\tb"), 3);
+    }
+
+    #[test]
+    fn test_boundaries() {
+        let line = "ae\u{301}x👍🏽";
+        assert_eq!(next_boundary(line, 1), 3);
+        assert_eq!(next_boundary(line, 4), 6);
+        assert_eq!(next_boundary(line, 6), 6);
+        assert_eq!(prev_boundary(line, 3), 1);
+        assert_eq!(prev_boundary(line, 6), 4);
+        assert_eq!(prev_boundary(line, 0), 0);
+        assert_eq!(snap_to_boundary(line, 2), 1);
+        assert_eq!(snap_to_boundary(line, 5), 4);
+    }
+
+    #[test]
+    fn test_display_columns() {
+        let line = "a日本b";
+        assert_eq!(display_col(line, 2), 3);
+        assert_eq!(display_col(line, 4), 6);
+        // Either cell of a wide char maps back to it
+        assert_eq!(col_at_display(line, 1), 1);
+        assert_eq!(col_at_display(line, 2), 1);
+        assert_eq!(col_at_display(line, 5), 3);
+        assert_eq!(col_at_display(line, 9), 4);
+        assert_eq!(fit_width(line, 4), 2);
+        assert_eq!(truncate_to_width(line, 2), "a");
+    }
+}
@@ -0,0 +1,373 @@
+use crate::buffer::TextBuffer;
+use crate::mouse::TextSelection;
+
+/// Maximum number of undo steps kept in history
+const MAX_UNDO_DEPTH: usize = 1000;
+
+/// A single reversible change to a text buffer, addressed by char index
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edit {
+    Insert { at: usize, text: String },
+    Delete { at: usize, text: String },
+}
+
+impl Edit {
+    pub fn insert(at: usize, text: impl Into<String>) -> Self {
+        Edit::Insert { at, text: text.into() }
+    }
+
+    pub fn delete(at: usize, text: impl Into<String>) -> Self {
+        Edit::Delete { at, text: text.into() }
+    }
+
+    /// Apply this edit to the buffer
+    pub fn apply(&self, buffer: &mut TextBuffer) {
+        match self {
+            Edit::Insert { at, text } => buffer.insert(*at, text),
+            Edit::Delete { at, text } => buffer.remove(*at, *at + text.chars().count()),
+        }
+    }
+
+    /// Get the edit that undoes this one
+    pub fn inverse(&self) -> Self {
+        match self {
+            Edit::Insert { at, text } => Edit::Delete { at: *at, text: text.clone() },
+            Edit::Delete { at, text } => Edit::Insert { at: *at, text: text.clone() },
+        }
+    }
+
+    /// Try to fold a follow-up edit into this one (typing, backspace or forward delete)
+    fn merge(&mut self, next: &Edit) -> bool {
+        match (self, next) {
+            (Edit::Insert { at, text }, Edit::Insert { at: next_at, text: next_text }) => {
+                if *next_at == *at + text.chars().count() && !next_text.contains('\n') {
+                    text.push_str(next_text);
+                    return true;
+                }
+                false
+            }
+            (Edit::Delete { at, text }, Edit::Delete { at: next_at, text: next_text }) => {
+                if next_text.contains('\n') {
+                    false
+                } else if *next_at + next_text.chars().count() == *at {
+                    // Backspace: the new deletion sits right before the previous one
+                    *at = *next_at;
+                    text.insert_str(0, next_text);
+                    true
+                } else if *next_at == *at {
+                    // Forward delete: the new deletion starts where the previous one was
+                    text.push_str(next_text);
+                    true
+                } else {
+                    false
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Cursor and selection captured around an undo step
+#[derive(Debug, Clone, PartialEq)]
+pub struct CursorState {
+    pub row: usize,
+    pub col: usize,
+    pub selection: Option<TextSelection>,
+}
+
+impl CursorState {
+    pub fn new(row: usize, col: usize, selection: Option<TextSelection>) -> Self {
+        Self { row, col, selection }
+    }
+}
+
+/// One undoable step, made of one or more edits applied in order
+#[derive(Debug, Clone)]
+struct UndoEntry {
+    edits: Vec<Edit>,
+    before: CursorState,
+    after: CursorState,
+    coalescable: bool,
+    // Identifies the text this step leaves behind
+    revision: u64,
+}
+
+/// Undo/redo history for a text buffer
+pub struct UndoStack {
+    undo: Vec<UndoEntry>,
+    redo: Vec<UndoEntry>,
+    group: Option<UndoEntry>,
+    sealed: bool,
+    // Revision of the text before the oldest step still in history
+    base_revision: u64,
+    last_revision: u64,
+}
+
+impl UndoStack {
+    pub fn new() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            group: None,
+            sealed: true,
+            base_revision: 0,
+            last_revision: 0,
+        }
+    }
+
+    /// Identifies the current text: undoing or redoing back to it gives the same revision,
+    /// any new edit a fresh one. Compare against the revision saved to tell if text changed.
+    pub fn revision(&self) -> u64 {
+        self.undo.last().map_or(self.base_revision, |entry| entry.revision)
+    }
+
+    fn next_revision(&mut self) -> u64 {
+        self.last_revision += 1;
+        self.last_revision
+    }
+
+    /// Add a finished step, dropping the oldest one past the depth limit
+    fn push_entry(&mut self, mut entry: UndoEntry) {
+        entry.revision = self.next_revision();
+        self.undo.push(entry);
+        if self.undo.len() > MAX_UNDO_DEPTH {
+            self.base_revision = self.undo.remove(0).revision;
+        }
+    }
+
+    /// Record an edit that was already applied as its own undo step
+    pub fn record(&mut self, edit: Edit, before: CursorState, after: CursorState) {
+        self.push(edit, before, after, false);
+    }
+
+    /// Record an edit that may merge with the previous step (consecutive typing or deleting)
+    pub fn record_coalescing(&mut self, edit: Edit, before: CursorState, after: CursorState) {
+        self.push(edit, before, after, true);
+    }
+
+    fn push(&mut self, edit: Edit, before: CursorState, after: CursorState, coalescable: bool) {
+        self.redo.clear();
+
+        if let Some(group) = self.group.as_mut() {
+            group.edits.push(edit);
+            group.after = after;
+            return;
+        }
+
+        if coalescable
+            && !self.sealed
+            && let Some(last) = self.undo.last_mut()
+            && last.coalescable
+            && last.edits.len() == 1
+            && last.edits[0].merge(&edit)
+        {
+            last.after = after;
+            self.last_revision += 1;
+            last.revision = self.last_revision;
+            return;
+        }
+
+        self.push_entry(UndoEntry { edits: vec![edit], before, after, coalescable, revision: 0 });
+        self.sealed = !coalescable;
+    }
+
+    /// Start collecting edits into a single undo step
+    pub fn begin_group(&mut self, before: CursorState) {
+        if self.group.is_none() {
+            self.group = Some(UndoEntry {
+                edits: Vec::new(),
+                after: before.clone(),
+                before,
+                coalescable: false,
+                revision: 0,
+            });
+        }
+    }
+
+    /// Finish the current group; empty groups are discarded
+    pub fn end_group(&mut self, after: CursorState) {
+        if let Some(mut group) = self.group.take()
+            && !group.edits.is_empty()
+        {
+            group.after = after;
+            self.push_entry(group);
+        }
+        self.sealed = true;
+    }
+
+    /// Stop the next edit from merging into the previous step (e.g. after cursor movement)
+    pub fn seal(&mut self) {
+        self.sealed = true;
+    }
+
+    /// Revert the most recent step and return the cursor state from before it
+    pub fn undo(&mut self, buffer: &mut TextBuffer) -> Option<CursorState> {
+        let entry = self.undo.pop()?;
+        for edit in entry.edits.iter().rev() {
+            edit.inverse().apply(buffer);
+        }
+        let state = entry.before.clone();
+        self.redo.push(entry);
+        self.sealed = true;
+        Some(state)
+    }
+
+    /// Re-apply the most recently undone step and return the cursor state after it
+    pub fn redo(&mut self, buffer: &mut TextBuffer) -> Option<CursorState> {
+        let entry = self.redo.pop()?;
+        for edit in &entry.edits {
+            edit.apply(buffer);
+        }
+        let state = entry.after.clone();
+        self.undo.push(entry);
+        self.sealed = true;
+        Some(state)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Forget all history (e.g. when a different file is loaded)
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.group = None;
+        self.sealed = true;
+        self.base_revision = self.next_revision();
+    }
+}
+
+impl Default for UndoStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(row: usize, col: usize) -> CursorState {
+        CursorState::new(row, col, None)
+    }
+
+    fn type_text(buffer: &mut TextBuffer, stack: &mut UndoStack, at: usize, text: &str) {
+        for (offset, ch) in text.chars().enumerate() {
+            let edit = Edit::insert(at + offset, ch.to_string());
+            edit.apply(buffer);
+            stack.record_coalescing(edit, state(0, at + offset), state(0, at + offset + 1));
+        }
+    }
+
+    #[test]
+    fn test_typing_coalesces_into_one_step() {
+        let mut buffer = TextBuffer::new();
+        let mut stack = UndoStack::new();
+        type_text(&mut buffer, &mut stack, 0, "hello");
+        assert_eq!(buffer.to_string(), "hello");
+
+        let restored = stack.undo(&mut buffer).unwrap();
+        assert_eq!(buffer.to_string(), "");
+        assert_eq!(restored, state(0, 0));
+        assert!(!stack.can_undo());
+
+        let restored = stack.redo(&mut buffer).unwrap();
+        assert_eq!(buffer.to_string(), "hello");
+        assert_eq!(restored, state(0, 5));
+    }
+
+    #[test]
+    fn test_seal_splits_typing() {
+        let mut buffer = TextBuffer::new();
+        let mut stack = UndoStack::new();
+        type_text(&mut buffer, &mut stack, 0, "ab");
+        stack.seal();
+        type_text(&mut buffer, &mut stack, 2, "cd");
+
+        stack.undo(&mut buffer);
+        assert_eq!(buffer.to_string(), "ab");
+        stack.undo(&mut buffer);
+        assert_eq!(buffer.to_string(), "");
+    }
+
+    #[test]
+    fn test_backspace_coalesces_and_restores_text() {
+        let mut buffer = TextBuffer::from("hello");
+        let mut stack = UndoStack::new();
+        for col in (3..5).rev() {
+            let edit = Edit::delete(col, buffer.slice(col, col + 1));
+            edit.apply(&mut buffer);
+            stack.record_coalescing(edit, state(0, col + 1), state(0, col));
+        }
+        assert_eq!(buffer.to_string(), "hel");
+
+        stack.undo(&mut buffer);
+        assert_eq!(buffer.to_string(), "hello");
+    }
+
+    #[test]
+    fn test_group_and_selection_restore() {
+        let mut buffer = TextBuffer::from("one two");
+        let mut stack = UndoStack::new();
+        let selection = TextSelection::new(0, 4, 0, 7);
+        let before = CursorState::new(0, 7, Some(selection.clone()));
+
+        stack.begin_group(before.clone());
+        let delete = Edit::delete(4, "two");
+        delete.apply(&mut buffer);
+        stack.record(delete, before.clone(), state(0, 4));
+        let insert = Edit::insert(4, "2");
+        insert.apply(&mut buffer);
+        stack.record(insert, state(0, 4), state(0, 5));
+        stack.end_group(state(0, 5));
+        assert_eq!(buffer.to_string(), "one 2");
+
+        let restored = stack.undo(&mut buffer).unwrap();
+        assert_eq!(buffer.to_string(), "one two");
+        assert_eq!(restored.selection, Some(selection));
+
+        // A new edit after undo drops the redo history
+        let edit = Edit::insert(0, "x");
+        edit.apply(&mut buffer);
+        stack.record(edit, state(0, 0), state(0, 1));
+        assert!(!stack.can_redo());
+    }
+
+    #[test]
+    fn test_revision_tracks_saved_text() {
+        let mut buffer = TextBuffer::new();
+        let mut stack = UndoStack::new();
+        type_text(&mut buffer, &mut stack, 0, "hel");
+        let saved = stack.revision();
+
+        // More typing merges into the same step but still changes the revision
+        type_text(&mut buffer, &mut stack, 3, "lo");
+        assert_ne!(stack.revision(), saved);
+        stack.undo(&mut buffer);
+        assert_ne!(stack.revision(), saved);
+        assert_eq!(stack.undo(&mut buffer), None);
+
+        let mut stack = UndoStack::new();
+        let empty = stack.revision();
+        type_text(&mut buffer, &mut stack, 0, "ab");
+        stack.seal();
+        type_text(&mut buffer, &mut stack, 2, "c");
+        let saved = stack.revision();
+        stack.undo(&mut buffer);
+        assert_ne!(stack.revision(), saved);
+        stack.redo(&mut buffer);
+        assert_eq!(stack.revision(), saved);
+        stack.undo(&mut buffer);
+        stack.undo(&mut buffer);
+        assert_eq!(stack.revision(), empty);
+
+        // Forgetting the history leaves no way back to an earlier revision
+        stack.clear();
+        assert_ne!(stack.revision(), empty);
+    }
+}
@@ -0,0 +1,1276 @@
+use std::collections::HashMap;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::buffer::TextBuffer;
+use crate::cursor::{cursor_utils, CursorController, CursorPosition};
+use crate::keys::Direction;
+use crate::mouse::TextSelection;
+use crate::text_metrics;
+use crate::undo::Edit;
+
+/// Vim editing mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VimMode {
+    Normal,
+    Insert,
+    Visual,
+    VisualLine,
+}
+
+/// Command the host editor has to carry out for a Vim key
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VimCommand {
+    Undo(usize),
+    Redo(usize),
+}
+
+/// Outcome of feeding one key to the Vim layer
+#[derive(Debug, Default)]
+pub struct VimResponse {
+    /// False when the key means nothing to Vim and should go to the regular keymap
+    pub handled: bool,
+    /// Edits already applied to the buffer, in order
+    pub edits: Vec<Edit>,
+    pub command: Option<VimCommand>,
+    /// Text written to the `+` register, to be placed on the system clipboard
+    pub clipboard: Option<String>,
+}
+
+/// Contents of a register; linewise text is put on lines of its own
+#[derive(Debug, Clone, PartialEq)]
+pub struct Register {
+    pub text: String,
+    pub linewise: bool,
+}
+
+impl Register {
+    pub fn new(text: impl Into<String>, linewise: bool) -> Self {
+        Self { text: text.into(), linewise }
+    }
+}
+
+/// The unnamed (`"`), yank (`0`), named (`a`-`z`) and clipboard (`+`/`*`) registers
+#[derive(Debug, Default)]
+pub struct Registers {
+    registers: HashMap<char, Register>,
+}
+
+impl Registers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a register by name; `*` is an alias for `+` and names are case-insensitive
+    pub fn get(&self, name: char) -> Option<&Register> {
+        self.registers.get(&Self::canonical(name))
+    }
+
+    /// Overwrite a register without touching the others (e.g. to sync `+` with the clipboard)
+    pub fn set(&mut self, name: char, register: Register) {
+        self.registers.insert(Self::canonical(name), register);
+    }
+
+    /// Store yanked or deleted text the way Vim does.
+    ///
+    /// Without a name the text goes to the unnamed register (and `0` for
+    /// yanks); a named register also fills the unnamed one, an uppercase
+    /// name appends, and `_` discards the text.
+    pub fn store(&mut self, name: Option<char>, register: Register, yank: bool) {
+        let register = match name {
+            Some('_') => return,
+            Some(name) if name.is_ascii_uppercase() => {
+                let combined = match self.get(name) {
+                    Some(existing) if existing.linewise || register.linewise => {
+                        Register::new(format!("{}\n{}", existing.text, register.text), true)
+                    }
+                    Some(existing) => Register::new(format!("{}{}", existing.text, register.text), false),
+                    None => register,
+                };
+                self.set(name, combined.clone());
+                combined
+            }
+            Some(name) if name != '"' => {
+                self.set(name, register.clone());
+                register
+            }
+            _ => {
+                if yank {
+                    self.set('0', register.clone());
+                }
+                register
+            }
+        };
+        self.set('"', register);
+    }
+
+    fn canonical(name: char) -> char {
+        match name {
+            '*' => '+',
+            name => name.to_ascii_lowercase(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+impl Operator {
+    fn from_char(ch: char) -> Option<Self> {
+        match ch {
+            'd' => Some(Operator::Delete),
+            'c' => Some(Operator::Change),
+            'y' => Some(Operator::Yank),
+            _ => None,
+        }
+    }
+}
+
+/// `f`, `F`, `t` and `T`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FindKind {
+    Forward,
+    Backward,
+    TillForward,
+    TillBackward,
+}
+
+impl FindKind {
+    fn reversed(self) -> Self {
+        match self {
+            FindKind::Forward => FindKind::Backward,
+            FindKind::Backward => FindKind::Forward,
+            FindKind::TillForward => FindKind::TillBackward,
+            FindKind::TillBackward => FindKind::TillForward,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+    WordStart { big: bool },
+    WordBack { big: bool },
+    WordEnd { big: bool },
+    LineStart,
+    FirstNonBlank,
+    LineEnd,
+    DocumentStart,
+    DocumentEnd,
+    Find(FindKind, char),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MotionKind {
+    Exclusive,
+    Inclusive,
+    Linewise,
+}
+
+/// Key that needs a following char before anything happens
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Prefix {
+    Register,
+    G,
+    Find(FindKind),
+    Replace,
+}
+
+/// Partially typed normal/visual mode command, e.g. `"a2d3`
+#[derive(Debug, Default)]
+struct Pending {
+    register: Option<char>,
+    count: Option<usize>,
+    operator: Option<Operator>,
+    operator_count: Option<usize>,
+    prefix: Option<Prefix>,
+    keys: String,
+}
+
+impl Pending {
+    fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Effective count; counts before and after the operator multiply (`2d3w` = 6 words)
+    fn count(&self) -> Option<usize> {
+        match (self.count, self.operator_count) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(1) * b.unwrap_or(1)),
+        }
+    }
+}
+
+/// Buffer, cursor and response a key is processed against
+struct Context<'a> {
+    buffer: &'a mut TextBuffer,
+    cursor: &'a mut CursorController,
+    response: &'a mut VimResponse,
+}
+
+impl Context<'_> {
+    fn position(&self) -> CursorPosition {
+        self.cursor.position().clone()
+    }
+
+    fn char_index(&self) -> usize {
+        self.buffer.pos_to_char(self.cursor.row(), self.cursor.col())
+    }
+
+    fn set_position(&mut self, position: CursorPosition) {
+        self.cursor.set_position(position.row, position.col);
+    }
+
+    fn set_char_index(&mut self, char_idx: usize) {
+        let (row, col) = self.buffer.char_to_pos(char_idx);
+        self.cursor.set_position(row, col);
+    }
+
+    fn apply(&mut self, edit: Edit) {
+        edit.apply(self.buffer);
+        self.response.edits.push(edit);
+    }
+
+    fn insert(&mut self, at: usize, text: &str) {
+        if !text.is_empty() {
+            self.apply(Edit::insert(at, text));
+        }
+    }
+
+    fn delete(&mut self, start: usize, end: usize) {
+        if start < end {
+            let text = self.buffer.slice(start, end);
+            self.apply(Edit::delete(start, text));
+        }
+    }
+
+    /// Char index just past the content of a line (before its line ending)
+    fn line_end(&self, row: usize) -> usize {
+        self.buffer.line_to_char(row) + self.buffer.line_len(row)
+    }
+
+    /// Char index `n` grapheme clusters after the cursor, stopping at the line end
+    fn clusters_after(&self, n: usize) -> usize {
+        let row = self.cursor.row();
+        self.buffer.line_to_char(row) + clusters_after(&self.buffer.line(row), self.cursor.col(), n)
+    }
+
+    /// Char index `n` grapheme clusters before the cursor, stopping at the line start
+    fn clusters_before(&self, n: usize) -> usize {
+        let row = self.cursor.row();
+        self.buffer.line_to_char(row) + clusters_before(&self.buffer.line(row), self.cursor.col(), n)
+    }
+}
+
+/// Column `n` grapheme clusters after `col`, stopping at the line end
+fn clusters_after(line: &str, col: usize, n: usize) -> usize {
+    (0..n).fold(col, |col, _| text_metrics::next_boundary(line, col))
+}
+
+/// Column `n` grapheme clusters before `col`, stopping at the line start
+fn clusters_before(line: &str, col: usize, n: usize) -> usize {
+    (0..n).fold(col, |col, _| text_metrics::prev_boundary(line, col))
+}
+
+/// Start of a line's last grapheme cluster, as far right as normal mode goes
+fn last_cluster(line: &str) -> usize {
+    text_metrics::prev_boundary(line, line.chars().count())
+}
+
+/// Vim emulation layer: modes, motions, operators, counts, registers and `.` repeat.
+///
+/// The host editor feeds it key events together with its buffer and cursor.
+/// Edits are applied directly and returned in the `VimResponse` so the host
+/// can record them for undo; keys Vim does not use (most Ctrl shortcuts)
+/// are left unhandled for the regular keymap.
+pub struct Vim {
+    mode: VimMode,
+    visual_anchor: CursorPosition,
+    pending: Pending,
+    registers: Registers,
+    last_find: Option<(FindKind, char)>,
+    // Keys of the change being typed, and of the last complete one for `.`
+    change_keys: Vec<KeyEvent>,
+    is_change: bool,
+    last_change: Vec<KeyEvent>,
+    last_change_count: Option<usize>,
+    replaying: bool,
+}
+
+impl Vim {
+    pub fn new() -> Self {
+        Self {
+            mode: VimMode::Normal,
+            visual_anchor: CursorPosition::zero(),
+            pending: Pending::default(),
+            registers: Registers::new(),
+            last_find: None,
+            change_keys: Vec::new(),
+            is_change: false,
+            last_change: Vec::new(),
+            last_change_count: None,
+            replaying: false,
+        }
+    }
+
+    pub fn mode(&self) -> VimMode {
+        self.mode
+    }
+
+    /// Keys of the command being typed, e.g. `2d`
+    pub fn pending_keys(&self) -> &str {
+        &self.pending.keys
+    }
+
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    pub fn registers_mut(&mut self) -> &mut Registers {
+        &mut self.registers
+    }
+
+    /// Drop back to normal mode, e.g. after the host moved the cursor with the mouse
+    pub fn reset(&mut self) {
+        self.mode = VimMode::Normal;
+        self.pending = Pending::default();
+    }
+
+    /// Selection covered by visual mode, with an exclusive end column
+    pub fn selection(&self, cursor: &CursorController, buffer: &TextBuffer) -> Option<TextSelection> {
+        let (start, end) = order(self.visual_anchor.clone(), cursor.position().clone());
+        match self.mode {
+            VimMode::Visual => {
+                // The end covers the whole cluster under the cursor
+                let end_col = text_metrics::next_boundary(&buffer.line(end.row), end.col).max(end.col);
+                Some(TextSelection::new(start.row, start.col, end.row, end_col))
+            }
+            VimMode::VisualLine => Some(TextSelection::new(start.row, 0, end.row, buffer.line_len(end.row))),
+            _ => None,
+        }
+    }
+
+    /// Process a key event against the buffer and cursor
+    pub fn handle_key(&mut self, key_event: KeyEvent, buffer: &mut TextBuffer, cursor: &mut CursorController) -> VimResponse {
+        let mut response = VimResponse::default();
+        let mut ctx = Context { buffer, cursor, response: &mut response };
+        ctx.response.handled = self.process(key_event, &mut ctx);
+        response
+    }
+
+    fn process(&mut self, key_event: KeyEvent, ctx: &mut Context) -> bool {
+        let fresh_command = self.mode == VimMode::Normal && self.pending.is_empty();
+        if fresh_command && !self.replaying {
+            self.change_keys.clear();
+            self.is_change = false;
+        }
+
+        let handled = match self.mode {
+            VimMode::Insert => self.insert_key(key_event, ctx),
+            _ => self.command_key(key_event, ctx),
+        };
+        if !handled || self.replaying {
+            return handled;
+        }
+
+        // A change is complete once we are back in normal mode with nothing pending
+        self.change_keys.push(key_event);
+        if self.is_change && self.mode == VimMode::Normal && self.pending.is_empty() {
+            let count_keys = self
+                .change_keys
+                .iter()
+                .take_while(|key| matches!(key.code, KeyCode::Char(ch) if ch.is_ascii_digit()))
+                .count();
+            let count: String = self.change_keys[..count_keys]
+                .iter()
+                .filter_map(|key| match key.code {
+                    KeyCode::Char(ch) => Some(ch),
+                    _ => None,
+                })
+                .collect();
+            self.last_change_count = count.parse().ok();
+            self.last_change = self.change_keys[count_keys..].to_vec();
+            self.is_change = false;
+        }
+        handled
+    }
+
+    fn insert_key(&mut self, key_event: KeyEvent, ctx: &mut Context) -> bool {
+        if key_event.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+            return false;
+        }
+
+        let idx = ctx.char_index();
+        match key_event.code {
+            KeyCode::Esc => {
+                self.mode = VimMode::Normal;
+                let idx = ctx.clusters_before(1);
+                ctx.set_char_index(idx);
+            }
+            KeyCode::Char(ch) => {
+                ctx.insert(idx, &ch.to_string());
+                ctx.set_char_index(idx + 1);
+            }
+            KeyCode::Tab => {
+                ctx.insert(idx, "\t");
+                ctx.set_char_index(idx + 1);
+            }
+            KeyCode::Enter => {
+                ctx.insert(idx, "\n");
+                ctx.set_char_index(idx + 1);
+            }
+            KeyCode::Backspace => {
+                if idx > 0 {
+                    ctx.delete(idx - 1, idx);
+                    ctx.set_char_index(idx - 1);
+                }
+            }
+            KeyCode::Delete => ctx.delete(idx, (idx + 1).min(ctx.buffer.len_chars())),
+            KeyCode::Up => {
+                ctx.cursor.move_cursor(Direction::Up, ctx.buffer);
+            }
+            KeyCode::Down => {
+                ctx.cursor.move_cursor(Direction::Down, ctx.buffer);
+            }
+            KeyCode::Left => {
+                ctx.cursor.move_cursor(Direction::Left, ctx.buffer);
+            }
+            KeyCode::Right => {
+                ctx.cursor.move_cursor(Direction::Right, ctx.buffer);
+            }
+            KeyCode::Home => {
+                ctx.cursor.move_to_line_start();
+            }
+            KeyCode::End => {
+                ctx.cursor.move_to_line_end(ctx.buffer);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn command_key(&mut self, key_event: KeyEvent, ctx: &mut Context) -> bool {
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+
+        // Keys that complete a prefix (`"a`, `fx`, `gg`, `rx`)
+        if let Some(prefix) = self.pending.prefix.take() {
+            match key_event.code {
+                KeyCode::Char(ch) if !ctrl => {
+                    self.pending.keys.push(ch);
+                    self.complete_prefix(prefix, ch, ctx);
+                }
+                _ => self.pending = Pending::default(),
+            }
+            self.clamp_cursor(ctx);
+            return true;
+        }
+
+        let ch = match key_event.code {
+            KeyCode::Char('r') if ctrl => {
+                let count = self.pending.count().unwrap_or(1);
+                ctx.response.command = Some(VimCommand::Redo(count));
+                self.pending = Pending::default();
+                return true;
+            }
+            _ if ctrl || key_event.modifiers.contains(KeyModifiers::ALT) => return false,
+            KeyCode::Char(ch) => ch,
+            KeyCode::Esc => {
+                self.pending = Pending::default();
+                if self.mode != VimMode::Normal {
+                    self.mode = VimMode::Normal;
+                }
+                self.clamp_cursor(ctx);
+                return true;
+            }
+            KeyCode::Left | KeyCode::Backspace => 'h',
+            KeyCode::Right => 'l',
+            KeyCode::Up => 'k',
+            KeyCode::Down | KeyCode::Enter => 'j',
+            KeyCode::Home => '0',
+            KeyCode::End => '$',
+            _ => return false,
+        };
+
+        self.pending.keys.push(ch);
+        self.command_char(ch, ctx);
+        self.clamp_cursor(ctx);
+        true
+    }
+
+    fn complete_prefix(&mut self, prefix: Prefix, ch: char, ctx: &mut Context) {
+        match prefix {
+            Prefix::Register => {
+                self.pending.register = Some(ch);
+            }
+            Prefix::G => match ch {
+                'g' => self.motion(Motion::DocumentStart, ctx),
+                _ => self.pending = Pending::default(),
+            },
+            Prefix::Find(kind) => {
+                self.last_find = Some((kind, ch));
+                self.motion(Motion::Find(kind, ch), ctx);
+            }
+            Prefix::Replace => {
+                self.replace_chars(ch, ctx);
+                self.pending = Pending::default();
+            }
+        }
+    }
+
+    fn command_char(&mut self, ch: char, ctx: &mut Context) {
+        let visual = matches!(self.mode, VimMode::Visual | VimMode::VisualLine);
+
+        // Counts: `0` is only part of a count once one has started
+        if let Some(digit) = ch.to_digit(10) {
+            let target = if self.pending.operator.is_some() {
+                &mut self.pending.operator_count
+            } else {
+                &mut self.pending.count
+            };
+            if digit != 0 || target.is_some() {
+                *target = Some(target.unwrap_or(0).saturating_mul(10).saturating_add(digit as usize));
+                return;
+            }
+        }
+
+        match ch {
+            '"' if self.pending.operator.is_none() => self.pending.prefix = Some(Prefix::Register),
+            'g' => self.pending.prefix = Some(Prefix::G),
+            'f' => self.pending.prefix = Some(Prefix::Find(FindKind::Forward)),
+            'F' => self.pending.prefix = Some(Prefix::Find(FindKind::Backward)),
+            't' => self.pending.prefix = Some(Prefix::Find(FindKind::TillForward)),
+            'T' => self.pending.prefix = Some(Prefix::Find(FindKind::TillBackward)),
+            'r' if !visual && self.pending.operator.is_none() => self.pending.prefix = Some(Prefix::Replace),
+
+            'h' => self.motion(Motion::Left, ctx),
+            'l' | ' ' => self.motion(Motion::Right, ctx),
+            'k' => self.motion(Motion::Up, ctx),
+            'j' => self.motion(Motion::Down, ctx),
+            'w' => self.motion(Motion::WordStart { big: false }, ctx),
+            'W' => self.motion(Motion::WordStart { big: true }, ctx),
+            'b' => self.motion(Motion::WordBack { big: false }, ctx),
+            'B' => self.motion(Motion::WordBack { big: true }, ctx),
+            'e' => self.motion(Motion::WordEnd { big: false }, ctx),
+            'E' => self.motion(Motion::WordEnd { big: true }, ctx),
+            '0' => self.motion(Motion::LineStart, ctx),
+            '^' => self.motion(Motion::FirstNonBlank, ctx),
+            '$' => self.motion(Motion::LineEnd, ctx),
+            'G' => self.motion(Motion::DocumentEnd, ctx),
+            ';' | ',' => match self.last_find {
+                Some((kind, target)) => {
+                    let kind = if ch == ',' { kind.reversed() } else { kind };
+                    self.motion(Motion::Find(kind, target), ctx);
+                }
+                None => self.pending = Pending::default(),
+            },
+
+            _ if visual => self.visual_command(ch, ctx),
+            _ => self.normal_command(ch, ctx),
+        }
+    }
+
+    fn normal_command(&mut self, ch: char, ctx: &mut Context) {
+        let count = self.pending.count().unwrap_or(1);
+
+        // Doubled operators work on whole lines: `dd`, `3cc`, `yy`
+        if let Some(operator) = self.pending.operator {
+            if Operator::from_char(ch) == Some(operator) {
+                let row = ctx.cursor.row();
+                let last = (row + count - 1).min(ctx.buffer.len_lines() - 1);
+                self.apply_operator(operator, (row, 0), (last, 0), MotionKind::Linewise, ctx);
+            }
+            self.pending = Pending::default();
+            return;
+        }
+        if let Some(operator) = Operator::from_char(ch) {
+            self.pending.operator = Some(operator);
+            return;
+        }
+
+        let register = self.pending.register;
+        let position = ctx.position();
+        let idx = ctx.char_index();
+        let line_end = ctx.line_end(position.row);
+
+        match ch {
+            'i' => self.start_insert(),
+            'a' => {
+                ctx.set_char_index(ctx.clusters_after(1));
+                self.start_insert();
+            }
+            'I' => {
+                let col = cursor_utils::first_non_blank(&ctx.buffer.line(position.row));
+                ctx.cursor.set_position(position.row, col);
+                self.start_insert();
+            }
+            'A' => {
+                ctx.set_char_index(line_end);
+                self.start_insert();
+            }
+            'o' => {
+                ctx.insert(line_end, "\n");
+                ctx.cursor.set_position(position.row + 1, 0);
+                self.start_insert();
+            }
+            'O' => {
+                let line_start = ctx.buffer.line_to_char(position.row);
+                ctx.insert(line_start, "\n");
+                ctx.cursor.set_position(position.row, 0);
+                self.start_insert();
+            }
+            'x' | 's' => {
+                let end = ctx.clusters_after(count);
+                let operator = if ch == 'x' { Operator::Delete } else { Operator::Change };
+                self.operate_on_chars(operator, idx, end, ctx);
+            }
+            'X' => {
+                self.operate_on_chars(Operator::Delete, ctx.clusters_before(count), idx, ctx);
+            }
+            'D' | 'C' => {
+                let last = (position.row + count - 1).min(ctx.buffer.len_lines() - 1);
+                let end = ctx.line_end(last);
+                let operator = if ch == 'D' { Operator::Delete } else { Operator::Change };
+                self.operate_on_chars(operator, idx, end, ctx);
+            }
+            'S' | 'Y' => {
+                let last = (position.row + count - 1).min(ctx.buffer.len_lines() - 1);
+                let operator = if ch == 'S' { Operator::Change } else { Operator::Yank };
+                self.apply_operator(operator, (position.row, 0), (last, 0), MotionKind::Linewise, ctx);
+            }
+            'p' | 'P' => {
+                if let Some(contents) = self.registers.get(register.unwrap_or('"')).cloned() {
+                    self.is_change = true;
+                    put(&contents, count, ch == 'p', ctx);
+                }
+            }
+            'J' => {
+                self.is_change = true;
+                join_lines(count.max(2) - 1, ctx);
+            }
+            '~' => {
+                self.is_change = true;
+                let end = ctx.clusters_after(count);
+                let toggled: String = ctx
+                    .buffer
+                    .slice(idx, end)
+                    .chars()
+                    .map(|c| if c.is_uppercase() { c.to_lowercase().next().unwrap_or(c) } else { c.to_uppercase().next().unwrap_or(c) })
+                    .collect();
+                ctx.delete(idx, end);
+                ctx.insert(idx, &toggled);
+                ctx.set_char_index(end);
+            }
+            'u' => ctx.response.command = Some(VimCommand::Undo(count)),
+            'v' => self.start_visual(VimMode::Visual, ctx),
+            'V' => self.start_visual(VimMode::VisualLine, ctx),
+            '.' => {
+                let count = self.pending.count().or(self.last_change_count);
+                self.pending = Pending::default();
+                self.repeat_last_change(count, ctx);
+                return;
+            }
+            _ => {}
+        }
+        self.pending = Pending::default();
+    }
+
+    fn visual_command(&mut self, ch: char, ctx: &mut Context) {
+        let register = self.pending.register;
+        self.pending = Pending::default();
+
+        let (start, end) = order(self.visual_anchor.clone(), ctx.position());
+        let kind = if self.mode == VimMode::VisualLine { MotionKind::Linewise } else { MotionKind::Inclusive };
+        let start = (start.row, start.col);
+        let end = (end.row, end.col);
+
+        match ch {
+            'v' | 'V' => {
+                let mode = if ch == 'v' { VimMode::Visual } else { VimMode::VisualLine };
+                self.mode = if self.mode == mode { VimMode::Normal } else { mode };
+            }
+            'o' => {
+                let anchor = std::mem::replace(&mut self.visual_anchor, ctx.position());
+                ctx.set_position(anchor);
+            }
+            'd' | 'x' | 'c' | 's' | 'y' => {
+                let operator = match ch {
+                    'd' | 'x' => Operator::Delete,
+                    'c' | 's' => Operator::Change,
+                    _ => Operator::Yank,
+                };
+                self.mode = VimMode::Normal;
+                self.pending.register = register;
+                self.apply_operator(operator, start, end, kind, ctx);
+                self.pending = Pending::default();
+            }
+            'p' | 'P' => {
+                // Read the register first: deleting the selection overwrites it
+                let Some(contents) = self.registers.get(register.unwrap_or('"')).cloned() else {
+                    return;
+                };
+                self.mode = VimMode::Normal;
+                self.apply_operator(Operator::Delete, start, end, kind, ctx);
+                let contents = Register::new(contents.text, contents.linewise || kind == MotionKind::Linewise);
+                put(&contents, 1, false, ctx);
+            }
+            _ => {}
+        }
+    }
+
+    fn start_insert(&mut self) {
+        self.mode = VimMode::Insert;
+        self.is_change = true;
+    }
+
+    fn start_visual(&mut self, mode: VimMode, ctx: &mut Context) {
+        self.mode = mode;
+        self.visual_anchor = ctx.position();
+    }
+
+    fn motion(&mut self, motion: Motion, ctx: &mut Context) {
+        let count = self.pending.count();
+        let operator = self.pending.operator;
+        let start = ctx.position();
+
+        // `cw` on a word behaves like `ce`
+        let motion = match (operator, motion) {
+            (Some(Operator::Change), Motion::WordStart { big }) => {
+                let idx = ctx.char_index();
+                match ctx.buffer.char_at(idx) {
+                    Some(ch) if !ch.is_whitespace() => Motion::WordEnd { big },
+                    _ => motion,
+                }
+            }
+            _ => motion,
+        };
+
+        let target = motion_target(motion, count, &start, ctx.buffer, operator.is_some());
+        match (operator, target) {
+            (Some(operator), Some((target, kind))) => {
+                self.apply_operator(operator, (start.row, start.col), (target.row, target.col), kind, ctx);
+            }
+            (None, Some((target, _))) => {
+                ctx.set_position(target);
+            }
+            _ => {}
+        }
+
+        // In visual mode a motion only moves the cursor and keeps the register
+        self.pending = Pending::default();
+    }
+
+    /// Run an operator over the text between two positions
+    fn apply_operator(&mut self, operator: Operator, from: (usize, usize), to: (usize, usize), kind: MotionKind, ctx: &mut Context) {
+        let (from, to) = if from <= to { (from, to) } else { (to, from) };
+
+        if kind == MotionKind::Linewise {
+            let (first, last) = (from.0, to.0);
+            let text = (first..=last).map(|row| ctx.buffer.line(row)).collect::<Vec<_>>().join("\n");
+            self.store_register(operator, Register::new(text, true), ctx);
+
+            match operator {
+                Operator::Yank => {
+                    if first < ctx.cursor.row() {
+                        ctx.cursor.set_position(first, ctx.cursor.col());
+                    }
+                }
+                Operator::Delete => {
+                    self.is_change = true;
+                    let (start, _) = ctx.buffer.line_range(first);
+                    let (_, end) = ctx.buffer.line_range(last);
+                    ctx.delete(start, end);
+                    let row = first.min(ctx.buffer.len_lines() - 1);
+                    let col = cursor_utils::first_non_blank(&ctx.buffer.line(row));
+                    ctx.cursor.set_position(row, col);
+                }
+                Operator::Change => {
+                    // Keep the indentation of the first line and one empty line to type on
+                    let indent = cursor_utils::first_non_blank(&ctx.buffer.line(first));
+                    let start = ctx.buffer.line_to_char(first) + indent;
+                    let end = ctx.line_end(last);
+                    ctx.delete(start, end);
+                    ctx.cursor.set_position(first, indent);
+                    self.start_insert();
+                }
+            }
+            return;
+        }
+
+        let start = ctx.buffer.pos_to_char(from.0, from.1);
+        let mut end = ctx.buffer.pos_to_char(to.0, to.1);
+        if kind == MotionKind::Inclusive {
+            let next = text_metrics::next_boundary(&ctx.buffer.line(to.0), to.1);
+            end = (ctx.buffer.line_to_char(to.0) + next).max(end);
+        }
+        self.operate_on_chars(operator, start, end, ctx);
+    }
+
+    /// Run an operator over a char range
+    fn operate_on_chars(&mut self, operator: Operator, start: usize, end: usize, ctx: &mut Context) {
+        let text = ctx.buffer.slice(start, end);
+        self.store_register(operator, Register::new(text, false), ctx);
+
+        if operator != Operator::Yank {
+            self.is_change = true;
+            ctx.delete(start, end);
+        }
+        ctx.set_char_index(start);
+        if operator == Operator::Change {
+            self.start_insert();
+        }
+    }
+
+    fn store_register(&mut self, operator: Operator, register: Register, ctx: &mut Context) {
+        let name = self.pending.register;
+        if matches!(name, Some('+') | Some('*')) {
+            ctx.response.clipboard = Some(register.text.clone());
+        }
+        self.registers.store(name, register, operator == Operator::Yank);
+    }
+
+    fn replace_chars(&mut self, ch: char, ctx: &mut Context) {
+        let count = self.pending.count().unwrap_or(1);
+        let idx = ctx.char_index();
+        let line = ctx.buffer.line(ctx.cursor.row());
+        let remaining = text_metrics::clusters(&line).iter().filter(|cluster| cluster.col >= ctx.cursor.col()).count();
+        if count > remaining {
+            return;
+        }
+        self.is_change = true;
+        let end = ctx.clusters_after(count);
+        ctx.delete(idx, end);
+        ctx.insert(idx, &ch.to_string().repeat(count));
+        ctx.set_char_index(idx + count - 1);
+    }
+
+    fn repeat_last_change(&mut self, count: Option<usize>, ctx: &mut Context) {
+        if self.last_change.is_empty() {
+            return;
+        }
+        self.last_change_count = count;
+
+        let mut keys: Vec<KeyEvent> = count
+            .map(|count| count.to_string().chars().map(|ch| KeyEvent::from(KeyCode::Char(ch))).collect())
+            .unwrap_or_default();
+        keys.extend(self.last_change.iter().copied());
+
+        self.replaying = true;
+        for key in keys {
+            self.process(key, ctx);
+        }
+        self.replaying = false;
+        self.is_change = false;
+    }
+
+    /// Normal and visual mode keep the cursor on a char, not past the end of the line
+    fn clamp_cursor(&self, ctx: &mut Context) {
+        if self.mode == VimMode::Insert {
+            return;
+        }
+        let row = ctx.cursor.row().min(ctx.buffer.len_lines() - 1);
+        let line = ctx.buffer.line(row);
+        let col = text_metrics::snap_to_boundary(&line, ctx.cursor.col().min(last_cluster(&line)));
+        ctx.cursor.set_position(row, col);
+    }
+}
+
+impl Default for Vim {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn order(a: CursorPosition, b: CursorPosition) -> (CursorPosition, CursorPosition) {
+    if (a.row, a.col) <= (b.row, b.col) { (a, b) } else { (b, a) }
+}
+
+/// Put register contents after (`p`) or before (`P`) the cursor
+fn put(register: &Register, count: usize, after: bool, ctx: &mut Context) {
+    let position = ctx.position();
+    if register.linewise {
+        let text = vec![register.text.as_str(); count].join("\n");
+        let row = if after {
+            let at = ctx.line_end(position.row);
+            ctx.insert(at, &format!("\n{}", text));
+            position.row + 1
+        } else {
+            let at = ctx.buffer.line_to_char(position.row);
+            ctx.insert(at, &format!("{}\n", text));
+            position.row
+        };
+        let col = cursor_utils::first_non_blank(&ctx.buffer.line(row));
+        ctx.cursor.set_position(row, col);
+    } else {
+        let text = register.text.repeat(count);
+        let idx = ctx.char_index();
+        let at = if after && ctx.buffer.line_len(position.row) > 0 {
+            ctx.clusters_after(1)
+        } else {
+            idx
+        };
+        ctx.insert(at, &text);
+        ctx.set_char_index((at + text.chars().count()).saturating_sub(1));
+    }
+}
+
+/// Join the cursor line with the `joins` lines below it, separated by single spaces
+fn join_lines(joins: usize, ctx: &mut Context) {
+    let row = ctx.cursor.row();
+    for _ in 0..joins {
+        if row + 1 >= ctx.buffer.len_lines() {
+            break;
+        }
+        let end = ctx.line_end(row);
+        let next = ctx.buffer.line(row + 1);
+        let next_start = ctx.buffer.line_to_char(row + 1);
+        ctx.delete(end, next_start + cursor_utils::first_non_blank(&next));
+
+        let current = ctx.buffer.line(row);
+        let next_content = next.trim_start();
+        let separator = if current.is_empty() || current.ends_with(' ') || next_content.is_empty() || next_content.starts_with(')') { "" } else { " " };
+        ctx.insert(end, separator);
+        ctx.set_char_index(end);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CharClass {
+    Blank,
+    Word,
+    Punctuation,
+}
+
+fn char_class(ch: char, big: bool) -> CharClass {
+    if ch.is_whitespace() {
+        CharClass::Blank
+    } else if big || ch.is_alphanumeric() || ch == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punctuation
+    }
+}
+
+/// Start of the next word (`w`); empty lines count as words
+fn next_word_start(buffer: &TextBuffer, idx: usize, big: bool) -> usize {
+    let len = buffer.len_chars();
+    let class_at = |i: usize| char_class(buffer.char_at(i).unwrap_or(' '), big);
+    let mut i = idx;
+    if i >= len {
+        return len;
+    }
+    let class = class_at(i);
+    if class != CharClass::Blank {
+        while i < len && class_at(i) == class {
+            i += 1;
+        }
+    }
+    while i < len && class_at(i) == CharClass::Blank {
+        if buffer.char_at(i) == Some('\n') && buffer.char_at(i + 1) == Some('\n') {
+            return i + 1;
+        }
+        i += 1;
+    }
+    i
+}
+
+/// Start of the current or previous word (`b`)
+fn prev_word_start(buffer: &TextBuffer, idx: usize, big: bool) -> usize {
+    let class_at = |i: usize| char_class(buffer.char_at(i).unwrap_or(' '), big);
+    if idx == 0 {
+        return 0;
+    }
+    let mut i = idx - 1;
+    while i > 0 && class_at(i) == CharClass::Blank {
+        if buffer.char_at(i) == Some('\n') && buffer.char_at(i - 1) == Some('\n') {
+            return i;
+        }
+        i -= 1;
+    }
+    let class = class_at(i);
+    while i > 0 && class_at(i - 1) == class {
+        i -= 1;
+    }
+    i
+}
+
+/// End of the current or next word (`e`)
+fn word_end(buffer: &TextBuffer, idx: usize, big: bool) -> usize {
+    let len = buffer.len_chars();
+    let class_at = |i: usize| char_class(buffer.char_at(i).unwrap_or(' '), big);
+    let mut i = idx + 1;
+    while i < len && class_at(i) == CharClass::Blank {
+        i += 1;
+    }
+    if i >= len {
+        return len.saturating_sub(1);
+    }
+    let class = class_at(i);
+    while i + 1 < len && class_at(i + 1) == class {
+        i += 1;
+    }
+    i
+}
+
+/// Where a motion lands from `start`, and how an operator treats the covered text
+fn motion_target(motion: Motion, count: Option<usize>, start: &CursorPosition, buffer: &TextBuffer, operator: bool) -> Option<(CursorPosition, MotionKind)> {
+    let n = count.unwrap_or(1).max(1);
+    let last_row = buffer.len_lines() - 1;
+    let line_len = buffer.line_len(start.row);
+    let mut target = start.clone();
+
+    let kind = match motion {
+        Motion::Left => {
+            target.col = clusters_before(&buffer.line(start.row), start.col, n);
+            MotionKind::Exclusive
+        }
+        Motion::Right => {
+            let line = buffer.line(start.row);
+            let max = if operator { line_len } else { last_cluster(&line) };
+            target.col = clusters_after(&line, start.col, n).min(max);
+            MotionKind::Exclusive
+        }
+        Motion::Up | Motion::Down => {
+            let direction = if motion == Motion::Up { Direction::Up } else { Direction::Down };
+            for _ in 0..n {
+                cursor_utils::step_position(&mut target, &direction, buffer);
+            }
+            MotionKind::Linewise
+        }
+        Motion::WordStart { big } => {
+            let mut idx = buffer.pos_to_char(start.row, start.col);
+            let mut previous = idx;
+            for _ in 0..n {
+                previous = idx;
+                idx = next_word_start(buffer, idx, big);
+            }
+            // `dw` on the last word of a line stops at the line end
+            if operator && buffer.char_to_line(idx) > buffer.char_to_line(previous) {
+                let row = buffer.char_to_line(previous);
+                idx = buffer.line_to_char(row) + buffer.line_len(row);
+            }
+            let (row, col) = buffer.char_to_pos(idx);
+            target = CursorPosition::new(row, col);
+            MotionKind::Exclusive
+        }
+        Motion::WordBack { big } => {
+            let mut idx = buffer.pos_to_char(start.row, start.col);
+            for _ in 0..n {
+                idx = prev_word_start(buffer, idx, big);
+            }
+            let (row, col) = buffer.char_to_pos(idx);
+            target = CursorPosition::new(row, col);
+            MotionKind::Exclusive
+        }
+        Motion::WordEnd { big } => {
+            let mut idx = buffer.pos_to_char(start.row, start.col);
+            for _ in 0..n {
+                idx = word_end(buffer, idx, big);
+            }
+            let (row, col) = buffer.char_to_pos(idx);
+            target = CursorPosition::new(row, col);
+            MotionKind::Inclusive
+        }
+        Motion::LineStart => {
+            target.col = 0;
+            MotionKind::Exclusive
+        }
+        Motion::FirstNonBlank => {
+            target.col = cursor_utils::first_non_blank(&buffer.line(start.row));
+            MotionKind::Exclusive
+        }
+        Motion::LineEnd => {
+            target.row = (start.row + n - 1).min(last_row);
+            target.col = last_cluster(&buffer.line(target.row));
+            MotionKind::Inclusive
+        }
+        Motion::DocumentStart | Motion::DocumentEnd => {
+            target.row = match (motion, count) {
+                (_, Some(line)) => line.saturating_sub(1).min(last_row),
+                (Motion::DocumentStart, None) => 0,
+                _ => last_row,
+            };
+            target.col = cursor_utils::first_non_blank(&buffer.line(target.row));
+            MotionKind::Linewise
+        }
+        Motion::Find(kind, ch) => {
+            let chars: Vec<char> = buffer.line(start.row).chars().collect();
+            let found = match kind {
+                FindKind::Forward | FindKind::TillForward => {
+                    // `t` repeated with `;` must not get stuck right before the same char
+                    let from = if kind == FindKind::TillForward { start.col + 2 } else { start.col + 1 };
+                    (from..chars.len()).filter(|&col| chars[col] == ch).nth(n - 1)
+                }
+                FindKind::Backward | FindKind::TillBackward => {
+                    let before = if kind == FindKind::TillBackward { start.col.saturating_sub(1) } else { start.col };
+                    (0..before).rev().filter(|&col| chars[col] == ch).nth(n - 1)
+                }
+            }?;
+            target.col = match kind {
+                FindKind::TillForward => found - 1,
+                FindKind::TillBackward => found + 1,
+                _ => found,
+            };
+            match kind {
+                FindKind::Forward | FindKind::TillForward => MotionKind::Inclusive,
+                _ => MotionKind::Exclusive,
+            }
+        }
+    };
+
+    Some((target, kind))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Harness {
+        vim: Vim,
+        buffer: TextBuffer,
+        cursor: CursorController,
+    }
+
+    impl Harness {
+        fn new(text: &str) -> Self {
+            Self { vim: Vim::new(), buffer: TextBuffer::from(text), cursor: CursorController::new() }
+        }
+
+        fn keys(&mut self, keys: &str) -> Vec<VimResponse> {
+            keys.chars()
+                .map(|ch| {
+                    let code = if ch == '\x1b' { KeyCode::Esc } else { KeyCode::Char(ch) };
+                    self.vim.handle_key(KeyEvent::from(code), &mut self.buffer, &mut self.cursor)
+                })
+                .collect()
+        }
+
+        fn pos(&self) -> (usize, usize) {
+            (self.cursor.row(), self.cursor.col())
+        }
+    }
+
+    #[test]
+    fn test_motions() {
+        let mut h = Harness::new("foo.bar baz\n\n  qux end");
+        h.keys("w");
+        assert_eq!(h.pos(), (0, 3));
+        h.keys("w");
+        assert_eq!(h.pos(), (0, 4));
+        h.keys("W");
+        assert_eq!(h.pos(), (0, 8));
+        // Empty lines are a stop for `w`
+        h.keys("w");
+        assert_eq!(h.pos(), (1, 0));
+        h.keys("we");
+        assert_eq!(h.pos(), (2, 4));
+        h.keys("b0");
+        assert_eq!(h.pos(), (2, 0));
+        h.keys("$");
+        assert_eq!(h.pos(), (2, 8));
+        h.keys("ggfb");
+        assert_eq!(h.pos(), (0, 4));
+        h.keys(";");
+        assert_eq!(h.pos(), (0, 8));
+        h.keys("Fo");
+        assert_eq!(h.pos(), (0, 2));
+        h.keys("tz");
+        assert_eq!(h.pos(), (0, 9));
+        h.keys("2G");
+        assert_eq!(h.pos(), (1, 0));
+        h.keys("G");
+        assert_eq!(h.pos(), (2, 2));
+    }
+
+    #[test]
+    fn test_grapheme_clusters() {
+        // "e" with a combining accent, then a family emoji joined by ZWJs
+        let mut h = Harness::new("ae\u{301}b\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}c");
+        h.keys("l");
+        assert_eq!(h.pos(), (0, 1));
+        h.keys("l");
+        assert_eq!(h.pos(), (0, 3));
+        h.keys("lh");
+        assert_eq!(h.pos(), (0, 3));
+        h.keys("$");
+        assert_eq!(h.pos(), (0, 9));
+        h.keys("h");
+        assert_eq!(h.pos(), (0, 4));
+        h.keys("v");
+        assert_eq!(h.vim.selection(&h.cursor, &h.buffer), Some(TextSelection::new(0, 4, 0, 9)));
+        h.keys("\x1bx");
+        assert_eq!(h.buffer.to_string(), "ae\u{301}bc");
+
+        h.keys("0lx");
+        assert_eq!(h.buffer.to_string(), "abc");
+        let mut h = Harness::new("ae\u{301}b");
+        h.keys("$X");
+        assert_eq!(h.buffer.to_string(), "ab");
+        let mut h = Harness::new("ae\u{301}b");
+        h.keys("lrx");
+        assert_eq!(h.buffer.to_string(), "axb");
+    }
+
+    #[test]
+    fn test_operators_counts_and_registers() {
+        let mut h = Harness::new("one two three four\nline2\nline3");
+        h.keys("dw");
+        assert_eq!(h.buffer.line(0), "two three four");
+        h.keys("2dw");
+        assert_eq!(h.buffer.line(0), "four");
+        assert_eq!(h.vim.registers().get('"'), Some(&Register::new("two three ", false)));
+
+        // `dw` on the last word stays on its line
+        h.keys("dw");
+        assert_eq!(h.buffer.to_string(), "\nline2\nline3");
+
+        h.keys("j\"ayy");
+        assert_eq!(h.vim.registers().get('a'), Some(&Register::new("line2", true)));
+        h.keys("jdd");
+        assert_eq!(h.buffer.to_string(), "\nline2");
+        h.keys("\"ap");
+        assert_eq!(h.buffer.to_string(), "\nline2\nline2");
+        assert_eq!(h.pos(), (2, 0));
+
+        h.keys("gg0cwfirst\x1b");
+        assert_eq!(h.buffer.line(0), "first");
+        assert_eq!(h.pos(), (0, 4));
+
+        h.keys("j0c$x\x1b");
+        assert_eq!(h.buffer.line(1), "x");
+        h.keys("ggd2j");
+        assert_eq!(h.buffer.to_string(), "");
+    }
+
+    #[test]
+    fn test_dot_repeat_and_visual() {
+        let mut h = Harness::new("a b c d e f");
+        h.keys("dw..");
+        assert_eq!(h.buffer.to_string(), "d e f");
+        h.keys("2.");
+        assert_eq!(h.buffer.to_string(), "f");
+
+        let mut h = Harness::new("foo\nbar");
+        h.keys("Ahi\x1bj.");
+        assert_eq!(h.buffer.to_string(), "foohi\nbarhi");
+        let responses = h.keys("u");
+        assert_eq!(responses[0].command, Some(VimCommand::Undo(1)));
+
+        let mut h = Harness::new("hello world\nsecond");
+        h.keys("wvl");
+        assert_eq!(h.vim.mode(), VimMode::Visual);
+        assert_eq!(h.vim.selection(&h.cursor, &h.buffer), Some(TextSelection::new(0, 6, 0, 8)));
+        h.keys("d");
+        assert_eq!(h.buffer.line(0), "hello rld");
+        assert_eq!(h.vim.mode(), VimMode::Normal);
+
+        h.keys("Vjy");
+        assert_eq!(h.vim.registers().get('0'), Some(&Register::new("hello rld\nsecond", true)));
+        h.keys("Vp");
+        assert_eq!(h.buffer.to_string(), "hello rld\nsecond\nsecond");
+    }
+}
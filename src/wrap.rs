@@ -0,0 +1,219 @@
+use crate::buffer::TextBuffer;
+use crate::text_metrics;
+
+/// Split a line into the visual rows it occupies when soft-wrapped to `width` screen cells.
+///
+/// Each row is a half-open char range `[start, end)` that never splits a
+/// grapheme cluster. Rows break after the last whitespace that fits, or
+/// mid-word when a word is wider than a row. An empty line still occupies
+/// one (empty) row.
+pub fn wrap_line(line: &str, width: usize) -> Vec<(usize, usize)> {
+    let chars: Vec<char> = line.chars().collect();
+    let clusters = text_metrics::clusters(line);
+    let mut rows = Vec::new();
+    let mut start = 0;
+    let mut used = 0;
+    let mut last_break = None;
+    let mut idx = 0;
+
+    while let Some(cluster) = clusters.get(idx) {
+        if used + cluster.width > width && cluster.col > start {
+            // Prefer breaking after whitespace so words stay whole
+            let end = last_break.unwrap_or(cluster.col);
+            rows.push((start, end));
+            start = end;
+            used = 0;
+            last_break = None;
+            idx = clusters.iter().position(|cluster| cluster.col == start).unwrap_or(idx);
+            continue;
+        }
+        used += cluster.width;
+        if chars[cluster.col].is_whitespace() {
+            last_break = Some(cluster.col + cluster.len);
+        }
+        idx += 1;
+    }
+    rows.push((start, chars.len()));
+    rows
+}
+
+/// Visual row holding `col`, and the column's offset within that row.
+///
+/// A column at the end of a wrapped row belongs to the start of the next one.
+pub fn locate(rows: &[(usize, usize)], col: usize) -> (usize, usize) {
+    let idx = rows.iter().rposition(|&(start, _)| start <= col).unwrap_or(0);
+    (idx, col.saturating_sub(rows[idx].0))
+}
+
+/// Screen cells between the start of visual row `idx` and `col`
+pub fn offset_in_row(line: &str, rows: &[(usize, usize)], idx: usize, col: usize) -> usize {
+    text_metrics::display_col(line, col).saturating_sub(text_metrics::display_col(line, rows[idx].0))
+}
+
+/// Column drawn `cells` screen cells into visual row `idx`, clamped to that row
+pub fn col_in_row(line: &str, rows: &[(usize, usize)], idx: usize, cells: usize) -> usize {
+    let (start, end) = rows[idx];
+    let col = text_metrics::col_at_display(line, text_metrics::display_col(line, start) + cells).max(start);
+    if idx + 1 == rows.len() {
+        col.min(end)
+    } else if col >= end {
+        // The end of a wrapped row is drawn at the start of the next one
+        text_metrics::prev_boundary(line, end).max(start)
+    } else {
+        col
+    }
+}
+
+/// Position one visual row above or below `(row, col)`, keeping the screen column.
+///
+/// Returns `None` at the top or bottom of the document.
+pub fn move_visual(buffer: &TextBuffer, row: usize, col: usize, width: usize, down: bool) -> Option<(usize, usize)> {
+    let line = buffer.line(row);
+    let rows = wrap_line(&line, width);
+    let (idx, _) = locate(&rows, col);
+    let cells = offset_in_row(&line, &rows, idx, col);
+
+    if down {
+        if idx + 1 < rows.len() {
+            return Some((row, col_in_row(&line, &rows, idx + 1, cells)));
+        }
+        if row + 1 >= buffer.len_lines() {
+            return None;
+        }
+        let next_line = buffer.line(row + 1);
+        let next = wrap_line(&next_line, width);
+        Some((row + 1, col_in_row(&next_line, &next, 0, cells)))
+    } else {
+        if idx > 0 {
+            return Some((row, col_in_row(&line, &rows, idx - 1, cells)));
+        }
+        if row == 0 {
+            return None;
+        }
+        let prev_line = buffer.line(row - 1);
+        let prev = wrap_line(&prev_line, width);
+        Some((row - 1, col_in_row(&prev_line, &prev, prev.len() - 1, cells)))
+    }
+}
+
+/// First buffer row to show so that `(row, col)` fits in a viewport of `height` visual rows.
+///
+/// Scrolls as little as possible from `scroll_offset`.
+pub fn scroll_to_show(buffer: &TextBuffer, scroll_offset: usize, row: usize, col: usize, width: usize, height: usize) -> usize {
+    if row <= scroll_offset {
+        return row;
+    }
+
+    // Visual rows from the top of the cursor line down to the cursor
+    let (idx, _) = locate(&wrap_line(&buffer.line(row), width), col);
+    let mut needed = idx + 1;
+    let mut top = row;
+    while top > scroll_offset {
+        let above = wrap_line(&buffer.line(top - 1), width).len();
+        if needed + above > height {
+            break;
+        }
+        needed += above;
+        top -= 1;
+    }
+    top
+}
+
+/// Visual rows of the top line, `scroll_offset`, kept above the viewport.
+///
+/// Scrolling goes by whole buffer lines, so a cursor deep inside a line taller
+/// than the viewport would otherwise be drawn below it.
+pub fn hidden_rows(buffer: &TextBuffer, scroll_offset: usize, row: usize, col: usize, width: usize, height: usize) -> usize {
+    if row != scroll_offset {
+        return 0;
+    }
+    let (idx, _) = locate(&wrap_line(&buffer.line(row), width), col);
+    (idx + 1).saturating_sub(height)
+}
+
+/// Buffer position displayed at `visual_row` (counted from the top of the viewport) and screen column `col`
+pub fn position_at(buffer: &TextBuffer, scroll_offset: usize, visual_row: usize, col: usize, width: usize) -> (usize, usize) {
+    let mut remaining = visual_row;
+    let mut row = scroll_offset;
+
+    while row < buffer.len_lines() {
+        let line = buffer.line(row);
+        let rows = wrap_line(&line, width);
+        if remaining < rows.len() {
+            return (row, col_in_row(&line, &rows, remaining, col));
+        }
+        remaining -= rows.len();
+        row += 1;
+    }
+
+    // Below the last line
+    let last = buffer.len_lines().saturating_sub(1);
+    (last, buffer.line_len(last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_line() {
+        assert_eq!(wrap_line("", 4), vec![(0, 0)]);
+        assert_eq!(wrap_line("abcd", 4), vec![(0, 4)]);
+        // Breaks after spaces, or mid-word when a word does not fit
+        assert_eq!(wrap_line("ab cd ef", 5), vec![(0, 3), (3, 8)]);
+        assert_eq!(wrap_line("abcdefghij", 4), vec![(0, 4), (4, 8), (8, 10)]);
+        // Wide chars take two cells each
+        assert_eq!(wrap_line("日本語テキスト", 4), vec![(0, 2), (2, 4), (4, 6), (6, 7)]);
+        assert_eq!(wrap_line("日本", 1), vec![(0, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn test_visual_row_movement() {
+        let buffer = TextBuffer::from_lines(&["abcdefghij", "xy"]);
+        assert_eq!(locate(&wrap_line("abcdefghij", 4), 4), (1, 0));
+        assert_eq!(move_visual(&buffer, 0, 1, 4, true), Some((0, 5)));
+        assert_eq!(move_visual(&buffer, 0, 9, 4, true), Some((1, 1)));
+        assert_eq!(move_visual(&buffer, 1, 1, 4, false), Some((0, 9)));
+        // Offsets past the end of a wrapped row stay on that row
+        let spaced = TextBuffer::from_lines(&["ab cd ef"]);
+        assert_eq!(move_visual(&spaced, 0, 7, 5, false), Some((0, 2)));
+        assert_eq!(move_visual(&buffer, 0, 3, 4, false), None);
+        assert_eq!(move_visual(&buffer, 1, 0, 4, true), None);
+        // The screen column is kept across wide chars
+        let wide = TextBuffer::from_lines(&["日本語", "abcd"]);
+        assert_eq!(move_visual(&wide, 0, 1, 10, true), Some((1, 2)));
+        assert_eq!(move_visual(&wide, 1, 3, 10, false), Some((0, 1)));
+    }
+
+    #[test]
+    fn test_scroll_and_hit_testing() {
+        let buffer = TextBuffer::from_lines(&["abcdefghij", "xy", "z"]);
+        assert_eq!(position_at(&buffer, 0, 1, 2, 4), (0, 6));
+        assert_eq!(position_at(&buffer, 0, 2, 9, 4), (0, 10));
+        assert_eq!(position_at(&buffer, 0, 3, 0, 4), (1, 0));
+        assert_eq!(position_at(&buffer, 1, 5, 0, 4), (2, 1));
+
+        // Line 0 takes three rows, so a two-row viewport must start at line 1 to show line 2
+        assert_eq!(scroll_to_show(&buffer, 0, 2, 0, 4, 2), 1);
+        assert_eq!(scroll_to_show(&buffer, 0, 0, 9, 4, 2), 0);
+        assert_eq!(scroll_to_show(&buffer, 2, 1, 0, 4, 2), 1);
+    }
+
+    #[test]
+    fn test_line_taller_than_viewport() {
+        // Ten visual rows in a three-row viewport
+        let buffer = TextBuffer::from_lines(&["x", &"y".repeat(40)]);
+        let top = scroll_to_show(&buffer, 0, 1, 30, 4, 3);
+        assert_eq!(top, 1);
+        let hidden = hidden_rows(&buffer, top, 1, 30, 4, 3);
+        assert_eq!(hidden, 5);
+        // The cursor's visual row, 7, is the last one on screen
+        let (idx, _) = locate(&wrap_line(&buffer.line(1), 4), 30);
+        assert!(idx >= hidden && idx - hidden < 3);
+        assert_eq!(position_at(&buffer, top, 2 + hidden, 2, 4), (1, 30));
+
+        // Near the start of the line nothing is hidden
+        assert_eq!(hidden_rows(&buffer, 1, 1, 5, 4, 3), 0);
+        assert_eq!(hidden_rows(&buffer, 0, 1, 30, 4, 3), 0);
+    }
+}